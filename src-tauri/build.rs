@@ -68,7 +68,9 @@ fn main() {
         }
     }
 
-    tauri_build::build()
+    // The headless daemon (--no-default-features) has no Tauri context to generate
+    #[cfg(feature = "desktop")]
+    tauri_build::build();
 }
 
 fn get_binary_name(target: &str) -> String {
@@ -0,0 +1,274 @@
+//! The loopback HTTP API described in crate::control. Every route calls the same
+//! command the webview would, with the app's own state.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::sync::Arc;
+use tauri::async_runtime::block_on;
+use tauri::{AppHandle, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::cloudflare_manager::CloudflareManager;
+use crate::config::AppConfig;
+use crate::control::{
+    generate_token, is_authorized, openapi_document, write_control_file, ControlEndpoint,
+};
+use crate::ssh_manager::SshManager;
+use crate::state::AppState;
+use crate::types::{QuotaReport, TunnelList, TunnelStatus};
+
+/// Largest request body accepted (PATCH /v1/config)
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+/// Threads serving requests. Quota and model requests take a few seconds, so more
+/// than one; further requests wait for a free worker.
+const WORKERS: usize = 4;
+
+/// Serve the control API on a random loopback port and write control.json
+pub(super) fn start(app: AppHandle) -> Result<(), String> {
+    let server = Server::http("127.0.0.1:0")
+        .map_err(|e| format!("Failed to start control API: {}", e))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or("Control API isn't listening on a TCP port")?;
+    let token = generate_token();
+    write_control_file(&ControlEndpoint {
+        port,
+        token: token.clone(),
+        pid: std::process::id(),
+    })?;
+    println!("[ProxyPal] Control API listening on 127.0.0.1:{}", port);
+
+    let server = Arc::new(server);
+    for _ in 0..WORKERS {
+        let server = server.clone();
+        let app = app.clone();
+        let token = token.clone();
+        std::thread::spawn(move || {
+            while let Ok(request) = server.recv() {
+                handle(&app, &token, request);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle(app: &AppHandle, token: &str, mut request: Request) {
+    let authorized = request
+        .headers()
+        .iter()
+        .any(|h| h.field.equiv("Authorization") && is_authorized(h.value.as_str(), token));
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let method = request.method().clone();
+
+    let result = if method == Method::Get && path == "/v1/openapi.json" {
+        Ok(openapi_document())
+    } else if !authorized {
+        Err((401, "Missing or invalid token".to_string()))
+    } else {
+        let mut body = String::new();
+        match request.as_reader().take(MAX_BODY_BYTES).read_to_string(&mut body) {
+            Ok(_) => route(app, &method, path, query, &body),
+            Err(e) => Err((400, format!("Failed to read the request body: {}", e))),
+        }
+    };
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, json!({ "error": error })),
+    };
+
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes("Content-Type", "application/json").expect("valid header"),
+        );
+    let _ = request.respond(response);
+}
+
+type RouteResult = Result<Value, (u16, String)>;
+
+fn to_json<T: Serialize>(result: Result<T, String>) -> RouteResult {
+    result
+        .and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string()))
+        .map_err(|e| (500, e))
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn tunnels(app: &AppHandle) -> TunnelList {
+    let config = crate::config::load_config();
+    let ssh_manager = app.state::<SshManager>();
+    let cf_manager = app.state::<CloudflareManager>();
+    TunnelList {
+        ssh: config
+            .ssh_configs
+            .iter()
+            .map(|c| TunnelStatus {
+                id: c.id.clone(),
+                name: format!("{}@{}:{}", c.username, c.host, c.remote_port),
+                enabled: c.enabled,
+                status: ssh_manager.get_status(&c.id),
+            })
+            .collect(),
+        cloudflare: config
+            .cloudflare_configs
+            .iter()
+            .map(|c| TunnelStatus {
+                id: c.id.clone(),
+                name: c.name.clone(),
+                enabled: c.enabled,
+                status: cf_manager.get_status(&c.id),
+            })
+            .collect(),
+    }
+}
+
+fn route(app: &AppHandle, method: &Method, path: &str, query: &str, body: &str) -> RouteResult {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        (Method::Get, ["v1", "status"]) => to_json(Ok(super::get_proxy_status(app.state()))),
+        (Method::Post, ["v1", "proxy", "start"]) => {
+            to_json(block_on(super::start_proxy(app.clone(), app.state())))
+        }
+        (Method::Post, ["v1", "proxy", "stop"]) => {
+            to_json(block_on(super::stop_proxy(app.clone(), app.state())))
+        }
+        (Method::Get, ["v1", "config"]) => {
+            to_json(Ok(app.state::<AppState>().config.lock().unwrap().clone()))
+        }
+        (Method::Patch, ["v1", "config"]) => {
+            let patch: Value = serde_json::from_str(body)
+                .map_err(|e| (400, format!("Invalid JSON: {}", e)))?;
+            if !patch.is_object() {
+                return Err((400, "Expected a JSON object of the fields to change".to_string()));
+            }
+            let state = app.state::<AppState>();
+            let mut merged = serde_json::to_value(state.config.lock().unwrap().clone())
+                .map_err(|e| (500, e.to_string()))?;
+            crate::config::profiles::merge_json(&mut merged, &patch);
+            let config: AppConfig = serde_json::from_value(merged)
+                .map_err(|e| (400, format!("Patch does not fit the config: {}", e)))?;
+            let result = block_on(super::apply_config(app.clone(), state, config))
+                .map_err(|e| (400, e))?;
+            to_json(Ok(result))
+        }
+        (Method::Get, ["v1", "usage"]) => {
+            let window = query_param(query, "since")
+                .map(|since| crate::usage::parse_since(&since))
+                .transpose()
+                .map_err(|e| (400, e))?;
+            let stats = super::get_usage_stats(app.state()).map_err(|e| (500, e))?;
+            let now = chrono::Local::now().naive_local();
+            to_json(Ok(crate::usage::summarize_usage(stats, window, now)))
+        }
+        (Method::Get, ["v1", "history"]) => {
+            let window = query_param(query, "since")
+                .map(|since| crate::usage::parse_since(&since))
+                .transpose()
+                .map_err(|e| (400, e))?;
+            let limit = query_param(query, "limit")
+                .map(|limit| limit.parse::<usize>())
+                .transpose()
+                .map_err(|_| (400, "limit must be a number".to_string()))?;
+            let mut history = super::get_request_history();
+            if let Some(window) = window {
+                let start = (chrono::Utc::now() - window).timestamp_millis().max(0) as u64;
+                history.requests.retain(|r| r.timestamp >= start);
+            }
+            if let Some(limit) = limit {
+                let skip = history.requests.len().saturating_sub(limit);
+                history.requests.drain(..skip);
+            }
+            to_json(Ok(history))
+        }
+        (Method::Get, ["v1", "quota"]) => {
+            let (antigravity, codex, copilot, claude, kiro) = block_on(async {
+                tokio::join!(
+                    super::fetch_antigravity_quota(),
+                    super::fetch_codex_quota(),
+                    super::fetch_copilot_quota(),
+                    super::fetch_claude_quota(),
+                    super::fetch_kiro_quota(),
+                )
+            });
+            to_json(Ok(QuotaReport {
+                antigravity: antigravity.into(),
+                codex: codex.into(),
+                copilot: copilot.into(),
+                claude: claude.into(),
+                kiro: kiro.into(),
+            }))
+        }
+        (Method::Get, ["v1", "auth-files"]) => to_json(block_on(super::get_auth_files(app.state()))),
+        (Method::Get, ["v1", "models"]) => to_json(block_on(super::get_available_models(app.state()))),
+        (Method::Get, ["v1", "agents"]) => to_json(Ok(super::detect_cli_agents(app.state()))),
+        (Method::Post, ["v1", "agents", agent_id, "configure"]) => {
+            let state = app.state::<AppState>();
+            let models = block_on(super::get_available_models(state.clone()))
+                .map_err(|e| (500, e))?;
+            to_json(block_on(super::configure_cli_agent(state, agent_id.to_string(), models)))
+        }
+        (Method::Get, ["v1", "logs"]) => {
+            let lines = query_param(query, "lines")
+                .map(|lines| lines.parse::<u32>())
+                .transpose()
+                .map_err(|_| (400, "lines must be a number".to_string()))?;
+            to_json(block_on(super::get_logs(app.state(), lines)))
+        }
+        (Method::Get, ["v1", "logs", "tail"]) => {
+            let after = query_param(query, "after")
+                .map(|after| after.parse::<u64>())
+                .transpose()
+                .map_err(|_| (400, "after must be a number".to_string()))?;
+            let lines = query_param(query, "lines")
+                .map(|lines| lines.parse::<usize>())
+                .transpose()
+                .map_err(|_| (400, "lines must be a number".to_string()))?
+                .unwrap_or(crate::proxy::logs::DEFAULT_TAIL_LINES);
+            if !app.state::<AppState>().config.lock().unwrap().logging_to_file {
+                return Err((409, "The proxy doesn't log to a file, turn on logging to file".to_string()));
+            }
+            let path = crate::proxy::logs::main_log_path();
+            to_json(crate::proxy::logs::read_log_tail(&path, after, lines))
+        }
+        (Method::Get, ["v1", "processes", process, "output"]) => {
+            let lines = query_param(query, "lines")
+                .map(|lines| lines.parse::<usize>())
+                .transpose()
+                .map_err(|_| (400, "lines must be a number".to_string()))?
+                .unwrap_or(crate::process_output::DEFAULT_LINES);
+            crate::process_output::output(process, lines)
+                .map_err(|e| (404, e))
+                .and_then(|output| to_json(Ok(output)))
+        }
+        (Method::Get, ["v1", "tunnels"]) => to_json(Ok(tunnels(app))),
+        (Method::Post, ["v1", "tunnels", kind, id, action @ ("enable" | "disable")]) => {
+            let (id, enable) = (id.to_string(), *action == "enable");
+            let result = match *kind {
+                "ssh" => block_on(crate::commands::ssh::set_ssh_connection(
+                    app.clone(),
+                    app.state(),
+                    id,
+                    enable,
+                )),
+                "cloudflare" => block_on(crate::commands::cloudflare::set_cloudflare_connection(
+                    app.clone(),
+                    app.state(),
+                    id,
+                    enable,
+                )),
+                _ => return Err((404, format!("Unknown tunnel kind '{}'", kind))),
+            };
+            result.map_err(|e| (404, e))?;
+            to_json(Ok(tunnels(app)))
+        }
+        _ => Err((404, format!("No route for {} {}", method, path))),
+    }
+}
@@ -0,0 +1,75 @@
+//! Applying a port move from crate::proxy::ports to the running app: saving it,
+//! reconnecting the tunnels that moved along and re-pointing agents at the new endpoint.
+
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::cloudflare_manager::CloudflareManager;
+use crate::config::{save_config_to_file, AppConfig};
+use crate::proxy::custom_config::write_effective_config;
+use crate::proxy::ports::PortOwner;
+use crate::ssh_manager::SshManager;
+use crate::state::AppState;
+use crate::types::PortChange;
+
+/// Agents pointing at the proxy's current port. Call before saving a move.
+pub(super) fn configured_agents(state: State<'_, AppState>) -> Vec<String> {
+    super::detect_cli_agents(state)
+        .into_iter()
+        .filter(|a| a.configured)
+        .map(|a| a.id)
+        .collect()
+}
+
+/// Save `config` with `owner` moved and reconnect the tunnels that followed it
+pub(super) fn save_port_change(
+    app: &AppHandle,
+    state: &AppState,
+    config: &AppConfig,
+    owner: PortOwner,
+    change: &PortChange,
+) -> Result<(), String> {
+    crate::config::baseline::check_locked(config).map_err(|e| {
+        format!("Port {} is in use by another program and can't be changed: {}", change.from, e)
+    })?;
+    *state.config.lock().unwrap() = config.clone();
+    save_config_to_file(config)?;
+
+    // The running proxy routes copilot models to the bridge's port
+    if owner == PortOwner::Copilot && state.proxy_status.lock().unwrap().running {
+        write_effective_config(config)?;
+    }
+
+    let ssh_manager = app.state::<SshManager>();
+    for ssh in config.ssh_configs.iter().filter(|c| c.enabled && change.ssh_tunnels.contains(&c.id)) {
+        ssh_manager.connect(Arc::new(app.clone()), ssh.clone());
+    }
+    let cf_manager = app.state::<CloudflareManager>();
+    for cf in config
+        .cloudflare_configs
+        .iter()
+        .filter(|c| c.enabled && change.cloudflare_tunnels.contains(&c.id))
+    {
+        cf_manager.connect(Arc::new(app.clone()), cf.clone());
+    }
+    Ok(())
+}
+
+/// Re-point `agents` at the new endpoint, once the proxy serves it, and emit "port-changed"
+pub(super) async fn finish_port_change(
+    app: &AppHandle,
+    state: State<'_, AppState>,
+    mut change: PortChange,
+    agents: Vec<String>,
+) {
+    if !agents.is_empty() {
+        let models = super::get_available_models(state.clone()).await.unwrap_or_default();
+        for agent_id in agents {
+            match super::configure_cli_agent(state.clone(), agent_id.clone(), models.clone()).await {
+                Ok(_) => change.reconfigured_agents.push(agent_id),
+                Err(e) => change.agent_errors.push(format!("{}: {}", agent_id, e)),
+            }
+        }
+    }
+    let _ = app.emit("port-changed", change);
+}
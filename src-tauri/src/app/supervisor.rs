@@ -0,0 +1,174 @@
+//! Restarts the proxy when it exits without being stopped, following the policy in
+//! crate::proxy::supervisor, and reports each crash as a "proxy-crashed" event.
+
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::proxy::supervisor::RestartDecision;
+use crate::state::AppState;
+use crate::types::{ProxyCrash, ProxyRestart};
+
+/// Crashes kept for get_proxy_crashes
+const MAX_RECORDED_CRASHES: usize = 10;
+
+pub(super) fn describe_exit(code: Option<i32>, signal: Option<i32>) -> String {
+    match (code, signal) {
+        (_, Some(signal)) => format!("killed by signal {}", signal),
+        (Some(code), None) => format!("exited with code {}", code),
+        (None, None) => "exited".to_string(),
+    }
+}
+
+/// Handle the Terminated event of the sidecar started with `pid`
+pub(super) fn on_proxy_terminated(
+    app: &AppHandle,
+    pid: u32,
+    ran_for: Duration,
+    code: Option<i32>,
+    signal: Option<i32>,
+    stderr_tail: Vec<String>,
+) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+
+    // stop_proxy (and start_proxy, before replacing it) take the child out before
+    // killing it. Still being tracked means nobody asked it to stop.
+    let crashed = {
+        let mut process = state.proxy_process.lock().unwrap();
+        if process.as_ref().is_some_and(|child| child.pid() == pid) {
+            process.take();
+            true
+        } else {
+            false
+        }
+    };
+    if !crashed {
+        return;
+    }
+
+    let status = {
+        let mut status = state.proxy_status.lock().unwrap();
+        status.running = false;
+        status.clone()
+    };
+    let _ = app.emit("proxy-status-changed", status);
+
+    let message = describe_exit(code, signal);
+    if let Some(delay) = report_crash(app, &state, code, signal, message, stderr_tail, ran_for) {
+        schedule_restart(app, &state, delay, code);
+    }
+}
+
+/// Record and emit a crash; returns the delay before restarting, if the proxy should be
+fn report_crash(
+    app: &AppHandle,
+    state: &AppState,
+    code: Option<i32>,
+    signal: Option<i32>,
+    message: String,
+    stderr_tail: Vec<String>,
+    ran_for: Duration,
+) -> Option<Duration> {
+    let config = state.config.lock().unwrap().clone();
+    let mut supervisor = state.proxy_supervisor.lock().unwrap();
+    supervisor.policy.configure(&config);
+
+    let (restart_in, recent_exits, crash_loop) = if config.auto_restart_proxy {
+        match supervisor.policy.on_exit(Instant::now(), ran_for) {
+            RestartDecision::Restart { delay, exits } => (Some(delay), exits, false),
+            RestartDecision::CrashLoop { exits } => (None, exits, true),
+        }
+    } else {
+        (None, 1, false)
+    };
+
+    let crash = ProxyCrash {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        exit_code: code,
+        signal,
+        message,
+        stderr_tail,
+        recent_exits,
+        restart_in_ms: restart_in.map(|delay| delay.as_millis() as u64),
+        crash_loop,
+    };
+    match restart_in {
+        Some(delay) => eprintln!(
+            "[ProxyPal] Proxy {}, restarting in {}s",
+            crash.message,
+            delay.as_secs()
+        ),
+        None if crash_loop => eprintln!(
+            "[ProxyPal] Proxy {}; {} exits within {} minutes, not restarting it again",
+            crash.message, recent_exits, config.crash_loop_window_minutes
+        ),
+        None => eprintln!("[ProxyPal] Proxy {}", crash.message),
+    }
+    for line in &crash.stderr_tail {
+        eprintln!("[ProxyPal]   {}", line);
+    }
+
+    supervisor.crashes.push(crash.clone());
+    let excess = supervisor.crashes.len().saturating_sub(MAX_RECORDED_CRASHES);
+    supervisor.crashes.drain(..excess);
+    drop(supervisor);
+
+    let _ = app.emit("proxy-crashed", crash);
+    restart_in
+}
+
+fn schedule_restart(app: &AppHandle, state: &AppState, delay: Duration, exit_code: Option<i32>) {
+    let generation = {
+        let mut supervisor = state.proxy_supervisor.lock().unwrap();
+        supervisor.restart_generation += 1;
+        supervisor.restart_generation
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut delay = delay;
+        loop {
+            tokio::time::sleep(delay).await;
+            let state = app.state::<AppState>();
+            // Cancelled, or started by hand in the meantime
+            if state.proxy_supervisor.lock().unwrap().restart_generation != generation
+                || state.proxy_status.lock().unwrap().running
+                || state.proxy_process.lock().unwrap().is_some()
+            {
+                return;
+            }
+
+            match super::start_proxy(app.clone(), state.clone()).await {
+                Ok(status) => {
+                    println!("[ProxyPal] Proxy restarted after a crash");
+                    let recent_exits = {
+                        let supervisor = state.proxy_supervisor.lock().unwrap();
+                        supervisor.crashes.last().map_or(0, |crash| crash.recent_exits)
+                    };
+                    let _ = app.emit(
+                        "proxy-restarted",
+                        ProxyRestart {
+                            status,
+                            exit_code,
+                            recent_exits,
+                        },
+                    );
+                    return;
+                }
+                Err(e) => {
+                    // A failed start counts as another exit
+                    let message = format!("failed to restart: {}", e);
+                    match report_crash(&app, &state, None, None, message, Vec::new(), Duration::ZERO) {
+                        Some(next) => delay = next,
+                        None => return,
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Drop a restart that is still waiting out its delay
+pub(super) fn cancel_pending_restart(state: &AppState) {
+    state.proxy_supervisor.lock().unwrap().restart_generation += 1;
+}
@@ -0,0 +1,4 @@
+// Headless ProxyPal for machines without a display, see daemon.rs
+fn main() {
+    std::process::exit(proxypal_lib::daemon::main())
+}
@@ -0,0 +1,420 @@
+//! `proxypal <command>`: scripting the running desktop app from a terminal.
+//!
+//! Commands go to the app's loopback control API (see crate::control), so they act on
+//! the same proxy, config and usage data as the window. Without a command the binary
+//! starts the app as usual.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::control::{read_control_file, ControlEndpoint};
+use crate::types::{AgentStatus, AvailableModel, LogChunk, LogEntry, ProxyStatus, QuotaReport, UsageSummary};
+
+const COMMANDS: &[&str] = &[
+    "status", "start", "stop", "usage", "quota", "models", "agents", "logs", "help",
+];
+
+/// How often `logs --follow` asks for new lines
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+const USAGE: &str = "Usage: proxypal <command> [options]
+
+Controls the running ProxyPal app. Without a command, starts the app.
+
+Commands:
+  status                       Show whether the proxy is running
+  start                        Start the proxy
+  stop                         Stop the proxy
+  usage [--since 7d]           Requests and tokens, optionally over the last
+                               30m, 12h, 7d, 2w, ...
+  quota                        Remaining quota of every connected account
+  models                       Models the proxy serves
+  agents                       Detected CLI agents
+  agents configure <agent>     Point an agent (e.g. claude-code) at the proxy
+  logs [--lines N] [--follow]  Proxy logs; --follow keeps printing new lines
+
+Options:
+  --json                       Print the raw JSON response
+  -h, --help                   Show this help";
+
+/// Run a CLI command, or None when `args` (without the program name) aren't one
+/// and the app should start instead
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first()?;
+    let is_help = command == "-h" || command == "--help";
+    if !is_help && !COMMANDS.contains(&command.as_str()) {
+        return None;
+    }
+    attach_console();
+    if is_help {
+        println!("{}", USAGE);
+        return Some(0);
+    }
+
+    match execute(command, &args[1..]) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("proxypal: {}", e);
+            Some(1)
+        }
+    }
+}
+
+/// Release builds on Windows are GUI programs without a console of their own, so
+/// output would go nowhere. Borrow the console of the terminal that ran us instead.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails when there is no parent console (or we already have one), which is fine
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+struct Options {
+    json: bool,
+    since: Option<String>,
+    lines: Option<u32>,
+    follow: bool,
+    positional: Vec<String>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        json: false,
+        since: None,
+        lines: None,
+        follow: false,
+        positional: Vec::new(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--follow" | "-f" => options.follow = true,
+            "--since" => options.since = Some(value("--since")?),
+            "--lines" | "-n" => {
+                options.lines = Some(
+                    value("--lines")?
+                        .parse()
+                        .map_err(|_| "--lines must be a number".to_string())?,
+                )
+            }
+            other if other.starts_with('-') => return Err(format!("Unknown option {}", other)),
+            other => options.positional.push(other.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+fn execute(command: &str, args: &[String]) -> Result<(), String> {
+    if command == "help" || args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let options = parse_options(args)?;
+    let client = Client::connect()?;
+
+    match (command, options.positional.as_slice()) {
+        ("status", []) => {
+            let status = client.get_value("/v1/status")?;
+            print_or(&options, status, print_status)
+        }
+        ("start", []) => {
+            let status = client.post_value("/v1/proxy/start")?;
+            print_or(&options, status, print_status)
+        }
+        ("stop", []) => {
+            let status = client.post_value("/v1/proxy/stop")?;
+            print_or(&options, status, print_status)
+        }
+        ("usage", []) => {
+            let path = match &options.since {
+                Some(since) => format!("/v1/usage?since={}", encode(since)),
+                None => "/v1/usage".to_string(),
+            };
+            let usage = client.get_value(&path)?;
+            print_or(&options, usage, print_usage)
+        }
+        ("quota", []) => {
+            let quota = client.get_value("/v1/quota")?;
+            print_or(&options, quota, print_quota)
+        }
+        ("models", []) => {
+            let models = client.get_value("/v1/models")?;
+            print_or(&options, models, print_models)
+        }
+        ("agents", []) => {
+            let agents = client.get_value("/v1/agents")?;
+            print_or(&options, agents, print_agents)
+        }
+        ("agents", [action, agent_id]) if action == "configure" => {
+            let result = client.post_value(&format!("/v1/agents/{}/configure", encode(agent_id)))?;
+            print_or(&options, result, print_configure_result)
+        }
+        ("logs", []) => follow_logs(&client, &options),
+        _ => Err(format!("Unexpected arguments for '{}', see proxypal --help", command)),
+    }
+}
+
+fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+struct Client {
+    endpoint: ControlEndpoint,
+    http: reqwest::blocking::Client,
+}
+
+impl Client {
+    fn connect() -> Result<Self, String> {
+        let endpoint = read_control_file()?;
+        let http = reqwest::blocking::Client::builder()
+            .no_proxy()
+            // Quota checks call every provider, give them time
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { endpoint, http })
+    }
+
+    fn send(&self, request: reqwest::blocking::RequestBuilder) -> Result<Value, String> {
+        let response = request
+            .bearer_auth(&self.endpoint.token)
+            .send()
+            .map_err(|e| {
+                if e.is_connect() {
+                    "ProxyPal is not running (the control API didn't answer)".to_string()
+                } else {
+                    e.to_string()
+                }
+            })?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .map_err(|e| format!("Invalid response from ProxyPal: {}", e))?;
+        if !status.is_success() {
+            let error = body["error"].as_str().unwrap_or("request failed");
+            return Err(error.to_string());
+        }
+        Ok(body)
+    }
+
+    fn get_value(&self, path: &str) -> Result<Value, String> {
+        self.send(self.http.get(self.endpoint.url(path)))
+    }
+
+    fn post_value(&self, path: &str) -> Result<Value, String> {
+        self.send(self.http.post(self.endpoint.url(path)))
+    }
+}
+
+/// Print `value` as JSON with --json, else decode it and print it for people
+fn print_or<T: DeserializeOwned>(
+    options: &Options,
+    value: Value,
+    print: fn(T),
+) -> Result<(), String> {
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    let value = serde_json::from_value(value)
+        .map_err(|e| format!("Unexpected response from ProxyPal: {}", e))?;
+    print(value);
+    Ok(())
+}
+
+fn print_status(status: ProxyStatus) {
+    if status.running {
+        println!("Proxy running on port {}", status.port);
+        println!("Endpoint: {}", status.endpoint);
+    } else {
+        println!("Proxy stopped");
+    }
+}
+
+fn print_usage(usage: UsageSummary) {
+    match &usage.since {
+        Some(since) => println!("Since {}:", since),
+        None => println!("All time:"),
+    }
+    println!("  Requests: {}", usage.requests);
+    println!("  Tokens:   {}", usage.tokens);
+    for point in &usage.requests_series {
+        let tokens = usage
+            .tokens_series
+            .iter()
+            .find(|t| t.label == point.label)
+            .map_or(0, |t| t.value);
+        println!("  {:<13} {:>8} requests {:>12} tokens", point.label, point.value, tokens);
+    }
+    if usage.stats.total_requests > 0 {
+        println!(
+            "All-time success rate: {:.1}%",
+            usage.stats.success_count as f64 * 100.0 / usage.stats.total_requests as f64
+        );
+    }
+}
+
+fn print_provider_error(name: &str, error: &Option<String>) {
+    if let Some(error) = error {
+        println!("{}: {}", name, error);
+    }
+}
+
+/// First line of an account: "Provider account (plan)", or the account's own error
+fn account_line(provider: &str, account: &str, plan: &str, error: &Option<String>) -> Option<String> {
+    let plan = if plan.is_empty() { String::new() } else { format!(" ({})", plan) };
+    match error {
+        Some(error) => {
+            println!("{} {}{}: {}", provider, account, plan, error);
+            None
+        }
+        None => Some(format!("{} {}{}", provider, account, plan)),
+    }
+}
+
+fn print_quota(quota: QuotaReport) {
+    for account in &quota.antigravity.accounts {
+        if let Some(line) = account_line("Antigravity", &account.account_email, "", &account.error) {
+            println!("{}", line);
+            for model in &account.quotas {
+                println!("  {:<40} {:>4.0}% left", model.display_name, model.remaining_percent);
+            }
+        }
+    }
+    print_provider_error("Antigravity", &quota.antigravity.error);
+    for account in &quota.codex.accounts {
+        if let Some(line) = account_line("Codex", &account.account_email, &account.plan_type, &account.error) {
+            println!(
+                "{}: {:.0}% used (short window), {:.0}% used (weekly)",
+                line, account.primary_used_percent, account.secondary_used_percent
+            );
+        }
+    }
+    print_provider_error("Codex", &quota.codex.error);
+    for account in &quota.copilot.accounts {
+        if let Some(line) = account_line("Copilot", &account.account_login, &account.plan, &account.error) {
+            println!(
+                "{}: {:.0}% premium left, {:.0}% chat left",
+                line, account.premium_interactions_percent, account.chat_percent
+            );
+        }
+    }
+    print_provider_error("Copilot", &quota.copilot.error);
+    for account in &quota.claude.accounts {
+        if let Some(line) = account_line("Claude", &account.account_email, &account.plan, &account.error) {
+            println!(
+                "{}: {:.0}% used (5h), {:.0}% used (7d)",
+                line, account.five_hour_percent, account.seven_day_percent
+            );
+        }
+    }
+    print_provider_error("Claude", &quota.claude.error);
+    for account in &quota.kiro.accounts {
+        if let Some(line) = account_line("Kiro", &account.account_email, &account.plan, &account.error) {
+            println!("{}: {:.0}% used", line, account.used_percent);
+        }
+    }
+    print_provider_error("Kiro", &quota.kiro.error);
+}
+
+fn print_models(models: Vec<AvailableModel>) {
+    if models.is_empty() {
+        println!("No models (is the proxy running?)");
+    }
+    for model in models {
+        println!("{:<50} {}", model.id, model.owned_by);
+    }
+}
+
+fn print_agents(agents: Vec<AgentStatus>) {
+    for agent in agents {
+        let state = match (agent.installed, agent.configured) {
+            (true, true) => "configured",
+            (true, false) => "installed",
+            _ => "not installed",
+        };
+        println!("{:<16} {:<28} {}", agent.id, agent.name, state);
+    }
+}
+
+fn print_configure_result(result: Value) {
+    if let Some(path) = result["configPath"].as_str() {
+        println!("Wrote {}", path);
+    }
+    if let Some(shell_config) = result["shellConfig"].as_str() {
+        println!("{}", shell_config);
+    }
+    if let Some(instructions) = result["instructions"].as_str() {
+        println!("{}", instructions);
+    }
+}
+
+fn print_log_entry(entry: &LogEntry, json: bool) {
+    if json {
+        if let Ok(line) = serde_json::to_string(entry) {
+            println!("{}", line);
+        }
+    } else {
+        println!("{} [{}] {}", entry.timestamp, entry.level, entry.message);
+    }
+}
+
+fn fetch_logs(client: &Client, path: &str) -> Result<LogChunk, String> {
+    serde_json::from_value(client.get_value(path)?)
+        .map_err(|e| format!("Unexpected response from ProxyPal: {}", e))
+}
+
+/// Print the last lines, then with --follow keep asking for the lines written after
+/// the cursor of the previous answer. Failed polls are reported once and retried.
+fn follow_logs(client: &Client, options: &Options) -> Result<(), String> {
+    let chunk = fetch_logs(client, &format!("/v1/logs/tail?lines={}", options.lines.unwrap_or(100)))?;
+    for entry in &chunk.entries {
+        print_log_entry(entry, options.json);
+    }
+    if !options.follow {
+        return Ok(());
+    }
+
+    let mut cursor = chunk.cursor;
+    let mut failing = false;
+    loop {
+        std::thread::sleep(FOLLOW_INTERVAL);
+        let chunk = match fetch_logs(client, &format!("/v1/logs/tail?after={}", cursor)) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                if !failing {
+                    eprintln!("proxypal: {} (retrying)", e);
+                    failing = true;
+                }
+                continue;
+            }
+        };
+        if failing {
+            eprintln!("proxypal: reading logs again");
+            failing = false;
+        }
+        if chunk.skipped > 0 {
+            eprintln!("proxypal: {} lines skipped, more were written than fit in one read", chunk.skipped);
+        }
+        for entry in &chunk.entries {
+            print_log_entry(entry, options.json);
+        }
+        cursor = chunk.cursor;
+    }
+}
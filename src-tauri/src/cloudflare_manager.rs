@@ -2,10 +2,12 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
 use tokio::process::Command;
 use tokio::sync::Notify;
 
+use crate::events::{self, Events};
+use crate::process_output;
+use crate::types::OutputStream;
 use crate::types::cloudflare::CloudflareConfig;
 
 /// Find cloudflared binary path - checks common installation locations
@@ -85,7 +87,7 @@ struct CloudflareStatusUpdate {
 struct RunningTunnel {
     notify_stop: Arc<Notify>,
     #[allow(dead_code)]
-    handle: tauri::async_runtime::JoinHandle<()>,
+    handle: events::JoinHandle<()>,
 }
 
 pub struct CloudflareManager {
@@ -99,7 +101,7 @@ impl CloudflareManager {
         }
     }
 
-    pub fn connect(&self, app: AppHandle, config: CloudflareConfig) {
+    pub fn connect(&self, sink: Events, config: CloudflareConfig) {
         let tunnels = self.tunnels.clone();
         let config_id = config.id.clone();
         
@@ -109,9 +111,10 @@ impl CloudflareManager {
         let notify_stop = Arc::new(Notify::new());
         let notify_clone = notify_stop.clone();
         let config_clone = config.clone();
+        let output_sink = sink.clone();
         
         let emit_status = move |status: &str, msg: Option<String>, url: Option<String>| {
-            let _ = app.emit("cloudflare-status-changed", CloudflareStatusUpdate {
+            sink.emit("cloudflare-status-changed", CloudflareStatusUpdate {
                 id: config_clone.id.clone(),
                 status: status.to_string(),
                 message: msg,
@@ -121,7 +124,7 @@ impl CloudflareManager {
 
         let emit_status_clone = emit_status.clone();
 
-        let handle = tauri::async_runtime::spawn(async move {
+        let handle = events::spawn(async move {
             emit_status_clone("connecting", Some("Starting tunnel...".into()), None);
             
             // Find cloudflared binary - check common installation paths
@@ -176,15 +179,18 @@ impl CloudflareManager {
 
                 match cmd.spawn() {
                     Ok(mut child) => {
+                        process_output::start_run(process_output::CLOUDFLARED, Some(&config.id));
                         emit_status_clone("connecting", Some("Authenticating...".into()), None);
                         
                         let stderr = child.stderr.take();
                         let emit_output = emit_status_clone.clone();
                         let is_connected = Arc::new(AtomicBool::new(false));
                         let is_connected_clone = is_connected.clone();
+                        let output_sink = output_sink.clone();
+                        let source = config.id.clone();
                         
                         // Spawn a task to read stderr and detect connection status
-                        let stderr_reader = tauri::async_runtime::spawn(async move {
+                        let stderr_reader = events::spawn(async move {
                             use tokio::io::{AsyncBufReadExt, BufReader};
                             
                             let mut detected_url: Option<String> = None;
@@ -194,6 +200,7 @@ impl CloudflareManager {
                                 let mut lines = reader.lines();
                                 
                                 while let Ok(Some(line)) = lines.next_line().await {
+                                    process_output::record(Some(&*output_sink), process_output::CLOUDFLARED, Some(&source), OutputStream::Stderr, &line);
                                     let line_lower = line.to_lowercase();
                                     
                                     // Debug: log all lines for troubleshooting
@@ -237,6 +244,7 @@ impl CloudflareManager {
                         tokio::select! {
                             exit_status = child.wait() => {
                                 stderr_reader.abort();
+                                process_output::save_last_run(process_output::CLOUDFLARED, Some(&config.id));
                                 match exit_status {
                                     Ok(status) => {
                                         if status.success() {
@@ -268,6 +276,7 @@ impl CloudflareManager {
                             _ = notify_clone.notified() => {
                                 let _ = child.kill().await;
                                 stderr_reader.abort();
+                                process_output::save_last_run(process_output::CLOUDFLARED, Some(&config.id));
                                 emit_status_clone("disconnected", Some("Tunnel stopped".into()), None);
                                 break;
                             }
@@ -329,7 +338,7 @@ impl CloudflareManager {
         tunnels.clear();
     }
 
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
     pub fn get_status(&self, id: &str) -> String {
        let tunnels = self.tunnels.lock().unwrap();
        if tunnels.contains_key(id) {
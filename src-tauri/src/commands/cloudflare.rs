@@ -51,7 +51,7 @@ pub async fn set_cloudflare_connection(
         save_config_to_file(&config)?;
         
         if enable {
-            state.connect(app, target_config);
+            state.connect(std::sync::Arc::new(app), target_config);
         } else {
             state.disconnect(&id);
         }
@@ -3,7 +3,15 @@
 use std::fs;
 use tauri::State;
 use crate::config::{AppConfig, save_config_to_file};
+use crate::config::validation;
+use crate::proxy::custom_config::{
+    annotate_effective_config, build_effective_config, render_effective_config,
+};
 use crate::state::AppState;
+use crate::types::{
+    BundlePreview, ConfigBundle, ConfigLoadStatus, ConfigSchemas, ConfigSources, ConfigValidation,
+    PayloadRule, ProxyConfigPreview,
+};
 
 #[tauri::command]
 pub fn get_config(state: State<AppState>) -> AppConfig {
@@ -29,14 +37,26 @@ pub fn save_config(state: State<AppState>, config: AppConfig) -> Result<(), Stri
         }
     }
 
+    let validation = validation::validate_config(&config);
+    if !validation.valid {
+        return Err(validation.error_summary());
+    }
+    crate::config::baseline::check_locked(&config)?;
+
     let mut current_config = state.config.lock().unwrap();
     *current_config = config.clone();
     save_config_to_file(&config)?;
+    crate::process_output::set_persist(config.persist_process_output);
 
     eprintln!("[ProxyPal Debug] Config saved successfully");
     Ok(())
 }
 
+#[tauri::command]
+pub fn validate_config(config: AppConfig) -> ConfigValidation {
+    validation::validate_config(&config)
+}
+
 #[allow(dead_code)]
 fn update_proxy_config_yaml(app_config: &AppConfig) -> Result<(), String> {
     let config_dir = dirs::config_dir()
@@ -98,6 +118,8 @@ pub fn save_config_yaml(yaml: String) -> Result<(), String> {
         .join("proxypal");
     fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
     
+    crate::config::schema::validate_proxy_config_yaml(&yaml)?;
+
     // Save directly to main config file
     // Note: This will be overwritten on next proxy restart
     let config_path = config_dir.join("proxy-config.yaml");
@@ -105,10 +127,37 @@ pub fn save_config_yaml(yaml: String) -> Result<(), String> {
         .map_err(|e| format!("Failed to save config YAML: {}", e))
 }
 
+/// JSON Schemas of config.json and proxy-config.yaml, also (re)written next to config.json
+#[tauri::command]
+pub fn get_config_schema() -> Result<ConfigSchemas, String> {
+    crate::config::schema::write_schema_files()
+}
+
+/// Show the proxy-config.yaml that the next start would write, with the lines
+/// contributed by proxy-config-custom.yaml marked
+#[tauri::command]
+pub fn preview_proxy_config(state: State<AppState>) -> Result<ProxyConfigPreview, String> {
+    let config = state.config.lock().unwrap().clone();
+    let proxy_url = crate::proxy::upstream::effective_proxy_url(&config);
+    let effective = build_effective_config(&config, Some(proxy_url))?;
+
+    Ok(ProxyConfigPreview {
+        yaml: render_effective_config(&effective)?,
+        lines: annotate_effective_config(&effective),
+        overrides: effective.custom_paths.iter().map(|p| p.to_string()).collect(),
+        custom_config_path: crate::config::get_custom_proxy_config_path()
+            .to_string_lossy()
+            .to_string(),
+    })
+}
+
 #[tauri::command]
 pub fn reload_config(state: State<AppState>) -> Result<AppConfig, String> {
     // Reload config from disk
     let fresh_config = crate::config::load_config();
+    if let Some(error) = crate::config::get_config_load_status().error {
+        return Err(error);
+    }
     
     // Update the in-memory state
     let mut current_config = state.config.lock().unwrap();
@@ -117,3 +166,59 @@ pub fn reload_config(state: State<AppState>) -> Result<AppConfig, String> {
     eprintln!("[ProxyPal Debug] Config reloaded from disk");
     Ok(fresh_config)
 }
+
+/// Result of the last config.json load: migrations, reset fields, or why it failed
+#[tauri::command]
+pub fn get_config_load_status() -> ConfigLoadStatus {
+    crate::config::get_config_load_status()
+}
+
+/// Give up on an unreadable config.json (a copy was kept) and save the config in use
+#[tauri::command]
+pub fn discard_corrupt_config(state: State<AppState>) -> Result<AppConfig, String> {
+    crate::config::clear_config_load_error();
+    let config = state.config.lock().unwrap().clone();
+    save_config_to_file(&config)?;
+    eprintln!("[ProxyPal] Replaced unreadable config.json with the current settings");
+    Ok(config)
+}
+
+/// Payload rules rendered into proxy-config.yaml, in order
+#[tauri::command]
+pub fn list_payload_rules(state: State<AppState>) -> Vec<PayloadRule> {
+    state.config.lock().unwrap().payload_rules.clone()
+}
+
+/// Where each config value comes from (default, organization baseline, user) and
+/// which fields the baseline locks
+#[tauri::command]
+pub fn get_config_sources(state: State<AppState>) -> Result<ConfigSources, String> {
+    let config = state.config.lock().unwrap().clone();
+    crate::config::baseline::config_sources(&config)
+}
+
+/// Current config as JSON for backup or sharing. Secrets are exported in plaintext
+/// (the encryption key never leaves this install) or blanked out.
+#[tauri::command]
+pub fn export_config(state: State<AppState>, include_secrets: bool) -> Result<serde_json::Value, String> {
+    let config = state.config.lock().unwrap().clone();
+    let mut value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    if !include_secrets {
+        crate::config::secrets::strip_secrets(&mut value);
+    }
+    Ok(value)
+}
+
+/// Shareable bundle of providers, mappings, routing and tunnel settings for a team
+#[tauri::command]
+pub fn export_config_bundle(state: State<AppState>, include_secrets: bool) -> Result<ConfigBundle, String> {
+    let config = state.config.lock().unwrap().clone();
+    crate::config::bundle::export_bundle(&config, include_secrets)
+}
+
+/// What importing a bundle would add and change, per section
+#[tauri::command]
+pub fn preview_config_bundle(state: State<AppState>, bundle: ConfigBundle) -> Result<BundlePreview, String> {
+    let config = state.config.lock().unwrap().clone();
+    crate::config::bundle::preview_bundle(&config, &bundle)
+}
@@ -3,4 +3,7 @@
 pub mod config;
 pub mod ssh;
 pub mod cloudflare;
+pub mod profiles;
 pub mod proxy;
+pub mod sidecars;
+pub mod snapshots;
@@ -0,0 +1,39 @@
+//! Configuration profile commands for Tauri IPC.
+//! Activation lives in app.rs next to apply_config, since it restarts/reloads the proxy.
+
+use tauri::State;
+use crate::config::profiles;
+use crate::state::AppState;
+use crate::types::{ConfigProfile, ProfileSummary};
+
+#[tauri::command]
+pub fn list_profiles() -> Result<Vec<ProfileSummary>, String> {
+    profiles::list_profiles()
+}
+
+#[tauri::command]
+pub fn get_profile(name: String) -> Result<ConfigProfile, String> {
+    profiles::load_profile(&name)
+}
+
+/// Create a profile from `overlay` (a partial AppConfig), or from the whole current config
+#[tauri::command]
+pub fn create_profile(
+    state: State<AppState>,
+    name: String,
+    description: Option<String>,
+    overlay: Option<serde_json::Value>,
+) -> Result<ConfigProfile, String> {
+    let current = state.config.lock().unwrap().clone();
+    profiles::create_profile(&name, description.unwrap_or_default(), overlay, &current)
+}
+
+#[tauri::command]
+pub fn clone_profile(source: String, name: String) -> Result<ConfigProfile, String> {
+    profiles::clone_profile(&source, &name)
+}
+
+#[tauri::command]
+pub fn delete_profile(name: String) -> Result<(), String> {
+    profiles::delete_profile(&name)
+}
@@ -0,0 +1,32 @@
+//! Commands for the CLIProxyAPI versions installed next to the bundled one.
+//! They take effect on the next proxy start.
+
+use crate::proxy::sidecars;
+use crate::types::{InstalledSidecar, SidecarInventory};
+
+#[tauri::command]
+pub fn list_sidecars() -> SidecarInventory {
+    sidecars::load_inventory()
+}
+
+/// Install from a file path or https URL, which needs `sha256`. `version` is needed when
+/// the file name doesn't carry it.
+#[tauri::command]
+pub async fn install_sidecar(
+    source: String,
+    sha256: Option<String>,
+    version: Option<String>,
+) -> Result<InstalledSidecar, String> {
+    sidecars::install(&source, sha256.as_deref(), version.as_deref()).await
+}
+
+/// Launch `version` from the next start on, or the bundled binary when omitted
+#[tauri::command]
+pub fn select_sidecar(version: Option<String>) -> Result<SidecarInventory, String> {
+    sidecars::select(version.as_deref())
+}
+
+#[tauri::command]
+pub fn remove_sidecar(version: String) -> Result<SidecarInventory, String> {
+    sidecars::remove(&version)
+}
@@ -0,0 +1,30 @@
+//! Config snapshot commands for Tauri IPC.
+//! Restoring lives in app.rs next to apply_config, since it reloads the proxy.
+
+use tauri::State;
+use crate::config::snapshots;
+use crate::state::AppState;
+use crate::types::{ConfigFieldDiff, ConfigSnapshot};
+
+#[tauri::command]
+pub fn list_config_snapshots() -> Result<Vec<ConfigSnapshot>, String> {
+    snapshots::list_snapshots()
+}
+
+/// Differences from snapshot `from` to snapshot `to`, or to the current config when `to` is omitted
+#[tauri::command]
+pub fn diff_config_snapshots(
+    state: State<AppState>,
+    from: String,
+    to: Option<String>,
+) -> Result<Vec<ConfigFieldDiff>, String> {
+    let before = snapshots::load_snapshot_value(&from)?;
+    let after = match to {
+        Some(id) => snapshots::load_snapshot_value(&id)?,
+        None => {
+            let config = state.config.lock().unwrap().clone();
+            crate::config::baseline::to_user_layer(&config)?
+        }
+    };
+    Ok(snapshots::diff_values(&before, &after))
+}
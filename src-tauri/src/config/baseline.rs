@@ -0,0 +1,419 @@
+//! Organization baseline: a read-only file shipped by an admin or a provisioning tool.
+//!
+//! Config layers, lowest first: built-in defaults, the baseline's `config`, the user's
+//! config.json, then the baseline's `locked` fields, which the user can't change.
+//! Only the user's own layer is written back to config.json, so baseline updates
+//! reach every field the user hasn't changed.
+//!
+//! ```json
+//! {
+//!   "config": { "routingStrategy": "fill-first", "proxyUrl": "http://proxy.corp:3128" },
+//!   "locked": ["proxyUrl"]
+//! }
+//! ```
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::AppConfig;
+use crate::types::{ConfigOverride, ConfigSources, ConfigValueSource};
+
+/// Overrides the location of the baseline file
+pub const BASELINE_PATH_ENV: &str = "PROXYPAL_BASELINE_CONFIG";
+
+/// Fields the baseline may not set or lock
+const UNMANAGED_FIELDS: &[&str] = &["configVersion", "ampOpenaiProvider"];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Baseline {
+    /// camelCase AppConfig fields
+    #[serde(default)]
+    pub config: Map<String, Value>,
+    /// Fields the user can't change. A locked field the baseline doesn't set keeps its default.
+    #[serde(default)]
+    pub locked: Vec<String>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// Where provisioning tools drop the baseline, unless PROXYPAL_BASELINE_CONFIG is set
+pub fn get_baseline_path() -> PathBuf {
+    // Next to the test's config, so tests never see each other's or the machine's baseline
+    #[cfg(test)]
+    if let Some(dir) = super::TEST_CONFIG_DIR.with(|d| d.borrow().clone()) {
+        return dir.join("baseline.json");
+    }
+    if let Some(path) = std::env::var_os(BASELINE_PATH_ENV).filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/ProxyPal/baseline.json")
+    }
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("C:\\ProgramData"))
+            .join("ProxyPal")
+            .join("baseline.json")
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        PathBuf::from("/etc/proxypal/baseline.json")
+    }
+}
+
+fn default_fields() -> Map<String, Value> {
+    match serde_json::to_value(AppConfig::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Read the baseline, if there is one. Unknown fields are dropped and reported.
+pub fn load_baseline() -> Result<Option<(Baseline, Vec<String>)>, String> {
+    let path = get_baseline_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read baseline {}: {}", path.display(), e))?;
+    let mut baseline: Baseline = serde_json::from_str(&data)
+        .map_err(|e| format!("Baseline {} is invalid: {}", path.display(), e))?;
+    baseline.path = path;
+
+    let known = default_fields();
+    let is_managed = |field: &str| known.contains_key(field) && !UNMANAGED_FIELDS.contains(&field);
+    let mut warnings = Vec::new();
+    baseline.config.retain(|field, _| {
+        let keep = is_managed(field);
+        if !keep {
+            warnings.push(format!("Baseline: ignoring unknown field \"{}\"", field));
+        }
+        keep
+    });
+    baseline.locked.retain(|field| {
+        let keep = is_managed(field);
+        if !keep {
+            warnings.push(format!("Baseline: cannot lock unknown field \"{}\"", field));
+        }
+        keep
+    });
+    Ok(Some((baseline, warnings)))
+}
+
+/// load_baseline for save paths, where a broken baseline must not block saving
+fn current_baseline() -> Option<Baseline> {
+    match load_baseline() {
+        Ok(baseline) => baseline.map(|(baseline, _)| baseline),
+        Err(e) => {
+            eprintln!("[ProxyPal] {}", e);
+            None
+        }
+    }
+}
+
+impl Baseline {
+    fn is_locked(&self, field: &str) -> bool {
+        self.locked.iter().any(|f| f == field)
+    }
+
+    /// The user's config.json layered on top of the baseline, with locked fields enforced
+    pub fn layer(&self, user: Map<String, Value>) -> Map<String, Value> {
+        let mut merged = self.config.clone();
+        for (field, value) in user {
+            if !self.is_locked(&field) {
+                merged.insert(field, value);
+            }
+        }
+        merged
+    }
+
+    /// The part of a full config that belongs in config.json: locked fields and values
+    /// equal to the baseline's are left to the baseline
+    pub fn user_layer(&self, config: &mut Map<String, Value>) {
+        config.retain(|field, value| !self.is_locked(field) && self.config.get(field) != Some(value));
+    }
+
+    /// Err naming the first locked field that `config` changes
+    pub fn check_locked(&self, config: &Map<String, Value>) -> Result<(), String> {
+        let defaults = default_fields();
+        for field in &self.locked {
+            let expected = self.config.get(field).or_else(|| defaults.get(field));
+            if config.get(field) != expected {
+                return Err(format!(
+                    "{} is locked by your organization's baseline ({})",
+                    field,
+                    self.path.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn config_map(config: &AppConfig) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(config).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map),
+        _ => Err("Failed to serialize config".to_string()),
+    }
+}
+
+/// Err if `config` changes a field the baseline locks
+pub fn check_locked(config: &AppConfig) -> Result<(), String> {
+    match current_baseline() {
+        Some(baseline) => baseline.check_locked(&config_map(config)?),
+        None => Ok(()),
+    }
+}
+
+/// Reduce a full config document to what config.json should hold
+pub fn to_user_layer(config: &AppConfig) -> Result<Value, String> {
+    let mut map = config_map(config)?;
+    super::overrides::restore_overridden(&mut map);
+    if let Some(baseline) = current_baseline() {
+        baseline.check_locked(&map)?;
+        baseline.user_layer(&mut map);
+    }
+    Ok(Value::Object(map))
+}
+
+/// A user layer (config.json, a snapshot) on top of the current baseline
+pub fn with_baseline(user: Value) -> Value {
+    match (current_baseline(), user) {
+        (Some(baseline), Value::Object(user)) => Value::Object(baseline.layer(user)),
+        (_, user) => user,
+    }
+}
+
+/// Where each top-level field of the effective config comes from, overrides included
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn config_sources(config: &AppConfig) -> Result<ConfigSources, String> {
+    let (baseline, baseline_error) = match load_baseline() {
+        Ok(baseline) => (baseline.map(|(baseline, _)| baseline), None),
+        Err(e) => (None, Some(e)),
+    };
+    let overrides = super::overrides::active_overrides();
+    let fields = field_sources(config_map(config)?, baseline.as_ref(), &overrides);
+
+    Ok(ConfigSources {
+        baseline_path: baseline
+            .as_ref()
+            .map(|b| b.path.to_string_lossy().to_string()),
+        baseline_error,
+        locked: baseline.map(|b| b.locked).unwrap_or_default(),
+        overrides,
+        fields,
+    })
+}
+
+/// Source of each field: Locked, then Override, then Baseline or User by whether the
+/// value still equals the baseline's, then Default or User the same way
+fn field_sources(
+    config: Map<String, Value>,
+    baseline: Option<&Baseline>,
+    overrides: &[ConfigOverride],
+) -> BTreeMap<String, ConfigValueSource> {
+    let defaults = default_fields();
+    let mut fields = BTreeMap::new();
+    for (field, value) in config {
+        let baseline_value = baseline.and_then(|b| b.config.get(&field));
+        let overridden = overrides
+            .iter()
+            .any(|o| o.field.split('.').next() == Some(field.as_str()));
+        let source = if baseline.is_some_and(|b| b.is_locked(&field)) {
+            ConfigValueSource::Locked
+        } else if overridden {
+            ConfigValueSource::Override
+        } else if baseline_value.is_some() {
+            if baseline_value == Some(&value) {
+                ConfigValueSource::Baseline
+            } else {
+                ConfigValueSource::User
+            }
+        } else if defaults.get(&field) == Some(&value) {
+            ConfigValueSource::Default
+        } else {
+            ConfigValueSource::User
+        };
+        fields.insert(field, source);
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn map(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    fn baseline(config: Value, locked: &[&str]) -> Baseline {
+        Baseline {
+            config: map(config),
+            locked: locked.iter().map(|f| f.to_string()).collect(),
+            path: PathBuf::from("/etc/proxypal/baseline.json"),
+        }
+    }
+
+    fn write_baseline(contents: Value) {
+        std::fs::write(get_baseline_path(), contents.to_string()).unwrap();
+    }
+
+    #[test]
+    fn path_comes_from_the_environment() {
+        // No test config dir on this thread, so the variable decides
+        let path = std::env::temp_dir().join("proxypal-test-no-such-baseline.json");
+        std::env::set_var(BASELINE_PATH_ENV, &path);
+        let resolved = get_baseline_path();
+        std::env::remove_var(BASELINE_PATH_ENV);
+        assert_eq!(resolved, path);
+    }
+
+    #[test]
+    fn layer_ignores_user_values_for_locked_fields() {
+        let baseline = baseline(json!({ "proxyUrl": "http://corp:3128", "requestRetry": 5 }), &["proxyUrl", "debug"]);
+        let merged = baseline.layer(map(json!({ "proxyUrl": "", "requestRetry": 2, "debug": true, "port": 8400 })));
+        assert_eq!(
+            Value::Object(merged),
+            json!({ "proxyUrl": "http://corp:3128", "requestRetry": 2, "port": 8400 })
+        );
+    }
+
+    #[test]
+    fn user_layer_drops_baseline_values_and_locked_fields() {
+        let baseline = baseline(json!({ "routingStrategy": "fill-first", "requestRetry": 5 }), &["debug"]);
+        let mut config = map(json!({ "routingStrategy": "fill-first", "requestRetry": 2, "debug": false, "port": 8317 }));
+        baseline.user_layer(&mut config);
+        assert_eq!(Value::Object(config), json!({ "requestRetry": 2, "port": 8317 }));
+    }
+
+    #[test]
+    fn check_locked_falls_back_to_defaults() {
+        let baseline = baseline(json!({ "proxyUrl": "http://corp:3128" }), &["proxyUrl", "debug"]);
+        let config = |value: Value| {
+            let mut config = default_fields();
+            config.extend(map(value));
+            config
+        };
+
+        assert!(baseline.check_locked(&config(json!({ "proxyUrl": "http://corp:3128" }))).is_ok());
+        let error = baseline
+            .check_locked(&config(json!({ "proxyUrl": "http://corp:3128", "debug": true })))
+            .unwrap_err();
+        assert!(error.starts_with("debug is locked"), "{}", error);
+        assert!(baseline.check_locked(&config(json!({ "proxyUrl": "" }))).is_err());
+    }
+
+    #[test]
+    fn unknown_and_unmanaged_fields_are_dropped() {
+        let _dir = crate::config::test_config_dir();
+        assert!(load_baseline().unwrap().is_none());
+
+        write_baseline(json!({
+            "config": { "port": 9000, "noSuchField": 1, "configVersion": 1 },
+            "locked": ["port", "ampOpenaiProvider"]
+        }));
+        let (baseline, warnings) = load_baseline().unwrap().unwrap();
+        assert_eq!(Value::Object(baseline.config), json!({ "port": 9000 }));
+        assert_eq!(baseline.locked, vec!["port"]);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert_eq!(baseline.path, get_baseline_path());
+
+        std::fs::write(get_baseline_path(), "{").unwrap();
+        assert!(load_baseline().unwrap_err().contains("is invalid"));
+    }
+
+    #[test]
+    fn user_layer_of_a_config_leaves_the_baseline_out() {
+        let _dir = crate::config::test_config_dir();
+        write_baseline(json!({ "config": { "requestRetry": 5, "proxyUrl": "http://corp:3128" }, "locked": ["proxyUrl"] }));
+        let config = AppConfig {
+            request_retry: 5,
+            port: 8400,
+            proxy_url: "http://corp:3128".to_string(),
+            ..Default::default()
+        };
+
+        let layer = to_user_layer(&config).unwrap();
+        assert!(layer.get("requestRetry").is_none() && layer.get("proxyUrl").is_none());
+        assert_eq!(layer["port"], json!(8400));
+        assert_eq!(with_baseline(json!({ "port": 8400 }))["requestRetry"], json!(5));
+
+        let changed = AppConfig {
+            proxy_url: String::new(),
+            ..config
+        };
+        assert!(check_locked(&changed).is_err());
+        assert!(to_user_layer(&changed).is_err());
+    }
+
+    #[test]
+    fn sources_follow_the_layer_precedence() {
+        let baseline = baseline(
+            json!({ "routingStrategy": "fill-first", "requestRetry": 5, "proxyUrl": "http://corp:3128" }),
+            &["proxyUrl", "debug"],
+        );
+        let overrides = ["proxyUrl", "requestRetry", "copilot.port"].map(|field| ConfigOverride {
+            field: field.to_string(),
+            source: format!("--set {}", field),
+        });
+        let config = AppConfig {
+            routing_strategy: "fill-first".to_string(),
+            request_retry: 7,
+            proxy_url: "http://corp:3128".to_string(),
+            ..Default::default()
+        };
+        let sources = |overrides: &[ConfigOverride]| field_sources(config_map(&config).unwrap(), Some(&baseline), overrides);
+
+        let plain = sources(&[]);
+        assert_eq!(plain["proxyUrl"], ConfigValueSource::Locked);
+        assert_eq!(plain["debug"], ConfigValueSource::Locked);
+        assert_eq!(plain["routingStrategy"], ConfigValueSource::Baseline);
+        assert_eq!(plain["requestRetry"], ConfigValueSource::User);
+        assert_eq!(plain["port"], ConfigValueSource::Default);
+
+        let overridden = sources(&overrides);
+        assert_eq!(overridden["proxyUrl"], ConfigValueSource::Locked);
+        assert_eq!(overridden["requestRetry"], ConfigValueSource::Override);
+        assert_eq!(overridden["copilot"], ConfigValueSource::Override);
+        assert_eq!(overridden["routingStrategy"], ConfigValueSource::Baseline);
+
+        let without_baseline = field_sources(config_map(&config).unwrap(), None, &[]);
+        assert_eq!(without_baseline["proxyUrl"], ConfigValueSource::User);
+        assert_eq!(without_baseline["port"], ConfigValueSource::Default);
+    }
+
+    #[test]
+    fn config_sources_reads_the_baseline_file() {
+        let _dir = crate::config::test_config_dir();
+        write_baseline(json!({ "config": { "requestRetry": 5 }, "locked": ["debug"] }));
+        let config = AppConfig {
+            request_retry: 5,
+            port: 8400,
+            ..Default::default()
+        };
+
+        let sources = config_sources(&config).unwrap();
+        assert_eq!(sources.baseline_path, Some(get_baseline_path().to_string_lossy().to_string()));
+        assert_eq!(sources.locked, vec!["debug"]);
+        assert_eq!(sources.fields["requestRetry"], ConfigValueSource::Baseline);
+        assert_eq!(sources.fields["debug"], ConfigValueSource::Locked);
+        assert_eq!(sources.fields["port"], ConfigValueSource::User);
+
+        std::fs::write(get_baseline_path(), "not json").unwrap();
+        let sources = config_sources(&config).unwrap();
+        assert!(sources.baseline_error.is_some());
+        assert_eq!(sources.fields["requestRetry"], ConfigValueSource::User);
+    }
+}
@@ -0,0 +1,495 @@
+//! Team config bundles: export a shareable subset of AppConfig and import it section by section.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::snapshots::diff_values;
+use super::AppConfig;
+use crate::types::{BundleConflict, BundleImportMode, BundlePreview, BundleSectionPreview, ConfigBundle};
+
+pub const BUNDLE_FORMAT: &str = "proxypal-config-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A bundle section: the AppConfig fields it carries. List fields name the key that
+/// identifies an entry, so merging can match incoming entries against local ones.
+struct Section {
+    id: &'static str,
+    label: &'static str,
+    /// Only exported when secrets are included
+    secret: bool,
+    fields: &'static [(&'static str, Option<&'static str>)],
+}
+
+const SECTIONS: &[Section] = &[
+    Section {
+        id: "openaiProviders",
+        label: "Custom OpenAI-compatible providers",
+        secret: false,
+        fields: &[("ampOpenaiProviders", Some("name"))],
+    },
+    Section {
+        id: "ampMappings",
+        label: "Amp model mappings",
+        secret: false,
+        fields: &[
+            ("ampModelMappings", Some("name")),
+            ("ampRoutingMode", None),
+            ("forceModelMappings", None),
+        ],
+    },
+    Section {
+        id: "routing",
+        label: "Routing and thinking settings",
+        secret: false,
+        fields: &[
+            ("routingStrategy", None),
+            ("requestRetry", None),
+            ("maxRetryInterval", None),
+            ("quotaSwitchProject", None),
+            ("quotaSwitchPreviewModel", None),
+            ("thinkingBudgetMode", None),
+            ("thinkingBudgetCustom", None),
+            ("geminiThinkingInjection", None),
+            ("reasoningEffortLevel", None),
+            ("payloadRules", Some("id")),
+        ],
+    },
+    Section {
+        id: "ssh",
+        label: "SSH tunnels",
+        secret: false,
+        fields: &[("sshConfigs", Some("id"))],
+    },
+    Section {
+        id: "cloudflare",
+        label: "Cloudflare tunnels",
+        secret: false,
+        fields: &[("cloudflareConfigs", Some("id"))],
+    },
+    Section {
+        id: "apiKeys",
+        label: "Provider API keys",
+        secret: true,
+        fields: &[
+            ("claudeApiKeys", Some("apiKey")),
+            ("geminiApiKeys", Some("apiKey")),
+            ("codexApiKeys", Some("apiKey")),
+            ("vertexApiKeys", Some("apiKey")),
+        ],
+    },
+];
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn config_object(config: &AppConfig) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(config).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map),
+        _ => Err("Failed to serialize config".to_string()),
+    }
+}
+
+pub fn export_bundle(config: &AppConfig, include_secrets: bool) -> Result<ConfigBundle, String> {
+    let local = config_object(config)?;
+    let mut sections = std::collections::BTreeMap::new();
+    for section in SECTIONS {
+        if section.secret && !include_secrets {
+            continue;
+        }
+        let mut fields = Map::new();
+        for (field, _) in section.fields {
+            if let Some(value) = local.get(*field) {
+                fields.insert(field.to_string(), value.clone());
+            }
+        }
+        let mut fields = Value::Object(fields);
+        if !include_secrets {
+            super::secrets::strip_secrets(&mut fields);
+        }
+        sections.insert(section.id.to_string(), fields);
+    }
+
+    Ok(ConfigBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        created_at: now_millis(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        includes_secrets: include_secrets,
+        sections,
+    })
+}
+
+fn check_bundle(bundle: &ConfigBundle) -> Result<(), String> {
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("This file is not a ProxyPal config bundle".to_string());
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "The bundle was made by a newer ProxyPal (bundle version {}), please update first",
+            bundle.version
+        ));
+    }
+    Ok(())
+}
+
+/// Sections of the bundle that this build knows, with their incoming fields
+fn bundle_sections(bundle: &ConfigBundle) -> impl Iterator<Item = (&'static Section, &Map<String, Value>)> {
+    SECTIONS.iter().filter_map(|section| {
+        let fields = bundle.sections.get(section.id)?.as_object()?;
+        Some((section, fields))
+    })
+}
+
+fn item_key<'a>(item: &'a Value, key: &str) -> Option<&'a str> {
+    item.get(key).and_then(|k| k.as_str()).filter(|k| !k.is_empty())
+}
+
+/// Label for a list entry. API keys are identified by key, so never show them in full.
+fn item_label(item: &Value, key: &str, index: usize) -> String {
+    match item_key(item, key) {
+        Some(k) if key == "apiKey" => {
+            let tail: String = k.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+            format!("key ending in {}", tail)
+        }
+        Some(k) => k.to_string(),
+        None => format!("#{}", index + 1),
+    }
+}
+
+pub fn preview_bundle(config: &AppConfig, bundle: &ConfigBundle) -> Result<BundlePreview, String> {
+    check_bundle(bundle)?;
+    let local = config_object(config)?;
+
+    let mut sections = Vec::new();
+    for (section, incoming) in bundle_sections(bundle) {
+        let mut preview = BundleSectionPreview {
+            id: section.id.to_string(),
+            label: section.label.to_string(),
+            new_items: Vec::new(),
+            conflicts: Vec::new(),
+            removed_on_replace: Vec::new(),
+        };
+
+        for (field, key) in section.fields {
+            let Some(theirs) = incoming.get(*field) else {
+                continue;
+            };
+            let ours = local.get(*field).cloned().unwrap_or(Value::Null);
+
+            match (key, &ours, theirs) {
+                (Some(key), Value::Array(ours), Value::Array(theirs)) => {
+                    for (i, item) in theirs.iter().enumerate() {
+                        let Some(local_index) = find_entry(ours, item, key) else {
+                            preview
+                                .new_items
+                                .push(format!("{}: {}", field, item_label(item, key, i)));
+                            continue;
+                        };
+                        let mut item = item.clone();
+                        keep_local_fields(&mut item, &ours[local_index], key);
+                        if ours[local_index] != item {
+                            preview.conflicts.push(BundleConflict {
+                                field: field.to_string(),
+                                item: Some(item_label(&item, key, i)),
+                                changes: diff_values(&ours[local_index], &item),
+                            });
+                        }
+                    }
+                    for (i, item) in ours.iter().enumerate() {
+                        if find_entry(theirs, item, key).is_none() {
+                            preview
+                                .removed_on_replace
+                                .push(format!("{}: {}", field, item_label(item, key, i)));
+                        }
+                    }
+                }
+                _ if &ours != theirs => {
+                    let mut before = Map::new();
+                    before.insert(field.to_string(), ours.clone());
+                    let mut after = Map::new();
+                    after.insert(field.to_string(), theirs.clone());
+                    preview.conflicts.push(BundleConflict {
+                        field: field.to_string(),
+                        item: None,
+                        changes: diff_values(&Value::Object(before), &Value::Object(after)),
+                    });
+                }
+                _ => {}
+            }
+        }
+        sections.push(preview);
+    }
+
+    Ok(BundlePreview {
+        version: bundle.version,
+        created_at: bundle.created_at,
+        app_version: bundle.app_version.clone(),
+        includes_secrets: bundle.includes_secrets,
+        sections,
+    })
+}
+
+/// Secret keys inside list entries, blanked in bundles exported without secrets
+fn is_entry_secret(key: &str) -> bool {
+    super::secrets::SECRET_PATHS
+        .iter()
+        .any(|path| path.len() == 3 && path[2] == key)
+}
+
+/// Carry over what the bundle can't know about a matched local entry: secrets it had
+/// stripped, and the local id when entries are matched by name
+fn keep_local_fields(incoming: &mut Value, local: &Value, key: &str) {
+    let (Value::Object(incoming), Value::Object(local)) = (incoming, local) else {
+        return;
+    };
+    for (k, v) in incoming.iter_mut() {
+        let keep = (is_entry_secret(k) && v.as_str() == Some("")) || (k == "id" && key != "id");
+        if keep {
+            if let Some(local_value) = local.get(k).filter(|s| s.is_string()) {
+                *v = local_value.clone();
+            }
+        }
+    }
+}
+
+fn find_entry(list: &[Value], item: &Value, key: &str) -> Option<usize> {
+    let k = item_key(item, key)?;
+    list.iter().position(|o| item_key(o, key) == Some(k))
+}
+
+fn merge_list(ours: &mut Vec<Value>, theirs: &[Value], key: &str) {
+    for item in theirs {
+        let mut item = item.clone();
+        match find_entry(ours, &item, key) {
+            Some(i) => {
+                keep_local_fields(&mut item, &ours[i], key);
+                ours[i] = item;
+            }
+            None => ours.push(item),
+        }
+    }
+}
+
+fn replace_list(ours: &[Value], theirs: &[Value], key: &str) -> Vec<Value> {
+    theirs
+        .iter()
+        .map(|item| {
+            let mut item = item.clone();
+            if let Some(i) = find_entry(ours, &item, key) {
+                keep_local_fields(&mut item, &ours[i], key);
+            }
+            item
+        })
+        .collect()
+}
+
+/// Apply the chosen sections of a bundle to a config. Sections without a choice are skipped.
+pub fn import_bundle(
+    config: &AppConfig,
+    bundle: &ConfigBundle,
+    choices: &HashMap<String, BundleImportMode>,
+) -> Result<AppConfig, String> {
+    check_bundle(bundle)?;
+    let mut local = config_object(config)?;
+
+    for (section, incoming) in bundle_sections(bundle) {
+        let mode = choices.get(section.id).copied().unwrap_or(BundleImportMode::Skip);
+        if mode == BundleImportMode::Skip {
+            continue;
+        }
+        for (field, key) in section.fields {
+            let Some(theirs) = incoming.get(*field) else {
+                continue;
+            };
+            let ours = local.entry(field.to_string()).or_insert(Value::Null);
+            match (key, &mut *ours, theirs) {
+                (Some(key), Value::Array(list), Value::Array(theirs)) => {
+                    if mode == BundleImportMode::Merge {
+                        merge_list(list, theirs, key);
+                    } else {
+                        *list = replace_list(list, theirs, key);
+                    }
+                }
+                _ => *ours = theirs.clone(),
+            }
+        }
+    }
+
+    serde_json::from_value(Value::Object(local))
+        .map_err(|e| format!("The bundle does not fit this version's config: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(fields: Value) -> AppConfig {
+        let mut config = config_object(&AppConfig::default()).unwrap();
+        config.extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(Value::Object(config)).unwrap()
+    }
+
+    fn provider(id: &str, name: &str, base_url: &str, api_key: &str) -> Value {
+        json!({ "id": id, "name": name, "baseUrl": base_url, "apiKey": api_key, "models": [] })
+    }
+
+    fn local() -> AppConfig {
+        config(json!({
+            "ampOpenaiProviders": [
+                provider("local-a", "a", "https://a.example/v1", "sk-a"),
+                provider("local-b", "b", "https://b.example/v1", "sk-b"),
+            ],
+            "routingStrategy": "round-robin",
+            "cloudflareConfigs": [
+                { "id": "cf", "name": "office", "tunnelToken": "token-local", "localPort": 8317, "enabled": true }
+            ],
+        }))
+    }
+
+    /// A bundle exported without secrets by a teammate who changed "a" and added "c"
+    fn teammate_bundle() -> ConfigBundle {
+        let theirs = config(json!({
+            "ampOpenaiProviders": [
+                provider("their-a", "a", "https://a.example/v2", "sk-theirs"),
+                provider("their-c", "c", "https://c.example/v1", "sk-c"),
+            ],
+            "routingStrategy": "fill-first",
+            "cloudflareConfigs": [
+                { "id": "cf", "name": "office", "tunnelToken": "token-theirs", "localPort": 9000, "enabled": true }
+            ],
+        }));
+        export_bundle(&theirs, false).unwrap()
+    }
+
+    fn choices(modes: &[(&str, BundleImportMode)]) -> HashMap<String, BundleImportMode> {
+        modes.iter().map(|(id, mode)| (id.to_string(), *mode)).collect()
+    }
+
+    fn providers(config: &AppConfig) -> Vec<(String, String, String, String)> {
+        config
+            .amp_openai_providers
+            .iter()
+            .map(|p| (p.id.clone(), p.name.clone(), p.base_url.clone(), p.api_key.clone()))
+            .collect()
+    }
+
+    fn owned(entries: &[(&str, &str, &str, &str)]) -> Vec<(String, String, String, String)> {
+        entries
+            .iter()
+            .map(|(a, b, c, d)| (a.to_string(), b.to_string(), c.to_string(), d.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn export_without_secrets_strips_them() {
+        let bundle = export_bundle(&local(), false).unwrap();
+        assert!(!bundle.includes_secrets);
+        assert!(!bundle.sections.contains_key("apiKeys"));
+        let exported = &bundle.sections["openaiProviders"]["ampOpenaiProviders"];
+        assert_eq!(exported[0]["apiKey"], json!(""));
+        assert_eq!(bundle.sections["cloudflare"]["cloudflareConfigs"][0]["tunnelToken"], json!(""));
+
+        let with_secrets = export_bundle(&local(), true).unwrap();
+        assert!(with_secrets.sections.contains_key("apiKeys"));
+        assert_eq!(with_secrets.sections["openaiProviders"]["ampOpenaiProviders"][0]["apiKey"], json!("sk-a"));
+    }
+
+    #[test]
+    fn merge_updates_matches_and_keeps_local_entries() {
+        let bundle = teammate_bundle();
+        let merged = import_bundle(&local(), &bundle, &choices(&[("openaiProviders", BundleImportMode::Merge)])).unwrap();
+
+        // "a" is matched by name: their URL, but our id and our key, which the bundle didn't carry
+        assert_eq!(
+            providers(&merged),
+            owned(&[
+                ("local-a", "a", "https://a.example/v2", "sk-a"),
+                ("local-b", "b", "https://b.example/v1", "sk-b"),
+                ("their-c", "c", "https://c.example/v1", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn replace_drops_local_only_entries() {
+        let bundle = teammate_bundle();
+        let replaced = import_bundle(&local(), &bundle, &choices(&[("openaiProviders", BundleImportMode::Replace)])).unwrap();
+        assert_eq!(
+            providers(&replaced),
+            owned(&[
+                ("local-a", "a", "https://a.example/v2", "sk-a"),
+                ("their-c", "c", "https://c.example/v1", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn only_chosen_sections_are_imported() {
+        let bundle = teammate_bundle();
+        let imported = import_bundle(
+            &local(),
+            &bundle,
+            &choices(&[("routing", BundleImportMode::Merge), ("openaiProviders", BundleImportMode::Skip)]),
+        )
+        .unwrap();
+        assert_eq!(imported.routing_strategy, "fill-first");
+        assert_eq!(providers(&imported), providers(&local()));
+        assert_eq!(imported.cloudflare_configs[0].local_port, 8317);
+
+        // Matched by id, the stripped token stays ours
+        let imported = import_bundle(&local(), &bundle, &choices(&[("cloudflare", BundleImportMode::Replace)])).unwrap();
+        assert_eq!(imported.cloudflare_configs[0].local_port, 9000);
+        assert_eq!(imported.cloudflare_configs[0].tunnel_token, "token-local");
+    }
+
+    #[test]
+    fn preview_lists_new_changed_and_removed_entries() {
+        let preview = preview_bundle(&local(), &teammate_bundle()).unwrap();
+        let providers = preview.sections.iter().find(|s| s.id == "openaiProviders").unwrap();
+
+        assert_eq!(providers.new_items, vec!["ampOpenaiProviders: c"]);
+        assert_eq!(providers.removed_on_replace, vec!["ampOpenaiProviders: b"]);
+        assert_eq!(providers.conflicts.len(), 1);
+        let conflict = &providers.conflicts[0];
+        assert_eq!(conflict.item.as_deref(), Some("a"));
+        // Neither the kept id nor the kept key show up as a change
+        let paths: Vec<&str> = conflict.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["baseUrl"]);
+
+        let routing = preview.sections.iter().find(|s| s.id == "routing").unwrap();
+        assert_eq!(routing.conflicts.len(), 1);
+        assert_eq!(routing.conflicts[0].field, "routingStrategy");
+        assert!(routing.conflicts[0].item.is_none());
+        assert!(routing.removed_on_replace.is_empty());
+    }
+
+    #[test]
+    fn api_keys_are_labelled_by_their_last_characters() {
+        let theirs = config(json!({ "claudeApiKeys": [{ "apiKey": "sk-ant-new-1234" }] }));
+        let bundle = export_bundle(&theirs, true).unwrap();
+        let ours = config(json!({ "claudeApiKeys": [{ "apiKey": "sk-ant-old-9876" }] }));
+
+        let preview = preview_bundle(&ours, &bundle).unwrap();
+        let keys = preview.sections.iter().find(|s| s.id == "apiKeys").unwrap();
+        assert_eq!(keys.new_items, vec!["claudeApiKeys: key ending in 1234"]);
+        assert_eq!(keys.removed_on_replace, vec!["claudeApiKeys: key ending in 9876"]);
+    }
+
+    #[test]
+    fn newer_or_foreign_bundles_are_refused() {
+        let mut bundle = teammate_bundle();
+        bundle.version = BUNDLE_VERSION + 1;
+        let error = import_bundle(&local(), &bundle, &HashMap::new()).unwrap_err();
+        assert!(error.contains("newer ProxyPal"), "{}", error);
+        assert!(preview_bundle(&local(), &bundle).is_err());
+
+        let mut bundle = teammate_bundle();
+        bundle.format = "something-else".to_string();
+        assert!(preview_bundle(&local(), &bundle).unwrap_err().contains("not a ProxyPal config bundle"));
+    }
+}
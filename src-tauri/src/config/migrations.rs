@@ -0,0 +1,287 @@
+//! Ordered config.json migrations, run on the raw JSON before it is deserialized.
+//!
+//! To change the shape of AppConfig, bump CURRENT_CONFIG_VERSION and append a step to
+//! MIGRATIONS that upgrades a document from the previous version. Steps must tolerate
+//! documents that were edited by hand (missing keys, null values).
+
+use serde_json::{Map, Value};
+
+use super::AppConfig;
+use crate::types::amp::generate_uuid;
+
+/// Version written by this build. Files without `configVersion` are version 1.
+pub const CURRENT_CONFIG_VERSION: u8 = 3;
+
+struct Migration {
+    /// Version the document has after this step
+    to: u8,
+    description: &'static str,
+    apply: fn(&mut Map<String, Value>) -> Result<(), String>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 2,
+        description: "move ampOpenaiProvider into the ampOpenaiProviders list",
+        apply: migrate_single_amp_provider,
+    },
+    Migration {
+        to: 3,
+        description: "encrypt secrets stored in plaintext",
+        apply: migrate_encrypt_secrets,
+    },
+];
+
+/// v1 -> v2: the single `ampOpenaiProvider` became the `ampOpenaiProviders` list
+fn migrate_single_amp_provider(config: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(old) = config.remove("ampOpenaiProvider") else {
+        return Ok(());
+    };
+    let Value::Object(mut provider) = old else {
+        // null (or junk) - nothing to carry over
+        return Ok(());
+    };
+
+    let providers = config
+        .entry("ampOpenaiProviders")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if providers.is_null() {
+        *providers = Value::Array(Vec::new());
+    }
+    let Value::Array(providers) = providers else {
+        return Err("ampOpenaiProviders is not a list".to_string());
+    };
+    if !providers.is_empty() {
+        // Already migrated by hand or by an older build; the list wins
+        return Ok(());
+    }
+
+    let has_id = provider
+        .get("id")
+        .and_then(|id| id.as_str())
+        .is_some_and(|id| !id.is_empty());
+    if !has_id {
+        provider.insert("id".to_string(), Value::String(generate_uuid()));
+    }
+    providers.push(Value::Object(provider));
+    Ok(())
+}
+
+/// v2 -> v3: secrets are stored sealed (see config::secrets)
+fn migrate_encrypt_secrets(config: &mut Map<String, Value>) -> Result<(), String> {
+    let mut value = Value::Object(std::mem::take(config));
+    let sealed = super::secrets::seal_secrets(&mut value);
+    if let Value::Object(map) = value {
+        *config = map;
+    }
+    sealed
+}
+
+/// Version recorded in a raw config document
+pub fn config_version(config: &Value) -> u8 {
+    config
+        .get("configVersion")
+        .and_then(|v| v.as_u64())
+        .map(|v| v.min(u8::MAX as u64) as u8)
+        .unwrap_or(1)
+}
+
+/// Bring a raw config document up to CURRENT_CONFIG_VERSION.
+///
+/// `backup` is called with the document's version before each step runs, so the
+/// caller can keep a copy of config.json as it was. A failing backup aborts the
+/// migration. Returns a description of every step that ran. A document newer than
+/// this build is refused.
+pub fn migrate_config(
+    config: &mut Value,
+    mut backup: impl FnMut(u8) -> Result<(), String>,
+) -> Result<Vec<String>, String> {
+    let mut version = config_version(config);
+    let Value::Object(map) = config else {
+        return Err("config.json does not contain a JSON object".to_string());
+    };
+    // Loading it would drop what this build doesn't know on the next save
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(format!(
+            "config.json is version {}, written by a newer ProxyPal than this one (version {}). Update ProxyPal to use it.",
+            version, CURRENT_CONFIG_VERSION
+        ));
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS {
+        if migration.to <= version {
+            continue;
+        }
+        backup(version)?;
+        (migration.apply)(map)
+            .map_err(|e| format!("Migration to config version {} failed: {}", migration.to, e))?;
+        eprintln!(
+            "[ProxyPal] Migrated config to version {}: {}",
+            migration.to, migration.description
+        );
+        version = migration.to;
+        map.insert("configVersion".to_string(), Value::from(version));
+        applied.push(format!("v{}: {}", migration.to, migration.description));
+    }
+    Ok(applied)
+}
+
+/// Deserialize a migrated document without losing the whole config to one bad field.
+///
+/// Fields are taken over one at a time on top of the defaults; a field that doesn't
+/// deserialize (wrong type, unknown enum shape, ...) keeps its default and is reported.
+pub fn deserialize_config(config: Value) -> Result<(AppConfig, Vec<String>), String> {
+    let Value::Object(user) = config else {
+        return Err("config.json does not contain a JSON object".to_string());
+    };
+
+    // Fast path: the whole document is valid
+    if let Ok(parsed) = serde_json::from_value::<AppConfig>(Value::Object(user.clone())) {
+        return Ok((parsed, Vec::new()));
+    }
+
+    let mut merged = match serde_json::to_value(AppConfig::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => return Err("Failed to serialize default config".to_string()),
+    };
+    let mut warnings = Vec::new();
+    for (key, value) in user {
+        let previous = merged.insert(key.clone(), value);
+        if let Err(e) = serde_json::from_value::<AppConfig>(Value::Object(merged.clone())) {
+            warnings.push(format!("{}: {} - using the default", key, e));
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+
+    let config = serde_json::from_value(Value::Object(merged))
+        .map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok((config, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn v1_document() -> Value {
+        json!({
+            "port": 8400,
+            "ampApiKey": "amp-secret",
+            "ampOpenaiProvider": {
+                "name": "Local",
+                "baseUrl": "http://localhost:1234/v1",
+                "apiKey": "provider-secret"
+            }
+        })
+    }
+
+    #[test]
+    fn v1_is_upgraded_to_the_current_version() {
+        let _dir = crate::config::test_config_dir();
+        let mut doc = v1_document();
+
+        let applied = migrate_config(&mut doc, |_| Ok(())).unwrap();
+
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(config_version(&doc), CURRENT_CONFIG_VERSION);
+        assert!(doc.get("ampOpenaiProvider").is_none());
+        let provider = &doc["ampOpenaiProviders"][0];
+        assert_eq!(provider["name"], "Local");
+        assert!(!provider["id"].as_str().unwrap().is_empty());
+        assert!(crate::config::secrets::is_sealed(provider["apiKey"].as_str().unwrap()));
+        assert!(crate::config::secrets::is_sealed(doc["ampApiKey"].as_str().unwrap()));
+
+        crate::config::secrets::open_secrets(&mut doc).unwrap();
+        let (config, warnings) = deserialize_config(doc).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(config.port, 8400);
+        assert_eq!(config.amp_api_key, "amp-secret");
+        assert_eq!(config.amp_openai_providers[0].api_key, "provider-secret");
+    }
+
+    #[test]
+    fn current_version_is_left_alone() {
+        let mut doc = json!({ "configVersion": CURRENT_CONFIG_VERSION, "port": 8400 });
+        let before = doc.clone();
+
+        let applied = migrate_config(&mut doc, |_| panic!("nothing to back up")).unwrap();
+
+        assert!(applied.is_empty());
+        assert_eq!(doc, before);
+    }
+
+    #[test]
+    fn backup_is_taken_before_each_step() {
+        let _dir = crate::config::test_config_dir();
+        let mut doc = v1_document();
+        let mut backed_up = Vec::new();
+
+        migrate_config(&mut doc, |version| {
+            backed_up.push(version);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(backed_up, vec![1, 2]);
+    }
+
+    #[test]
+    fn failed_backup_stops_before_the_step() {
+        let mut doc = v1_document();
+
+        let error = migrate_config(&mut doc, |version| {
+            if version == 2 {
+                Err("disk full".to_string())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(error, "disk full");
+        // v2 ran, v3 (sealing) didn't
+        assert_eq!(config_version(&doc), 2);
+        assert_eq!(doc["ampApiKey"], "amp-secret");
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let mut doc = json!({ "configVersion": CURRENT_CONFIG_VERSION + 1, "port": 8400 });
+        let before = doc.clone();
+
+        let error = migrate_config(&mut doc, |_| panic!("nothing to back up")).unwrap_err();
+
+        assert!(error.contains("newer ProxyPal"), "{}", error);
+        assert_eq!(doc, before);
+    }
+
+    #[test]
+    fn fields_that_dont_parse_fall_back_to_defaults() {
+        let doc = json!({
+            "configVersion": CURRENT_CONFIG_VERSION,
+            "port": "not a port",
+            "debug": true,
+            "claudeApiKeys": { "not": "a list" }
+        });
+
+        let (config, warnings) = deserialize_config(doc).unwrap();
+
+        let defaults = AppConfig::default();
+        assert_eq!(config.port, defaults.port);
+        assert!(config.claude_api_keys.is_empty());
+        assert!(config.debug);
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.starts_with("port: ")));
+        assert!(warnings.iter().any(|w| w.starts_with("claudeApiKeys: ")));
+    }
+
+    #[test]
+    fn non_object_is_an_error() {
+        assert!(migrate_config(&mut json!([1, 2]), |_| Ok(())).is_err());
+        assert!(deserialize_config(json!("config")).is_err());
+    }
+}
@@ -0,0 +1,685 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+pub mod baseline;
+pub mod migrations;
+pub mod overrides;
+pub mod secrets;
+pub mod snapshots;
+pub mod validation;
+
+// Used by the desktop app only, kept in the headless build for their tests
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub mod bundle;
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub mod profiles;
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub mod schema;
+
+use crate::types::{
+    cloudflare::CloudflareConfig, AmpModelMapping, AmpOpenAIProvider, ClaudeApiKey, CodexApiKey,
+    ConfigLoadStatus, CopilotConfig, GeminiApiKey, PayloadRule, SshConfig, VertexApiKey,
+};
+use migrations::CURRENT_CONFIG_VERSION;
+
+/// App configuration persisted to config.json
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppConfig {
+    pub port: u16,
+    pub auto_start: bool,
+    pub launch_at_login: bool,
+    #[serde(default)]
+    pub debug: bool,
+    #[serde(default)]
+    pub proxy_url: String,
+    #[serde(default)]
+    pub proxy_username: String,
+    #[serde(default)]
+    pub proxy_password: String,
+    #[serde(default)]
+    pub use_system_proxy: bool,
+    #[serde(default)]
+    pub request_retry: u16,
+    #[serde(default)]
+    pub quota_switch_project: bool,
+    #[serde(default)]
+    pub quota_switch_preview_model: bool,
+    #[serde(default = "default_usage_stats_enabled")]
+    pub usage_stats_enabled: bool,
+    #[serde(default)]
+    pub request_logging: bool,
+    #[serde(default)]
+    pub logging_to_file: bool,
+    #[serde(default = "default_logs_max_total_size_mb")]
+    pub logs_max_total_size_mb: u32,
+    #[serde(default = "default_config_version")]
+    pub config_version: u8,
+    #[serde(default)]
+    pub amp_api_key: String,
+    #[serde(default)]
+    pub amp_model_mappings: Vec<AmpModelMapping>,
+    #[serde(default)]
+    pub amp_openai_provider: Option<AmpOpenAIProvider>, // DEPRECATED: Use amp_openai_providers
+    #[serde(default)]
+    pub amp_openai_providers: Vec<AmpOpenAIProvider>,
+    #[serde(default)]
+    #[schemars(schema_with = "schema::amp_routing_mode")]
+    pub amp_routing_mode: String,
+    #[serde(default = "default_routing_strategy")]
+    #[schemars(schema_with = "schema::routing_strategy")]
+    pub routing_strategy: String,
+    #[serde(default)]
+    pub copilot: CopilotConfig,
+    #[serde(default)]
+    pub force_model_mappings: bool,
+    #[serde(default)]
+    pub claude_api_keys: Vec<ClaudeApiKey>,
+    #[serde(default)]
+    pub gemini_api_keys: Vec<GeminiApiKey>,
+    #[serde(default)]
+    pub codex_api_keys: Vec<CodexApiKey>,
+    #[serde(default)]
+    pub vertex_api_keys: Vec<VertexApiKey>,
+    #[serde(default)]
+    #[schemars(schema_with = "schema::thinking_budget_mode")]
+    pub thinking_budget_mode: String,
+    #[serde(default)]
+    pub thinking_budget_custom: u32,
+    #[serde(default = "default_gemini_thinking_injection")]
+    pub gemini_thinking_injection: bool,
+    #[serde(default)]
+    #[schemars(schema_with = "schema::reasoning_effort_level")]
+    pub reasoning_effort_level: String,
+    #[serde(default = "default_close_to_tray")]
+    pub close_to_tray: bool,
+    #[serde(default)]
+    pub max_retry_interval: i32,
+    #[serde(default = "default_proxy_api_key")]
+    pub proxy_api_key: String,
+    #[serde(default = "default_management_key")]
+    pub management_key: String,
+    #[serde(default)]
+    pub commercial_mode: bool,
+    #[serde(default)]
+    pub ws_auth: bool,
+    #[serde(default)]
+    pub sidebar_pinned: bool,
+    #[serde(default)]
+    pub ssh_configs: Vec<SshConfig>,
+    #[serde(default)]
+    pub cloudflare_configs: Vec<CloudflareConfig>,
+    #[serde(default = "default_disable_control_panel")]
+    pub disable_control_panel: bool,
+    /// Number of config snapshots kept in snapshots/ (0 disables snapshots)
+    #[serde(default = "default_snapshot_limit")]
+    pub snapshot_limit: u32,
+    /// Models offered to the UI and agents, "*" wildcards allowed (empty allows all).
+    /// Usually set by an organization baseline.
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    /// Rules rendered into the payload section of proxy-config.yaml
+    #[serde(default = "default_payload_rules")]
+    pub payload_rules: Vec<PayloadRule>,
+    /// Restart the proxy with a growing delay when it exits without being stopped
+    #[serde(default = "default_auto_restart_proxy")]
+    pub auto_restart_proxy: bool,
+    /// Give up restarting after this many exits within crash_loop_window_minutes
+    #[serde(default = "default_crash_loop_max_exits")]
+    pub crash_loop_max_exits: u32,
+    #[serde(default = "default_crash_loop_window_minutes")]
+    pub crash_loop_window_minutes: u32,
+    /// How long start_proxy waits for the sidecar to answer before giving up
+    #[serde(default = "default_proxy_ready_timeout_secs")]
+    pub proxy_ready_timeout_secs: u32,
+    /// Move the proxy or Copilot bridge to the next free port when another program holds theirs
+    #[serde(default = "default_auto_select_port")]
+    pub auto_select_port: bool,
+    /// Save each process's output when it exits, see process_output
+    #[serde(default = "default_persist_process_output")]
+    pub persist_process_output: bool,
+}
+
+fn default_persist_process_output() -> bool {
+    true
+}
+
+fn default_auto_select_port() -> bool {
+    true
+}
+
+fn default_proxy_ready_timeout_secs() -> u32 {
+    15
+}
+
+fn default_auto_restart_proxy() -> bool {
+    true
+}
+
+fn default_crash_loop_max_exits() -> u32 {
+    5
+}
+
+fn default_crash_loop_window_minutes() -> u32 {
+    10
+}
+
+fn default_disable_control_panel() -> bool {
+    true
+}
+
+fn default_snapshot_limit() -> u32 {
+    20
+}
+
+fn default_management_key() -> String {
+    "proxypal-mgmt-key".to_string()
+}
+
+fn default_proxy_api_key() -> String {
+    "proxypal-local".to_string()
+}
+
+fn default_close_to_tray() -> bool {
+    true
+}
+
+fn default_usage_stats_enabled() -> bool {
+    true
+}
+
+fn default_logs_max_total_size_mb() -> u32 {
+    100
+}
+
+fn default_config_version() -> u8 {
+    CURRENT_CONFIG_VERSION
+}
+
+fn default_routing_strategy() -> String {
+    "round-robin".to_string()
+}
+
+fn default_gemini_thinking_injection() -> bool {
+    true
+}
+
+fn default_payload_rules() -> Vec<PayloadRule> {
+    crate::proxy::payload_rules::default_payload_rules()
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            port: 8317,
+            auto_start: true,
+            launch_at_login: false,
+            debug: false,
+            proxy_url: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            use_system_proxy: false,
+            request_retry: 0,
+            quota_switch_project: false,
+            quota_switch_preview_model: false,
+            usage_stats_enabled: true,
+            request_logging: true,
+            logging_to_file: true,
+            logs_max_total_size_mb: 100,
+            config_version: CURRENT_CONFIG_VERSION,
+            amp_api_key: String::new(),
+            amp_model_mappings: Vec::new(),
+            amp_openai_provider: None,
+            amp_openai_providers: Vec::new(),
+            amp_routing_mode: "mappings".to_string(),
+            sidebar_pinned: false,
+            routing_strategy: "round-robin".to_string(),
+            copilot: CopilotConfig::default(),
+            force_model_mappings: true,
+            claude_api_keys: Vec::new(),
+            gemini_api_keys: Vec::new(),
+            codex_api_keys: Vec::new(),
+            vertex_api_keys: Vec::new(),
+            thinking_budget_mode: "medium".to_string(),
+            thinking_budget_custom: 16000,
+            gemini_thinking_injection: true,
+            reasoning_effort_level: "medium".to_string(),
+            close_to_tray: true,
+            max_retry_interval: 0,
+            proxy_api_key: "proxypal-local".to_string(),
+            management_key: "proxypal-mgmt-key".to_string(),
+            commercial_mode: false,
+            ws_auth: false,
+            ssh_configs: Vec::new(),
+            cloudflare_configs: Vec::new(),
+            disable_control_panel: true,
+            snapshot_limit: default_snapshot_limit(),
+            allowed_models: Vec::new(),
+            payload_rules: default_payload_rules(),
+            auto_restart_proxy: default_auto_restart_proxy(),
+            crash_loop_max_exits: default_crash_loop_max_exits(),
+            crash_loop_window_minutes: default_crash_loop_window_minutes(),
+            proxy_ready_timeout_secs: default_proxy_ready_timeout_secs(),
+            auto_select_port: default_auto_select_port(),
+            persist_process_output: default_persist_process_output(),
+        }
+    }
+}
+
+impl AppConfig {
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+    pub fn is_model_allowed(&self, model: &str) -> bool {
+        self.allowed_models.is_empty()
+            || self.allowed_models.iter().any(|pattern| wildcard_match(pattern, model))
+    }
+}
+
+/// Case-insensitive match where "*" stands for any run of characters
+pub(crate) fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let value = value.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !value.starts_with(first) || value.len() < first.len() + last.len() || !value.ends_with(last) {
+        return false;
+    }
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Config dir of the test running on this thread, see test_config_dir
+    static TEST_CONFIG_DIR: std::cell::RefCell<Option<std::path::PathBuf>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Point get_proxypal_config_dir at a fresh temp dir for the calling test, until the
+/// returned dir is dropped
+#[cfg(test)]
+pub(crate) fn test_config_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().expect("temp dir");
+    TEST_CONFIG_DIR.with(|d| *d.borrow_mut() = Some(dir.path().to_path_buf()));
+    dir
+}
+
+/// Get the proxypal config directory, creating it if needed
+pub fn get_proxypal_config_dir() -> std::path::PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_CONFIG_DIR.with(|d| d.borrow().clone()) {
+        return dir;
+    }
+
+    let config_dir = dirs::config_dir()
+        .unwrap_or_else(|| {
+            eprintln!(
+                "[ProxyPal] Warning: Could not determine config directory, using current directory"
+            );
+            std::path::PathBuf::from(".")
+        })
+        .join("proxypal");
+
+    if let Err(e) = std::fs::create_dir_all(&config_dir) {
+        eprintln!(
+            "[ProxyPal] Error: Failed to create config directory '{}': {}",
+            config_dir.display(),
+            e
+        );
+    }
+
+    config_dir
+}
+
+/// Config file path
+pub fn get_config_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("config.json")
+}
+
+/// Generated CLIProxyAPI config path
+pub fn get_proxy_config_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("proxy-config.yaml")
+}
+
+/// User overrides deep-merged into the generated CLIProxyAPI config
+pub fn get_custom_proxy_config_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("proxy-config-custom.yaml")
+}
+
+/// Auth status file path
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn get_auth_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("auth.json")
+}
+
+/// Request history file path
+pub fn get_history_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("history.json")
+}
+
+/// Aggregate analytics file path (cumulative stats, never trimmed)
+pub fn get_aggregate_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("aggregate.json")
+}
+
+/// Outcome of the last load_config, see get_config_load_status
+static LOAD_STATUS: Mutex<ConfigLoadStatus> = Mutex::new(ConfigLoadStatus::new());
+
+pub fn get_config_load_status() -> ConfigLoadStatus {
+    LOAD_STATUS.lock().unwrap().clone()
+}
+
+/// Forget a load error so the next save may overwrite the unreadable config.json
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn clear_config_load_error() {
+    let mut status = LOAD_STATUS.lock().unwrap();
+    status.error = None;
+    status.corrupt_copy_path = None;
+}
+
+/// Write a file that may hold plaintext secrets, readable by the owner only on Unix.
+/// It is created 0600, so its content is never readable by others, not even briefly.
+pub(crate) fn write_private(path: &std::path::Path, data: &[u8]) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        // The mode only applies to new files, an older one may still be wider
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| e.to_string())?;
+        file.write_all(data).map_err(|e| e.to_string())
+    }
+    #[cfg(not(unix))]
+    {
+        // %APPDATA% is already private to the user on Windows
+        std::fs::write(path, data).map_err(|e| e.to_string())
+    }
+}
+
+fn backup_timestamp() -> String {
+    chrono::Local::now().format("%Y%m%d-%H%M%S").to_string()
+}
+
+/// Copy the current config.json to backups/config-v<version>-<timestamp>.json
+fn backup_config_file(raw: &str, version: u8) -> Result<String, String> {
+    let dir = get_proxypal_config_dir().join("backups");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let path = dir.join(format!("config-v{}-{}.json", version, backup_timestamp()));
+    write_private(&path, raw.as_bytes()).map_err(|e| format!("Failed to back up config.json: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Keep a copy of an unreadable config.json next to it, once per distinct content
+fn keep_corrupt_copy(path: &std::path::Path, raw: &[u8], previous: &ConfigLoadStatus) -> Option<String> {
+    if let Some(existing) = &previous.corrupt_copy_path {
+        if std::fs::read(existing).ok().as_deref() == Some(raw) {
+            return Some(existing.clone());
+        }
+    }
+    let copy = path.with_file_name(format!("config.corrupt-{}.json", backup_timestamp()));
+    match write_private(&copy, raw) {
+        Ok(_) => Some(copy.to_string_lossy().to_string()),
+        Err(e) => {
+            eprintln!("[ProxyPal] Failed to keep a copy of the unreadable config: {}", e);
+            None
+        }
+    }
+}
+
+/// Put the organization baseline under the user's config document (see config::baseline)
+fn layer_baseline(baseline: Option<&baseline::Baseline>, user: serde_json::Value) -> serde_json::Value {
+    match (baseline, user) {
+        (Some(baseline), serde_json::Value::Object(user)) => serde_json::Value::Object(baseline.layer(user)),
+        (_, user) => user,
+    }
+}
+
+/// The organization baseline for one load_config, with its warnings. One that fails to
+/// load is ignored with a warning.
+fn load_baseline_for_config() -> (Option<baseline::Baseline>, Vec<String>) {
+    match baseline::load_baseline() {
+        Ok(Some((baseline, warnings))) => {
+            eprintln!("[ProxyPal] Using organization baseline {}", baseline.path.display());
+            (Some(baseline), warnings)
+        }
+        Ok(None) => (None, Vec::new()),
+        Err(e) => {
+            eprintln!("[ProxyPal] {}", e);
+            (None, vec![format!("{} - it is ignored", e)])
+        }
+    }
+}
+
+fn read_config_file(
+    status: &mut ConfigLoadStatus,
+    previous: &ConfigLoadStatus,
+    baseline: Option<&baseline::Baseline>,
+) -> Option<AppConfig> {
+    let path = get_config_path();
+    if !path.exists() {
+        let value = layer_baseline(baseline, serde_json::Value::Object(Default::default()));
+        return match migrations::deserialize_config(value) {
+            Ok((config, warnings)) => {
+                status.warnings.extend(warnings);
+                Some(config)
+            }
+            Err(e) => {
+                status.error = Some(e);
+                None
+            }
+        };
+    }
+
+    let raw = match std::fs::read(&path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            status.error = Some(format!("Failed to read {}: {}", path.display(), e));
+            return None;
+        }
+    };
+    let mut value = match serde_json::from_slice::<serde_json::Value>(&raw) {
+        Ok(value @ serde_json::Value::Object(_)) => value,
+        Ok(_) => {
+            status.error = Some(format!("{} does not contain a JSON object", path.display()));
+            status.corrupt_copy_path = keep_corrupt_copy(&path, &raw, previous);
+            return None;
+        }
+        Err(e) => {
+            status.error = Some(format!("{} is not valid JSON: {}", path.display(), e));
+            status.corrupt_copy_path = keep_corrupt_copy(&path, &raw, previous);
+            return None;
+        }
+    };
+    let raw = String::from_utf8_lossy(&raw).to_string();
+    let original_version = migrations::config_version(&value);
+
+    let mut backup_paths = Vec::new();
+    let migrated = migrations::migrate_config(&mut value, |version| {
+        backup_paths.push(backup_config_file(&raw, version)?);
+        Ok(())
+    });
+    status.backup_paths = backup_paths;
+    match migrated {
+        Ok(applied) => status.migrations = applied,
+        Err(e) => {
+            status.error = Some(e);
+            return None;
+        }
+    }
+    if let Err(e) = secrets::open_secrets(&mut value) {
+        status.error = Some(e);
+        return None;
+    }
+    let value = layer_baseline(baseline, value);
+
+    match migrations::deserialize_config(value) {
+        Ok((config, warnings)) => {
+            for warning in &warnings {
+                eprintln!("[ProxyPal] Config field reset to default: {}", warning);
+            }
+            if !warnings.is_empty() && status.backup_paths.is_empty() {
+                // The next save drops the unreadable values, keep them recoverable (once)
+                if previous.warnings.starts_with(&warnings) && !previous.backup_paths.is_empty() {
+                    status.backup_paths = previous.backup_paths.clone();
+                } else {
+                    match backup_config_file(&raw, original_version) {
+                        Ok(path) => status.backup_paths.push(path),
+                        Err(e) => eprintln!("[ProxyPal] {}", e),
+                    }
+                }
+            }
+            status.warnings = warnings;
+            Some(config)
+        }
+        Err(e) => {
+            status.error = Some(e);
+            None
+        }
+    }
+}
+
+/// Load config from file, migrating older versions.
+///
+/// If config.json can't be read, defaults are returned, the error is recorded for
+/// get_config_load_status and save_config_to_file refuses to overwrite the file.
+pub fn load_config() -> AppConfig {
+    let previous = get_config_load_status();
+    let mut status = ConfigLoadStatus::new();
+    // Read once, for layering and for the fields overrides can't touch
+    let (baseline, baseline_warnings) = load_baseline_for_config();
+    let locked = baseline.as_ref().map(|b| b.locked.clone()).unwrap_or_default();
+
+    let config = read_config_file(&mut status, &previous, baseline.as_ref());
+    status.warnings.extend(baseline_warnings);
+    match config {
+        Some(config) => {
+            let config = with_overrides(config, &locked, &mut status);
+            let migrated = !status.migrations.is_empty();
+            // Migrations run once per file; keep reporting them for the rest of the session
+            if !migrated && !previous.migrations.is_empty() {
+                status.migrations = previous.migrations;
+                status.backup_paths = previous.backup_paths;
+            }
+            *LOAD_STATUS.lock().unwrap() = status;
+            if migrated {
+                if let Err(e) = save_config_to_file(&config) {
+                    eprintln!("[ProxyPal] Failed to save migrated config: {}", e);
+                }
+            }
+            config
+        }
+        None => {
+            eprintln!(
+                "[ProxyPal] Error: {}",
+                status.error.as_deref().unwrap_or("Failed to load config")
+            );
+            let config = with_overrides(AppConfig::default(), &locked, &mut status);
+            *LOAD_STATUS.lock().unwrap() = status;
+            config
+        }
+    }
+}
+
+/// Apply PROXYPAL_* environment variables and `--set` arguments (see config::overrides)
+fn with_overrides(config: AppConfig, locked: &[String], status: &mut ConfigLoadStatus) -> AppConfig {
+    let (config, warnings) = overrides::apply_overrides(config, locked);
+    for warning in &warnings {
+        eprintln!("[ProxyPal] Config override ignored: {}", warning);
+    }
+    status.warnings.extend(warnings);
+    config
+}
+
+/// Save config to file
+/// Uses atomic write (write to temp file then rename) to prevent corruption
+pub fn save_config_to_file(config: &AppConfig) -> Result<(), String> {
+    if let Some(error) = &LOAD_STATUS.lock().unwrap().error {
+        return Err(format!(
+            "Not saving: the existing config.json could not be loaded ({}). Fix the file and reload it, or discard it first.",
+            error
+        ));
+    }
+
+    let path = get_config_path();
+    let config_dir = path.parent().ok_or("Invalid config path")?;
+
+    // Ensure config directory exists
+    if let Err(e) = std::fs::create_dir_all(config_dir) {
+        return Err(format!(
+            "Failed to create config directory '{}': {}",
+            config_dir.display(),
+            e
+        ));
+    }
+
+    // Kept for the first snapshot, so the file being replaced can be restored too
+    let previous_raw = std::fs::read_to_string(&path).ok();
+
+    // Serialize the user's layer to JSON (baseline values stay in the baseline),
+    // with secrets encrypted
+    let mut value = baseline::to_user_layer(config)
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+    let user_layer = value.clone();
+    secrets::seal_secrets(&mut value)?;
+    let data = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    // Write to temporary file first, then rename for atomic write
+    let temp_path = path.with_extension("tmp");
+
+    // Try writing to temp file with retry for Windows file locking issues
+    let mut last_error = String::new();
+    for attempt in 0..3 {
+        match write_private(&temp_path, data.as_bytes()) {
+            Ok(_) => break,
+            Err(e) => {
+                last_error = e.clone();
+                if attempt < 2 {
+                    eprintln!(
+                        "[ProxyPal] Save attempt {} failed, retrying: {}",
+                        attempt + 1,
+                        e
+                    );
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+            }
+        }
+    }
+
+    // Verify temp file was written successfully
+    if !temp_path.exists() {
+        return Err(format!(
+            "Failed to write config to temp file (attempted 3 times): {}",
+            last_error
+        ));
+    }
+
+    // Atomic rename from temp to actual config file
+    std::fs::rename(&temp_path, &path)
+        .map_err(|e| format!("Failed to rename temp file to config: {}", e))?;
+
+    eprintln!("[ProxyPal] Config saved successfully to: {:?}", path);
+
+    // A failed snapshot must not fail the save itself
+    if let Err(e) = snapshots::record_save(config, &user_layer, previous_raw.as_deref()) {
+        eprintln!("[ProxyPal] Failed to snapshot config: {}", e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,383 @@
+//! Per-launch overrides from the environment and the command line, applied on top of
+//! the loaded config and never written back to config.json.
+//!
+//! - `PROXYPAL_<FIELD>=value`, e.g. `PROXYPAL_PORT=8400`, `PROXYPAL_DEBUG=true`,
+//!   `PROXYPAL_PROXY_URL=http://proxy:3128`, `PROXYPAL_ROUTING_STRATEGY=fill-first`.
+//!   Nested fields use a double underscore: `PROXYPAL_COPILOT__PORT=4142`.
+//! - `--set key=value` (or `--set=key=value`), with camelCase or snake_case keys and
+//!   dots for nesting: `--set port=8400 --set copilot.port=4142`. These win over the
+//!   environment.
+//!
+//! Values for string fields are taken as is; anything else is parsed as JSON
+//! (`true`, `8400`, `["claude-*"]`). Fields locked by the organization baseline
+//! can't be overridden.
+
+use serde_json::{Map, Value};
+use std::sync::{Mutex, OnceLock};
+
+use super::AppConfig;
+use crate::types::ConfigOverride;
+
+const ENV_PREFIX: &str = "PROXYPAL_";
+
+/// Variables with the prefix that configure something else
+const NON_FIELD_VARS: &[&str] = &[super::baseline::BASELINE_PATH_ENV, crate::daemon::SIDECAR_PATH_ENV];
+
+/// An override as given, before it is matched against the config
+struct RawOverride {
+    /// Key segments, in any case, with or without underscores
+    path: Vec<String>,
+    value: String,
+    source: String,
+}
+
+/// An override that was applied by the last load_config
+#[derive(Debug, Clone)]
+struct AppliedOverride {
+    /// camelCase key segments
+    path: Vec<String>,
+    value: Value,
+    /// What the config held before the override, written to config.json instead
+    previous: Value,
+    source: String,
+}
+
+static APPLIED: Mutex<Vec<AppliedOverride>> = Mutex::new(Vec::new());
+
+/// The environment and `--set` arguments, read once per launch
+static LAUNCH_OVERRIDES: OnceLock<Vec<RawOverride>> = OnceLock::new();
+
+fn launch_overrides() -> &'static [RawOverride] {
+    LAUNCH_OVERRIDES.get_or_init(|| {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        raw_overrides(std::env::vars(), &args)
+    })
+}
+
+/// Environment overrides first, so `--set` wins
+fn raw_overrides(vars: impl Iterator<Item = (String, String)>, args: &[String]) -> Vec<RawOverride> {
+    env_overrides(vars).into_iter().chain(arg_overrides(args)).collect()
+}
+
+fn env_overrides(vars: impl Iterator<Item = (String, String)>) -> Vec<RawOverride> {
+    let mut overrides: Vec<RawOverride> = vars
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX) && !NON_FIELD_VARS.contains(&name.as_str()))
+        .map(|(name, value)| RawOverride {
+            path: name[ENV_PREFIX.len()..].split("__").map(str::to_string).collect(),
+            value,
+            source: name,
+        })
+        .collect();
+    // Deterministic order when two variables name the same field
+    overrides.sort_by(|a, b| a.source.cmp(&b.source));
+    overrides
+}
+
+fn arg_overrides(args: &[String]) -> Vec<RawOverride> {
+    let mut overrides = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let assignment = match arg.strip_prefix("--set=") {
+            Some(assignment) => assignment,
+            None if arg == "--set" => match args.next() {
+                Some(assignment) => assignment.as_str(),
+                None => break,
+            },
+            None => continue,
+        };
+        let Some((key, value)) = assignment.split_once('=') else {
+            eprintln!("[ProxyPal] Ignoring --set {}: expected key=value", assignment);
+            continue;
+        };
+        overrides.push(RawOverride {
+            path: key.split('.').map(str::to_string).collect(),
+            value: value.to_string(),
+            source: format!("--set {}", key),
+        });
+    }
+    overrides
+}
+
+/// Compare keys across camelCase, snake_case and SCREAMING_SNAKE_CASE
+fn normalize(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find the config value a raw path names, returning its camelCase path
+fn resolve<'a>(config: &'a mut Value, path: &[String]) -> Option<(Vec<String>, &'a mut Value)> {
+    let mut current = config;
+    let mut resolved = Vec::new();
+    for segment in path {
+        let map = current.as_object_mut()?;
+        let key = map.keys().find(|k| normalize(k) == normalize(segment))?.clone();
+        resolved.push(key.clone());
+        current = map.get_mut(&key)?;
+    }
+    Some((resolved, current))
+}
+
+fn parse_value(raw: &str, current: &Value) -> Result<Value, String> {
+    match current {
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+        _ => serde_json::from_str(raw).map_err(|e| format!("\"{}\" is not valid JSON: {}", raw, e)),
+    }
+}
+
+/// Apply the environment and command line overrides to a freshly loaded config.
+/// Problems (unknown fields, bad values, locked fields) are returned as warnings.
+pub fn apply_overrides(config: AppConfig, locked: &[String]) -> (AppConfig, Vec<String>) {
+    let (config, warnings, applied) = apply(config, launch_overrides(), locked);
+    for a in &applied {
+        eprintln!("[ProxyPal] Config override: {} = {} ({})", a.path.join("."), shown_value(a), a.source);
+    }
+    *APPLIED.lock().unwrap() = applied;
+    (config, warnings)
+}
+
+/// Secrets are only logged as set, stderr ends up in the journal for the daemon
+fn shown_value(applied: &AppliedOverride) -> String {
+    if super::secrets::is_secret_path(&applied.path) {
+        "••••".to_string()
+    } else {
+        applied.value.to_string()
+    }
+}
+
+fn apply(
+    config: AppConfig,
+    overrides: &[RawOverride],
+    locked: &[String],
+) -> (AppConfig, Vec<String>, Vec<AppliedOverride>) {
+    let mut warnings = Vec::new();
+    let mut applied: Vec<AppliedOverride> = Vec::new();
+    let Ok(mut value) = serde_json::to_value(&config) else {
+        return (config, warnings, applied);
+    };
+
+    for raw in overrides {
+        let Some((path, target)) = resolve(&mut value, &raw.path) else {
+            warnings.push(format!("{}: no such config field", raw.source));
+            continue;
+        };
+        if path.first().is_some_and(|field| locked.contains(field)) {
+            warnings.push(format!("{}: {} is locked by your organization's baseline", raw.source, path[0]));
+            continue;
+        }
+        let new_value = match parse_value(&raw.value, target) {
+            Ok(v) => v,
+            Err(e) => {
+                warnings.push(format!("{}: {}", raw.source, e));
+                continue;
+            }
+        };
+        let previous = std::mem::replace(target, new_value.clone());
+        // A later override of the same field replaces the earlier one
+        let previous = match applied.iter().position(|a| a.path == path) {
+            Some(i) => applied.remove(i).previous,
+            None => previous,
+        };
+        applied.push(AppliedOverride {
+            path,
+            value: new_value,
+            previous,
+            source: raw.source.clone(),
+        });
+    }
+
+    if applied.is_empty() {
+        return (config, warnings, applied);
+    }
+    match serde_json::from_value::<AppConfig>(value) {
+        Ok(overridden) => (overridden, warnings, applied),
+        Err(e) => {
+            warnings.push(format!("Overrides ignored, they don't fit the config: {}", e));
+            (config, warnings, Vec::new())
+        }
+    }
+}
+
+/// Put back the pre-override values in a config document about to be saved. A field
+/// the user changed since loading is saved as changed.
+pub fn restore_overridden(config: &mut Map<String, Value>) {
+    restore(config, &APPLIED.lock().unwrap());
+}
+
+fn restore(config: &mut Map<String, Value>, applied: &[AppliedOverride]) {
+    let mut doc = Value::Object(std::mem::take(config));
+    for a in applied {
+        if let Some((_, target)) = resolve(&mut doc, &a.path) {
+            if *target == a.value {
+                *target = a.previous.clone();
+            }
+        }
+    }
+    if let Value::Object(map) = doc {
+        *config = map;
+    }
+}
+
+/// Overrides in effect, for get_config_sources
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn active_overrides() -> Vec<ConfigOverride> {
+    APPLIED
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|a| ConfigOverride {
+            field: a.path.join("."),
+            source: a.source.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Iterator<Item = (String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn keys_resolve_in_any_case() {
+        let mut doc = serde_json::to_value(AppConfig::default()).unwrap();
+        for key in [
+            &["copilot", "port"][..],
+            &["COPILOT", "PORT"],
+            &["proxyUrl"],
+            &["proxy_url"],
+            &["PROXY_URL"],
+            &["proxy-url"],
+        ] {
+            let key: Vec<String> = key.iter().map(|k| k.to_string()).collect();
+            let (resolved, _) = resolve(&mut doc, &key).unwrap_or_else(|| panic!("{:?}", key));
+            assert_eq!(resolved.join("."), if key.len() == 2 { "copilot.port" } else { "proxyUrl" });
+        }
+        assert!(resolve(&mut doc, &path("noSuchField")).is_none());
+        assert!(resolve(&mut doc, &path("port.deeper")).is_none());
+    }
+
+    #[test]
+    fn env_and_args_are_applied() {
+        let raw = raw_overrides(
+            vars(&[("PROXYPAL_COPILOT__PORT", "4142"), ("PROXYPAL_PROXY_URL", "http://proxy:3128"), ("HOME", "/root")]),
+            &args(&["--sidecar", "/bin/cli-proxy-api", "--set", "debug=true", "--set=routingStrategy=fill-first"]),
+        );
+        let (config, warnings, applied) = apply(AppConfig::default(), &raw, &[]);
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(config.copilot.port, 4142);
+        // String fields are taken as is, without JSON quoting
+        assert_eq!(config.proxy_url, "http://proxy:3128");
+        assert!(config.debug);
+        assert_eq!(config.routing_strategy, "fill-first");
+        assert_eq!(applied.len(), 4);
+    }
+
+    #[test]
+    fn set_wins_over_the_environment() {
+        let raw = raw_overrides(vars(&[("PROXYPAL_PORT", "8400")]), &args(&["--set", "port=8500"]));
+        let (config, _, applied) = apply(AppConfig::default(), &raw, &[]);
+
+        assert_eq!(config.port, 8500);
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].source, "--set port");
+    }
+
+    #[test]
+    fn repeated_override_keeps_the_original_value() {
+        let config = AppConfig {
+            port: 8317,
+            ..Default::default()
+        };
+        let raw = raw_overrides(vars(&[("PROXYPAL_PORT", "8400")]), &args(&["--set", "port=8500"]));
+        let (_, _, applied) = apply(config, &raw, &[]);
+
+        assert_eq!(applied[0].previous, serde_json::json!(8317));
+    }
+
+    #[test]
+    fn problems_are_warnings() {
+        let raw = raw_overrides(
+            vars(&[("PROXYPAL_NO_SUCH_FIELD", "1"), ("PROXYPAL_BASELINE_CONFIG", "/etc/baseline.json")]),
+            &args(&["--set", "port=not-a-number", "--set", "debug"]),
+        );
+        let (config, warnings, applied) = apply(AppConfig::default(), &raw, &[]);
+
+        assert!(applied.is_empty());
+        assert_eq!(config.port, AppConfig::default().port);
+        // PROXYPAL_BASELINE_CONFIG configures something else and isn't reported
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].starts_with("PROXYPAL_NO_SUCH_FIELD"));
+        assert!(warnings[1].starts_with("--set port"));
+    }
+
+    #[test]
+    fn overrides_that_dont_fit_the_config_are_dropped() {
+        let raw = raw_overrides(vars(&[]), &args(&["--set", "port=-1", "--set", "debug=true"]));
+        let (config, warnings, applied) = apply(AppConfig::default(), &raw, &[]);
+
+        assert!(applied.is_empty());
+        assert!(!config.debug);
+        assert!(warnings[0].starts_with("Overrides ignored"), "{:?}", warnings);
+    }
+
+    #[test]
+    fn locked_fields_are_refused() {
+        let raw = raw_overrides(vars(&[("PROXYPAL_PORT", "8400")]), &args(&["--set", "copilot.port=4142"]));
+        let locked = vec!["port".to_string(), "copilot".to_string()];
+        let (config, warnings, applied) = apply(AppConfig::default(), &raw, &locked);
+
+        assert!(applied.is_empty());
+        assert_eq!(config.port, AppConfig::default().port);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("port is locked"), "{:?}", warnings);
+        assert!(warnings[1].contains("copilot is locked"), "{:?}", warnings);
+    }
+
+    #[test]
+    fn restore_only_reverts_values_still_overridden() {
+        let config = AppConfig {
+            port: 8317,
+            debug: false,
+            ..Default::default()
+        };
+        let raw = raw_overrides(vars(&[("PROXYPAL_PORT", "8400"), ("PROXYPAL_DEBUG", "true")]), &[]);
+        let (mut config, _, applied) = apply(config, &raw, &[]);
+        // The user changes the port in the app after loading
+        config.port = 9000;
+
+        let Value::Object(mut doc) = serde_json::to_value(&config).unwrap() else { unreachable!() };
+        restore(&mut doc, &applied);
+
+        assert_eq!(doc["port"], 9000);
+        assert_eq!(doc["debug"], false);
+    }
+
+    #[test]
+    fn secrets_are_masked_in_the_log() {
+        let raw = raw_overrides(
+            vars(&[("PROXYPAL_MANAGEMENT_KEY", "hunter2-key"), ("PROXYPAL_PORT", "8400")]),
+            &args(&["--set", "copilot.githubToken=ghu_secret"]),
+        );
+        let (_, _, applied) = apply(AppConfig::default(), &raw, &[]);
+        let shown: Vec<String> = applied.iter().map(shown_value).collect();
+
+        assert_eq!(shown, vec!["••••", "8400", "••••"]);
+    }
+}
@@ -0,0 +1,348 @@
+//! Named configuration profiles, stored as JSON overlays in <config dir>/profiles/.
+
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::{get_proxypal_config_dir, write_private, AppConfig};
+use crate::types::{ConfigProfile, ProfileSummary};
+
+const ACTIVE_PROFILE_FILE: &str = ".active";
+
+pub fn get_profiles_dir() -> PathBuf {
+    get_proxypal_config_dir().join("profiles")
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Profile names double as file names, so keep them to a portable character set
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if trimmed != name {
+        return Err("Profile name cannot start or end with whitespace".to_string());
+    }
+    if name.len() > 64 {
+        return Err("Profile name is too long (max 64 characters)".to_string());
+    }
+    if name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ' '))
+    {
+        return Err(format!(
+            "Invalid profile name \"{}\": use letters, digits, spaces, '-', '_' or '.'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn profile_path(name: &str) -> Result<PathBuf, String> {
+    validate_profile_name(name)?;
+    Ok(get_profiles_dir().join(format!("{}.json", name)))
+}
+
+/// Merge `overlay` into `base`: objects merge key by key, anything else is replaced
+pub fn merge_json(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Apply a profile overlay on top of a config
+pub fn apply_overlay(config: &AppConfig, overlay: &Value) -> Result<AppConfig, String> {
+    if !overlay.is_object() {
+        return Err("Profile overlay must be a JSON object".to_string());
+    }
+    let mut merged = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    merge_json(&mut merged, overlay);
+    serde_json::from_value(merged).map_err(|e| format!("Profile does not fit the config: {}", e))
+}
+
+pub fn load_profile(name: &str) -> Result<ConfigProfile, String> {
+    let path = profile_path(name)?;
+    let data = std::fs::read_to_string(&path)
+        .map_err(|_| format!("Profile \"{}\" not found", name))?;
+    let mut profile: ConfigProfile = serde_json::from_str(&data)
+        .map_err(|e| format!("Failed to parse profile \"{}\": {}", name, e))?;
+    super::secrets::open_secrets(&mut profile.overlay)?;
+    Ok(profile)
+}
+
+/// Write a profile. Secrets in the overlay are encrypted and the file is owner-only on Unix.
+pub fn save_profile(profile: &ConfigProfile) -> Result<(), String> {
+    let path = profile_path(&profile.name)?;
+    std::fs::create_dir_all(get_profiles_dir())
+        .map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+
+    let mut sealed = profile.clone();
+    super::secrets::seal_secrets(&mut sealed.overlay)?;
+    let data = serde_json::to_string_pretty(&sealed)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    let temp_path = path.with_extension("tmp");
+    write_private(&temp_path, data.as_bytes()).map_err(|e| format!("Failed to write profile: {}", e))?;
+    std::fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save profile: {}", e))
+}
+
+pub fn profile_exists(name: &str) -> bool {
+    profile_path(name).map(|p| p.exists()).unwrap_or(false)
+}
+
+/// Create a new profile. Without an overlay the whole current config is captured.
+pub fn create_profile(
+    name: &str,
+    description: String,
+    overlay: Option<Value>,
+    current: &AppConfig,
+) -> Result<ConfigProfile, String> {
+    validate_profile_name(name)?;
+    if profile_exists(name) {
+        return Err(format!("Profile \"{}\" already exists", name));
+    }
+    let overlay = match overlay {
+        Some(overlay) => {
+            // Reject overlays that would not produce a loadable config
+            apply_overlay(current, &overlay)?;
+            overlay
+        }
+        None => serde_json::to_value(current).map_err(|e| e.to_string())?,
+    };
+
+    let now = now_millis();
+    let profile = ConfigProfile {
+        name: name.to_string(),
+        description,
+        overlay,
+        created_at: now,
+        updated_at: now,
+    };
+    save_profile(&profile)?;
+    Ok(profile)
+}
+
+pub fn clone_profile(source: &str, name: &str) -> Result<ConfigProfile, String> {
+    validate_profile_name(name)?;
+    if profile_exists(name) {
+        return Err(format!("Profile \"{}\" already exists", name));
+    }
+    let now = now_millis();
+    let profile = ConfigProfile {
+        name: name.to_string(),
+        created_at: now,
+        updated_at: now,
+        ..load_profile(source)?
+    };
+    save_profile(&profile)?;
+    Ok(profile)
+}
+
+pub fn delete_profile(name: &str) -> Result<(), String> {
+    let path = profile_path(name)?;
+    if !path.exists() {
+        return Err(format!("Profile \"{}\" not found", name));
+    }
+    let was_active = get_active_profile().as_deref() == Some(name);
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete profile: {}", e))?;
+    if was_active {
+        set_active_profile(None)?;
+    }
+    Ok(())
+}
+
+pub fn get_active_profile() -> Option<String> {
+    let name = std::fs::read_to_string(get_profiles_dir().join(ACTIVE_PROFILE_FILE)).ok()?;
+    let name = name.trim().to_string();
+    profile_exists(&name).then_some(name)
+}
+
+pub fn set_active_profile(name: Option<&str>) -> Result<(), String> {
+    let path = get_profiles_dir().join(ACTIVE_PROFILE_FILE);
+    match name {
+        Some(name) => {
+            std::fs::create_dir_all(get_profiles_dir())
+                .map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+            std::fs::write(&path, name).map_err(|e| format!("Failed to record active profile: {}", e))
+        }
+        None if path.exists() => {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to clear active profile: {}", e))
+        }
+        None => Ok(()),
+    }
+}
+
+pub fn list_profiles() -> Result<Vec<ProfileSummary>, String> {
+    let dir = get_profiles_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let active = get_active_profile();
+
+    let mut profiles = Vec::new();
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read profiles: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        match load_profile(name) {
+            Ok(profile) => {
+                let mut fields: Vec<String> = profile
+                    .overlay
+                    .as_object()
+                    .map(|o| o.keys().cloned().collect())
+                    .unwrap_or_default();
+                fields.sort();
+                profiles.push(ProfileSummary {
+                    active: active.as_deref() == Some(profile.name.as_str()),
+                    name: profile.name,
+                    description: profile.description,
+                    fields,
+                    updated_at: profile.updated_at,
+                });
+            }
+            Err(e) => eprintln!("[ProxyPal] Skipping profile {}: {}", path.display(), e),
+        }
+    }
+    profiles.sort_by_key(|p| p.name.to_lowercase());
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn create(name: &str, overlay: Value) -> ConfigProfile {
+        create_profile(name, String::new(), Some(overlay), &AppConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn names_are_checked() {
+        for name in ["work", "Work laptop", "team-a_v1.2"] {
+            assert!(validate_profile_name(name).is_ok(), "{}", name);
+        }
+        let too_long = "a".repeat(65);
+        for name in ["", " ", " work", "work ", ".hidden", ".active", "../config", "a/b", "a\\b", "a:b", too_long.as_str()] {
+            assert!(validate_profile_name(name).is_err(), "{}", name);
+        }
+        assert!(validate_profile_name(&"a".repeat(64)).is_ok());
+    }
+
+    #[test]
+    fn merge_json_merges_objects_and_replaces_the_rest() {
+        let mut base = json!({ "port": 8317, "copilot": { "enabled": false, "port": 4141 }, "claudeApiKeys": [1, 2] });
+        merge_json(&mut base, &json!({ "copilot": { "enabled": true }, "claudeApiKeys": [3], "debug": true }));
+        assert_eq!(
+            base,
+            json!({ "port": 8317, "copilot": { "enabled": true, "port": 4141 }, "claudeApiKeys": [3], "debug": true })
+        );
+    }
+
+    #[test]
+    fn partial_overlay_keeps_the_rest_of_the_config() {
+        let current = AppConfig {
+            port: 8400,
+            ..Default::default()
+        };
+        let config = apply_overlay(&current, &json!({ "copilot": { "enabled": true }, "debug": true })).unwrap();
+
+        assert_eq!(config.port, 8400);
+        assert!(config.debug);
+        assert!(config.copilot.enabled);
+        assert_eq!(config.copilot.port, current.copilot.port);
+        assert!(apply_overlay(&current, &json!([1])).is_err());
+        assert!(apply_overlay(&current, &json!({ "port": "not a port" }))
+            .unwrap_err()
+            .contains("does not fit"));
+    }
+
+    #[test]
+    fn created_profile_round_trips() {
+        let _dir = crate::config::test_config_dir();
+        create("work", json!({ "debug": true }));
+
+        assert!(create_profile("work", String::new(), None, &AppConfig::default())
+            .unwrap_err()
+            .contains("already exists"));
+        assert!(create_profile("bad", String::new(), Some(json!({ "port": -1 })), &AppConfig::default()).is_err());
+        // Without an overlay the whole config is captured
+        let full = create_profile("full", String::new(), None, &AppConfig::default()).unwrap();
+        assert_eq!(full.overlay, serde_json::to_value(AppConfig::default()).unwrap());
+
+        assert_eq!(load_profile("work").unwrap().overlay, json!({ "debug": true }));
+        let names: Vec<String> = list_profiles().unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["full", "work"]);
+    }
+
+    #[test]
+    fn overlay_secrets_are_sealed_on_disk() {
+        let _dir = crate::config::test_config_dir();
+        let overlay = json!({ "proxyPassword": "hunter2", "claudeApiKeys": [{ "apiKey": "sk-ant-1" }] });
+        create("secrets", overlay.clone());
+
+        let path = get_profiles_dir().join("secrets.json");
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("hunter2") && !on_disk.contains("sk-ant-1"), "{}", on_disk);
+        assert_eq!(load_profile("secrets").unwrap().overlay, overlay);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn clone_copies_the_overlay_but_not_the_active_state() {
+        let _dir = crate::config::test_config_dir();
+        create("work", json!({ "proxyPassword": "hunter2" }));
+        set_active_profile(Some("work")).unwrap();
+
+        let clone = clone_profile("work", "work copy").unwrap();
+
+        assert_eq!(clone.overlay, json!({ "proxyPassword": "hunter2" }));
+        assert_eq!(get_active_profile().as_deref(), Some("work"));
+        let summaries = list_profiles().unwrap();
+        assert_eq!(
+            summaries.iter().map(|p| (p.name.as_str(), p.active)).collect::<Vec<_>>(),
+            vec![("work", true), ("work copy", false)]
+        );
+        assert!(clone_profile("work", "work copy").is_err());
+        assert!(clone_profile("missing", "other").unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn deleting_the_active_profile_clears_it() {
+        let _dir = crate::config::test_config_dir();
+        create("work", json!({ "debug": true }));
+        create("home", json!({ "debug": false }));
+        set_active_profile(Some("work")).unwrap();
+
+        delete_profile("home").unwrap();
+        assert_eq!(get_active_profile().as_deref(), Some("work"));
+
+        delete_profile("work").unwrap();
+        assert_eq!(get_active_profile(), None);
+        assert!(!get_profiles_dir().join(ACTIVE_PROFILE_FILE).exists());
+        assert!(delete_profile("work").unwrap_err().contains("not found"));
+    }
+}
@@ -0,0 +1,262 @@
+//! JSON Schemas for config.json and proxy-config.yaml, generated from the Rust types.
+//!
+//! Both are written next to config.json (config.schema.json, proxy-config.schema.json)
+//! so editors can validate and autocomplete hand edits. The proxy-config schema has no
+//! required fields: it also describes proxy-config-custom.yaml, which only holds the
+//! keys a user changes. Keys we don't generate are allowed, CLIProxyAPI has more.
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use super::validation::{AMP_ROUTING_MODES, ROUTING_STRATEGIES, THINKING_BUDGET_MODES};
+use super::{get_proxypal_config_dir, AppConfig};
+use crate::proxy::sidecar_config::{SidecarConfig, GPT5_REASONING_SUFFIXES};
+use crate::types::ConfigSchemas;
+
+/// Copilot account types copilot-api accepts for --account-type
+const COPILOT_ACCOUNT_TYPES: &[&str] = &["individual", "business", "enterprise"];
+
+/// Most errors listed when a YAML document doesn't match the schema
+const MAX_REPORTED_ERRORS: usize = 10;
+
+pub fn get_config_schema_path() -> PathBuf {
+    get_proxypal_config_dir().join("config.schema.json")
+}
+
+pub fn get_proxy_config_schema_path() -> PathBuf {
+    get_proxypal_config_dir().join("proxy-config.schema.json")
+}
+
+/// A string limited to `values`; empty strings are accepted as "use the default"
+fn string_enum(values: &[&str]) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        enum_values: Some(values.iter().chain([&""]).map(|v| json!(v)).collect()),
+        ..Default::default()
+    }
+    .into()
+}
+
+pub(crate) fn routing_strategy(_: &mut SchemaGenerator) -> Schema {
+    string_enum(ROUTING_STRATEGIES)
+}
+
+pub(crate) fn thinking_budget_mode(_: &mut SchemaGenerator) -> Schema {
+    string_enum(THINKING_BUDGET_MODES)
+}
+
+pub(crate) fn amp_routing_mode(_: &mut SchemaGenerator) -> Schema {
+    string_enum(AMP_ROUTING_MODES)
+}
+
+pub(crate) fn reasoning_effort_level(_: &mut SchemaGenerator) -> Schema {
+    string_enum(GPT5_REASONING_SUFFIXES)
+}
+
+pub(crate) fn copilot_account_type(_: &mut SchemaGenerator) -> Schema {
+    string_enum(COPILOT_ACCOUNT_TYPES)
+}
+
+/// Drop the generated uuid schemars records as the default of `id` fields
+fn strip_id_defaults(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Object(id)) = map
+                .get_mut("properties")
+                .and_then(|p| p.get_mut("id"))
+            {
+                id.remove("default");
+            }
+            map.values_mut().for_each(strip_id_defaults);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_id_defaults),
+        _ => {}
+    }
+}
+
+fn strip_required(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("required");
+            map.values_mut().for_each(strip_required);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_required),
+        _ => {}
+    }
+}
+
+/// Schema of config.json
+pub fn app_config_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(AppConfig))
+        .expect("schema serializes");
+    strip_id_defaults(&mut schema);
+    schema
+}
+
+/// Schema of proxy-config.yaml and proxy-config-custom.yaml
+pub fn proxy_config_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(SidecarConfig))
+        .expect("schema serializes");
+    strip_required(&mut schema);
+    if let Some(map) = schema.as_object_mut() {
+        map.insert("title".to_string(), json!("CLIProxyAPI config"));
+    }
+    schema
+}
+
+/// Check a proxy-config YAML document against proxy_config_schema
+pub fn validate_proxy_config_yaml(yaml: &str) -> Result<(), String> {
+    let document: Value = match serde_yaml::from_str::<Option<Value>>(yaml) {
+        Ok(document) => document.unwrap_or(Value::Null),
+        Err(e) => return Err(format!("Invalid YAML: {}", e)),
+    };
+    if document.is_null() {
+        return Ok(());
+    }
+
+    let validator = jsonschema::validator_for(&proxy_config_schema())
+        .map_err(|e| format!("Invalid proxy config schema: {}", e))?;
+    let errors: Vec<String> = validator
+        .iter_errors(&document)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            if path.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}: {}", path, error)
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let mut summary = errors
+        .iter()
+        .take(MAX_REPORTED_ERRORS)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if errors.len() > MAX_REPORTED_ERRORS {
+        summary.push_str(&format!("\n... and {} more", errors.len() - MAX_REPORTED_ERRORS));
+    }
+    Err(format!("Config YAML doesn't match the proxy config schema:\n{}", summary))
+}
+
+/// Write both schemas next to config.json
+pub fn write_schema_files() -> Result<ConfigSchemas, String> {
+    let schemas = ConfigSchemas {
+        app_config: app_config_schema(),
+        proxy_config: proxy_config_schema(),
+        app_config_path: get_config_schema_path().to_string_lossy().to_string(),
+        proxy_config_path: get_proxy_config_schema_path().to_string_lossy().to_string(),
+    };
+    std::fs::create_dir_all(get_proxypal_config_dir())
+        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    for (path, schema) in [
+        (get_config_schema_path(), &schemas.app_config),
+        (get_proxy_config_schema_path(), &schemas.proxy_config),
+    ] {
+        let data = serde_json::to_string_pretty(schema).map_err(|e| e.to_string())?;
+        std::fs::write(&path, data)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(schemas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::custom_config::{build_effective_config, render_effective_config};
+
+    fn config_with_providers() -> AppConfig {
+        let mut config = serde_json::to_value(AppConfig::default()).unwrap();
+        config.as_object_mut().unwrap().extend(json!({
+            "claudeApiKeys": [{ "apiKey": "sk-ant-1", "baseUrl": "https://api.anthropic.com", "prefix": "team" }],
+            "geminiApiKeys": [{ "apiKey": "AIza-1" }],
+            "ampOpenaiProviders": [{
+                "id": "p1",
+                "name": "local",
+                "baseUrl": "http://localhost:11434/v1",
+                "apiKey": "ollama",
+                "models": [{ "name": "llama3", "alias": "llama" }]
+            }],
+            "ampModelMappings": [{ "name": "claude-opus-4", "alias": "gemini-2.5-pro" }],
+            "copilot": { "enabled": true, "port": 4141 },
+        })
+        .as_object()
+        .unwrap()
+        .clone());
+        serde_json::from_value(config).unwrap()
+    }
+
+    #[test]
+    fn generated_config_matches_the_schema() {
+        let _dir = crate::config::test_config_dir();
+        // Merged in like a user's own settings, including a key CLIProxyAPI has and we don't model
+        std::fs::write(
+            crate::config::get_custom_proxy_config_path(),
+            "request-retry: 5\nws-auth: true\n",
+        )
+        .unwrap();
+        for (config, proxy_url) in [
+            (AppConfig::default(), None),
+            (config_with_providers(), Some("http://proxy.corp:3128".to_string())),
+        ] {
+            let effective = build_effective_config(&config, proxy_url).unwrap();
+            let yaml = render_effective_config(&effective).unwrap();
+            assert!(yaml.contains("ws-auth: true"), "{}", yaml);
+            validate_proxy_config_yaml(&yaml).unwrap_or_else(|e| panic!("{}\n{}", e, yaml));
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_accepted() {
+        validate_proxy_config_yaml("port: 8317\nws-auth: true\nsome-future-option:\n  enabled: true\n").unwrap();
+        validate_proxy_config_yaml("").unwrap();
+        validate_proxy_config_yaml("# only a comment\n").unwrap();
+    }
+
+    #[test]
+    fn wrongly_typed_keys_are_rejected() {
+        let error = validate_proxy_config_yaml("port: not-a-port\ndebug: yes please\n").unwrap_err();
+        assert!(error.contains("/port"), "{}", error);
+        assert!(error.contains("/debug"), "{}", error);
+
+        assert!(validate_proxy_config_yaml("request-retry: -1\n").is_err());
+        assert!(validate_proxy_config_yaml("api-keys: sk-not-a-list\n").is_err());
+        assert!(validate_proxy_config_yaml("port: [").unwrap_err().starts_with("Invalid YAML"));
+    }
+
+    #[test]
+    fn many_errors_are_summarized() {
+        let keys: Vec<String> = (0..MAX_REPORTED_ERRORS + 2).map(|i| i.to_string()).collect();
+        let yaml = format!("api-keys: [{}]\n", keys.join(", "));
+        let error = validate_proxy_config_yaml(&yaml).unwrap_err();
+        assert!(error.ends_with("... and 2 more"), "{}", error);
+    }
+
+    #[test]
+    fn app_schema_checks_enums() {
+        let validator = jsonschema::validator_for(&app_config_schema()).unwrap();
+        let mut config = serde_json::to_value(AppConfig::default()).unwrap();
+        assert!(validator.is_valid(&config));
+
+        config["routingStrategy"] = json!("");
+        assert!(validator.is_valid(&config));
+        config["routingStrategy"] = json!("random");
+        assert!(!validator.is_valid(&config));
+    }
+
+    #[test]
+    fn schema_files_are_written_next_to_the_config() {
+        let dir = crate::config::test_config_dir();
+        let schemas = write_schema_files().unwrap();
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("proxy-config.schema.json")).unwrap()).unwrap();
+        assert_eq!(written, schemas.proxy_config);
+        assert!(dir.path().join("config.schema.json").exists());
+    }
+}
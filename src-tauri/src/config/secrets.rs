@@ -0,0 +1,342 @@
+//! Encryption of secret fields in config.json (and profiles/snapshots) at rest.
+//!
+//! Secrets are sealed with ChaCha20-Poly1305 under a per-install key in secret.key
+//! (owner-only on Unix) and stored as `enc:v1:<base64(nonce || ciphertext)>`.
+//! Everything in memory, including AppConfig, holds plaintext.
+
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::get_proxypal_config_dir;
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+/// Secret fields as camelCase JSON paths; "*" matches every element of a list
+pub const SECRET_PATHS: &[&[&str]] = &[
+    &["proxyPassword"],
+    &["ampApiKey"],
+    &["managementKey"],
+    &["copilot", "githubToken"],
+    &["cloudflareConfigs", "*", "tunnelToken"],
+    &["claudeApiKeys", "*", "apiKey"],
+    &["geminiApiKeys", "*", "apiKey"],
+    &["codexApiKeys", "*", "apiKey"],
+    &["vertexApiKeys", "*", "apiKey"],
+    &["ampOpenaiProviders", "*", "apiKey"],
+];
+
+pub fn get_secret_key_path() -> PathBuf {
+    get_proxypal_config_dir().join("secret.key")
+}
+
+/// Whether the value at a camelCase `path` is, or holds, a secret
+pub fn is_secret_path(path: &[String]) -> bool {
+    SECRET_PATHS.iter().any(|secret| {
+        path.len() <= secret.len() && path.iter().zip(secret.iter()).all(|(p, s)| *s == "*" || p == s)
+    })
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+fn read_key() -> Result<Option<ChaCha20Poly1305>, String> {
+    let path = get_secret_key_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if bytes.len() != 32 {
+        return Err(format!("{} is not a valid key file", path.display()));
+    }
+    Ok(Some(ChaCha20Poly1305::new(Key::from_slice(&bytes))))
+}
+
+/// The install's key, created (owner-only) on first use
+fn load_or_create_key() -> Result<ChaCha20Poly1305, String> {
+    if let Some(cipher) = read_key()? {
+        return Ok(cipher);
+    }
+    let path = get_secret_key_path();
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        file.write_all(key.as_slice())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    #[cfg(not(unix))]
+    {
+        // %APPDATA% is already private to the user on Windows
+        std::fs::write(&path, key.as_slice())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    eprintln!("[ProxyPal] Created secret key at {}", path.display());
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+fn seal(cipher: &ChaCha20Poly1305, plaintext: &str) -> Result<String, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    let mut data = nonce.to_vec();
+    data.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", PREFIX, base64::engine::general_purpose::STANDARD.encode(data)))
+}
+
+fn open(cipher: &ChaCha20Poly1305, sealed: &str) -> Result<String, String> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(&sealed[PREFIX.len()..])
+        .map_err(|_| "malformed encrypted value".to_string())?;
+    if data.len() < NONCE_LEN {
+        return Err("malformed encrypted value".to_string());
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "it was encrypted with a different secret.key".to_string())?;
+    String::from_utf8(plaintext).map_err(|_| "decrypted value is not text".to_string())
+}
+
+/// Call `f` on every string at a secret path, with the path rendered for messages
+fn for_each_secret(
+    value: &mut Value,
+    f: &mut dyn FnMut(&str, &mut String) -> Result<(), String>,
+) -> Result<(), String> {
+    fn walk(
+        value: &mut Value,
+        path: &[&str],
+        shown: String,
+        f: &mut dyn FnMut(&str, &mut String) -> Result<(), String>,
+    ) -> Result<(), String> {
+        match (path.split_first(), value) {
+            (None, Value::String(s)) => f(&shown, s),
+            (Some((&"*", rest)), Value::Array(items)) => items
+                .iter_mut()
+                .enumerate()
+                .try_for_each(|(i, item)| walk(item, rest, format!("{}[{}]", shown, i), f)),
+            (Some((key, rest)), Value::Object(map)) => match map.get_mut(*key) {
+                Some(child) => {
+                    let shown = if shown.is_empty() { key.to_string() } else { format!("{}.{}", shown, key) };
+                    walk(child, rest, shown, f)
+                }
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+    SECRET_PATHS
+        .iter()
+        .try_for_each(|path| walk(value, path, String::new(), f))
+}
+
+/// Encrypt every plaintext secret in a config document. Empty values stay empty.
+pub fn seal_secrets(value: &mut Value) -> Result<(), String> {
+    let mut cipher = None;
+    for_each_secret(value, &mut |_, s| {
+        if s.is_empty() || is_sealed(s) {
+            return Ok(());
+        }
+        if cipher.is_none() {
+            cipher = Some(load_or_create_key()?);
+        }
+        *s = seal(cipher.as_ref().unwrap(), s)?;
+        Ok(())
+    })
+}
+
+/// Decrypt every sealed secret in a config document. Plaintext values are left as they are.
+pub fn open_secrets(value: &mut Value) -> Result<(), String> {
+    let mut cipher = None;
+    for_each_secret(value, &mut |path, s| {
+        if !is_sealed(s) {
+            return Ok(());
+        }
+        if cipher.is_none() {
+            cipher = Some(read_key()?.ok_or_else(|| {
+                format!(
+                    "{} is encrypted but {} is missing",
+                    path,
+                    get_secret_key_path().display()
+                )
+            })?);
+        }
+        *s = open(cipher.as_ref().unwrap(), s)
+            .map_err(|e| format!("Cannot decrypt {}: {}", path, e))?;
+        Ok(())
+    })
+}
+
+/// Blank every secret, for exports that must not carry credentials
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn strip_secrets(value: &mut Value) {
+    let _ = for_each_secret(value, &mut |_, s| {
+        s.clear();
+        Ok(())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config_dir;
+    use serde_json::json;
+
+    fn document() -> Value {
+        json!({
+            "port": 8317,
+            "proxyPassword": "hunter2",
+            "managementKey": "",
+            "copilot": { "enabled": true, "githubToken": "gho_token" },
+            "claudeApiKeys": [
+                { "apiKey": "sk-ant-1", "baseUrl": "https://a.example" },
+                { "apiKey": "sk-ant-2" }
+            ],
+            "cloudflareConfigs": [{ "name": "tunnel", "tunnelToken": "cf-token" }]
+        })
+    }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let _dir = test_config_dir();
+        let mut value = document();
+        seal_secrets(&mut value).unwrap();
+
+        assert!(is_sealed(value["proxyPassword"].as_str().unwrap()));
+        assert!(is_sealed(value["copilot"]["githubToken"].as_str().unwrap()));
+        assert!(is_sealed(value["claudeApiKeys"][0]["apiKey"].as_str().unwrap()));
+        assert!(is_sealed(value["claudeApiKeys"][1]["apiKey"].as_str().unwrap()));
+        assert!(is_sealed(value["cloudflareConfigs"][0]["tunnelToken"].as_str().unwrap()));
+        // Empty secrets and everything else stay as they are
+        assert_eq!(value["managementKey"], "");
+        assert_eq!(value["claudeApiKeys"][0]["baseUrl"], "https://a.example");
+        assert_eq!(value["port"], 8317);
+
+        // Sealing again leaves sealed values alone
+        let sealed = value.clone();
+        seal_secrets(&mut value).unwrap();
+        assert_eq!(value, sealed);
+
+        open_secrets(&mut value).unwrap();
+        assert_eq!(value, document());
+    }
+
+    #[test]
+    fn same_secret_seals_differently_each_time() {
+        let _dir = test_config_dir();
+        let mut first = json!({ "ampApiKey": "same" });
+        let mut second = first.clone();
+        seal_secrets(&mut first).unwrap();
+        seal_secrets(&mut second).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn key_file_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let _dir = test_config_dir();
+        seal_secrets(&mut document()).unwrap();
+        let mode = std::fs::metadata(get_secret_key_path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn open_leaves_plaintext_alone_without_a_key() {
+        let _dir = test_config_dir();
+        let mut value = document();
+        open_secrets(&mut value).unwrap();
+        assert_eq!(value, document());
+        assert!(!get_secret_key_path().exists());
+    }
+
+    #[test]
+    fn strip_blanks_every_secret() {
+        let mut value = document();
+        strip_secrets(&mut value);
+        assert_eq!(value["proxyPassword"], "");
+        assert_eq!(value["copilot"]["githubToken"], "");
+        assert_eq!(value["claudeApiKeys"][0]["apiKey"], "");
+        assert_eq!(value["claudeApiKeys"][1]["apiKey"], "");
+        assert_eq!(value["cloudflareConfigs"][0]["tunnelToken"], "");
+        assert_eq!(value["copilot"]["enabled"], true);
+        assert_eq!(value["claudeApiKeys"][0]["baseUrl"], "https://a.example");
+    }
+
+    #[test]
+    fn missing_key_names_the_field() {
+        let _dir = test_config_dir();
+        let mut value = json!({ "claudeApiKeys": [{ "apiKey": "sk-ant" }] });
+        seal_secrets(&mut value).unwrap();
+        std::fs::remove_file(get_secret_key_path()).unwrap();
+
+        let error = open_secrets(&mut value).unwrap_err();
+        assert!(error.contains("claudeApiKeys[0].apiKey"), "{}", error);
+        assert!(error.contains("missing"), "{}", error);
+    }
+
+    #[test]
+    fn wrong_key_is_refused() {
+        let _dir = test_config_dir();
+        let mut value = json!({ "ampApiKey": "secret" });
+        seal_secrets(&mut value).unwrap();
+        std::fs::write(get_secret_key_path(), [7u8; 32]).unwrap();
+
+        let error = open_secrets(&mut value).unwrap_err();
+        assert!(error.contains("Cannot decrypt ampApiKey"), "{}", error);
+        assert!(error.contains("different secret.key"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_key_file_is_refused() {
+        let _dir = test_config_dir();
+        std::fs::write(get_secret_key_path(), b"too short").unwrap();
+        let mut value = json!({ "ampApiKey": "enc:v1:AAAA" });
+        let error = open_secrets(&mut value).unwrap_err();
+        assert!(error.contains("not a valid key file"), "{}", error);
+        // Sealing doesn't replace a broken key file either
+        assert!(seal_secrets(&mut json!({ "ampApiKey": "secret" })).is_err());
+    }
+
+    #[test]
+    fn malformed_values_are_refused() {
+        let _dir = test_config_dir();
+        seal_secrets(&mut json!({ "ampApiKey": "creates the key" })).unwrap();
+        let short = base64::engine::general_purpose::STANDARD.encode([1u8; 5]);
+        for sealed in [
+            "enc:v1:not base64!".to_string(),
+            format!("enc:v1:{}", short),
+            "enc:v1:".to_string(),
+        ] {
+            let mut value = json!({ "ampApiKey": sealed });
+            let error = open_secrets(&mut value).unwrap_err();
+            assert!(error.contains("malformed encrypted value"), "{}: {}", sealed, error);
+        }
+
+        // Well-formed but tampered with
+        let mut value = json!({ "ampApiKey": "secret" });
+        seal_secrets(&mut value).unwrap();
+        let sealed = value["ampApiKey"].as_str().unwrap();
+        let mut data = base64::engine::general_purpose::STANDARD
+            .decode(&sealed[PREFIX.len()..])
+            .unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        value["ampApiKey"] =
+            json!(format!("{}{}", PREFIX, base64::engine::general_purpose::STANDARD.encode(data)));
+        assert!(open_secrets(&mut value).is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! Rolling history of config.json in <config dir>/snapshots/<id>/.
+//!
+//! A snapshot is taken after every save_config_to_file that changes something. Each one
+//! holds the saved config.json, the proxy-config.yaml it generates and a snapshot.json
+//! with the metadata. Only the newest `AppConfig::snapshot_limit` are kept.
+//!
+//! Like config.json, snapshots hold the user's layer only: no baseline values and no
+//! per-launch overrides. They are put on top of the baseline current when restored.
+
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::{get_proxypal_config_dir, write_private, AppConfig};
+use crate::proxy::custom_config::{build_effective_config, render_effective_config};
+use crate::types::{ConfigFieldDiff, ConfigSnapshot};
+
+const CONFIG_FILE: &str = "config.json";
+const PROXY_CONFIG_FILE: &str = "proxy-config.yaml";
+const META_FILE: &str = "snapshot.json";
+
+pub fn get_snapshots_dir() -> PathBuf {
+    get_proxypal_config_dir().join("snapshots")
+}
+
+fn snapshot_dir(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(format!("Invalid snapshot id \"{}\"", id));
+    }
+    Ok(get_snapshots_dir().join(id))
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Snapshot ids sort chronologically: 20250101-120000-123
+fn new_snapshot_id() -> String {
+    let base = chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+    let mut id = base.clone();
+    let mut n = 1;
+    while get_snapshots_dir().join(&id).exists() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// Snapshots, newest first
+pub fn list_snapshots() -> Result<Vec<ConfigSnapshot>, String> {
+    let dir = get_snapshots_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read snapshots: {}", e))?;
+    let mut snapshots: Vec<ConfigSnapshot> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let data = std::fs::read_to_string(entry.path().join(META_FILE)).ok()?;
+            serde_json::from_str(&data).ok()
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+/// The config.json stored in a snapshot, as on disk
+fn read_snapshot_file(id: &str) -> Result<Value, String> {
+    let path = snapshot_dir(id)?.join(CONFIG_FILE);
+    let data = std::fs::read_to_string(&path)
+        .map_err(|_| format!("Snapshot \"{}\" not found", id))?;
+    serde_json::from_str(&data).map_err(|e| format!("Snapshot \"{}\" is unreadable: {}", id, e))
+}
+
+/// The config.json stored in a snapshot, as raw JSON
+pub fn load_snapshot_value(id: &str) -> Result<Value, String> {
+    let mut value = read_snapshot_file(id)?;
+    super::secrets::open_secrets(&mut value)?;
+    Ok(value)
+}
+
+/// The config stored in a snapshot, upgraded to the current config version and put on
+/// top of the baseline
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn load_snapshot_config(id: &str) -> Result<AppConfig, String> {
+    // Migrated before opening, as config.json is: the v3 step seals whatever is plaintext
+    let mut value = read_snapshot_file(id)?;
+    super::migrations::migrate_config(&mut value, |_| Ok(()))?;
+    super::secrets::open_secrets(&mut value)?;
+    let (config, warnings) = super::migrations::deserialize_config(super::baseline::with_baseline(value))?;
+    for warning in warnings {
+        eprintln!("[ProxyPal] Snapshot {}: {}", id, warning);
+    }
+    Ok(config)
+}
+
+/// Create a snapshot's directory, and snapshots/ itself, readable by the owner only on Unix
+fn create_snapshot_dir(dir: &std::path::Path) -> Result<(), String> {
+    let result = {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                // snapshots/ may predate owner-only snapshots
+                .and_then(|_| {
+                    std::fs::set_permissions(get_snapshots_dir(), std::fs::Permissions::from_mode(0o700))
+                })
+        }
+        #[cfg(not(unix))]
+        {
+            std::fs::create_dir_all(dir)
+        }
+    };
+    result.map_err(|e| format!("Failed to create snapshot directory: {}", e))
+}
+
+fn write_snapshot(value: &Value, reason: &str, previous: Option<&Value>) -> Result<ConfigSnapshot, String> {
+    let id = new_snapshot_id();
+    let dir = get_snapshots_dir().join(&id);
+    create_snapshot_dir(&dir)?;
+
+    let mut sealed = value.clone();
+    super::secrets::seal_secrets(&mut sealed)?;
+    let data = serde_json::to_string_pretty(&sealed).map_err(|e| e.to_string())?;
+    write_private(&dir.join(CONFIG_FILE), data.as_bytes())
+        .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+
+    // The sidecar config this version generates. With "use system proxy" the proxy URL is
+    // only resolved at start, so it is left out here.
+    let has_proxy_config = super::migrations::deserialize_config(super::baseline::with_baseline(value.clone()))
+        .and_then(|(config, _)| {
+            let effective = build_effective_config(&config, Some(config.proxy_url.clone()))?;
+            render_effective_config(&effective)
+        })
+        // The generated YAML carries plaintext keys for the sidecar
+        .and_then(|yaml| write_private(&dir.join(PROXY_CONFIG_FILE), yaml.as_bytes()))
+        .map_err(|e| eprintln!("[ProxyPal] Snapshot {}: no proxy-config.yaml: {}", id, e))
+        .is_ok();
+
+    let changed_fields = match previous {
+        Some(previous) => changed_top_level_fields(previous, value),
+        None => Vec::new(),
+    };
+    let snapshot = ConfigSnapshot {
+        id,
+        created_at: now_millis(),
+        changed_fields,
+        reason: reason.to_string(),
+        has_proxy_config,
+    };
+    let meta = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(META_FILE), meta).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    Ok(snapshot)
+}
+
+/// Delete the oldest snapshots beyond `limit`
+pub fn prune_snapshots(limit: usize) -> Result<(), String> {
+    for snapshot in list_snapshots()?.into_iter().skip(limit) {
+        let dir = snapshot_dir(&snapshot.id)?;
+        std::fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to delete snapshot {}: {}", snapshot.id, e))?;
+    }
+    Ok(())
+}
+
+/// Record `user_layer`, what config.json now holds unsealed, after `config` was saved.
+/// The first time, the file it replaced (`previous_raw`) is recorded too, so the very
+/// first edit can be undone.
+pub fn record_save(
+    config: &AppConfig,
+    user_layer: &Value,
+    previous_raw: Option<&str>,
+) -> Result<Option<ConfigSnapshot>, String> {
+    let limit = config.snapshot_limit as usize;
+    if limit == 0 {
+        return Ok(None);
+    }
+    let value = user_layer.clone();
+
+    let mut latest = match list_snapshots()?.first() {
+        Some(snapshot) => load_snapshot_value(&snapshot.id).ok(),
+        None => None,
+    };
+    if latest.is_none() {
+        let previous = previous_raw
+            .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+            .and_then(|mut previous| super::secrets::open_secrets(&mut previous).ok().map(|_| previous));
+        if let Some(previous) = previous {
+            write_snapshot(&previous, "before first snapshot", None)?;
+            latest = Some(previous);
+        }
+    }
+    if latest.as_ref() == Some(&value) {
+        return Ok(None);
+    }
+
+    let snapshot = write_snapshot(&value, "save", latest.as_ref())?;
+    prune_snapshots(limit)?;
+    Ok(Some(snapshot))
+}
+
+fn changed_top_level_fields(before: &Value, after: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let mut fields: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["apikey", "password", "secret", "token", "managementkey"]
+        .iter()
+        .any(|suffix| key.ends_with(suffix))
+}
+
+/// Keep the last 4 characters of long secrets so keys can still be told apart
+fn mask_secret(value: &Value) -> Value {
+    match value {
+        Value::String(s) if s.is_empty() => value.clone(),
+        Value::String(s) if s.chars().count() > 8 => {
+            let tail: String = s.chars().skip(s.chars().count() - 4).collect();
+            Value::String(format!("••••{}", tail))
+        }
+        Value::String(_) => Value::String("••••".to_string()),
+        other => other.clone(),
+    }
+}
+
+fn diff_into(
+    path: String,
+    key: Option<&str>,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    out: &mut Vec<ConfigFieldDiff>,
+) {
+    // Added or removed objects/lists are walked too, so secrets inside them get masked
+    let both = |check: fn(&Value) -> bool| {
+        (before.is_some_and(check) || after.is_some_and(check))
+            && before.is_none_or(check)
+            && after.is_none_or(check)
+    };
+
+    if both(Value::is_object) {
+        let empty = serde_json::Map::new();
+        let b = before.and_then(Value::as_object).unwrap_or(&empty);
+        let a = after.and_then(Value::as_object).unwrap_or(&empty);
+        let mut keys: Vec<&String> = b.keys().chain(a.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for k in keys {
+            let child = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+            diff_into(child, Some(k), b.get(k), a.get(k), out);
+        }
+    } else if both(Value::is_array) {
+        let b = before.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+        let a = after.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+        for i in 0..b.len().max(a.len()) {
+            diff_into(format!("{}[{}]", path, i), key, b.get(i), a.get(i), out);
+        }
+    } else if before != after {
+        let secret = key.is_some_and(is_secret_key);
+        let show = |v: Option<&Value>| v.map(|v| if secret { mask_secret(v) } else { v.clone() });
+        out.push(ConfigFieldDiff {
+            path,
+            before: show(before),
+            after: show(after),
+        });
+    }
+}
+
+/// Leaf-level differences between two config documents, with secrets masked
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn diff_values(before: &Value, after: &Value) -> Vec<ConfigFieldDiff> {
+    let mut out = Vec::new();
+    diff_into(String::new(), None, Some(before), Some(after), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(limit: u32) -> AppConfig {
+        AppConfig {
+            snapshot_limit: limit,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn saves_are_recorded_newest_first() {
+        let _dir = crate::config::test_config_dir();
+        let first = record_save(&config(10), &json!({ "port": 8400 }), None).unwrap().unwrap();
+        let second = record_save(&config(10), &json!({ "port": 8500, "debug": true }), None)
+            .unwrap()
+            .unwrap();
+
+        assert!(first.changed_fields.is_empty());
+        assert_eq!(second.changed_fields, vec!["debug", "port"]);
+        let ids: Vec<String> = list_snapshots().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![second.id, first.id]);
+    }
+
+    #[test]
+    fn unchanged_save_isnt_recorded() {
+        let _dir = crate::config::test_config_dir();
+        record_save(&config(10), &json!({ "port": 8400 }), None).unwrap();
+
+        assert!(record_save(&config(10), &json!({ "port": 8400 }), None).unwrap().is_none());
+        assert!(record_save(&config(0), &json!({ "port": 8500 }), None).unwrap().is_none());
+        assert_eq!(list_snapshots().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn first_save_records_the_file_it_replaced() {
+        let _dir = crate::config::test_config_dir();
+        let saved = record_save(&config(10), &json!({ "port": 8500 }), Some(r#"{ "port": 8400 }"#))
+            .unwrap()
+            .unwrap();
+
+        let snapshots = list_snapshots().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].reason, "before first snapshot");
+        assert_eq!(load_snapshot_value(&snapshots[1].id).unwrap(), json!({ "port": 8400 }));
+        assert_eq!(saved.changed_fields, vec!["port"]);
+    }
+
+    #[test]
+    fn only_the_newest_are_kept() {
+        let _dir = crate::config::test_config_dir();
+        for port in 8400..8405 {
+            record_save(&config(3), &json!({ "port": port }), None).unwrap();
+        }
+
+        let snapshots = list_snapshots().unwrap();
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(load_snapshot_value(&snapshots[0].id).unwrap()["port"], 8404);
+        assert_eq!(load_snapshot_value(&snapshots[2].id).unwrap()["port"], 8402);
+    }
+
+    #[test]
+    fn secrets_are_sealed_on_disk_and_restored() {
+        let _dir = crate::config::test_config_dir();
+        let user_layer = json!({ "port": 8400, "managementKey": "my-management-key" });
+        let snapshot = record_save(&config(10), &user_layer, None).unwrap().unwrap();
+
+        let on_disk = std::fs::read_to_string(snapshot_dir(&snapshot.id).unwrap().join(CONFIG_FILE)).unwrap();
+        assert!(!on_disk.contains("my-management-key"));
+        assert!(snapshot.has_proxy_config);
+        assert_eq!(load_snapshot_value(&snapshot.id).unwrap(), user_layer);
+
+        // Fields the snapshot doesn't hold come back as defaults
+        let restored = load_snapshot_config(&snapshot.id).unwrap();
+        assert_eq!(restored.port, 8400);
+        assert_eq!(restored.management_key, "my-management-key");
+        assert_eq!(restored.routing_strategy, AppConfig::default().routing_strategy);
+    }
+
+    #[test]
+    fn invalid_ids_are_refused() {
+        let _dir = crate::config::test_config_dir();
+        assert!(load_snapshot_value("../config").is_err());
+        assert!(load_snapshot_value("").is_err());
+        assert!(load_snapshot_value("20250101-120000-123").unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn diff_masks_secrets() {
+        let before = json!({
+            "port": 8317,
+            "claudeApiKeys": [{ "apiKey": "sk-ant-0000000001", "baseUrl": null }]
+        });
+        let after = json!({
+            "port": 8400,
+            "claudeApiKeys": [
+                { "apiKey": "sk-ant-0000000002", "baseUrl": null },
+                { "apiKey": "short" }
+            ]
+        });
+
+        let diff = diff_values(&before, &after);
+        let shown: Vec<(String, Option<Value>, Option<Value>)> =
+            diff.into_iter().map(|d| (d.path, d.before, d.after)).collect();
+
+        assert_eq!(
+            shown,
+            vec![
+                ("claudeApiKeys[0].apiKey".to_string(), Some(json!("••••0001")), Some(json!("••••0002"))),
+                ("claudeApiKeys[1].apiKey".to_string(), None, Some(json!("••••"))),
+                ("port".to_string(), Some(json!(8317)), Some(json!(8400))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Pre-flight validation of AppConfig, run before save_config and start_proxy accept it.
+
+use std::collections::HashMap;
+
+use super::AppConfig;
+use crate::proxy::payload_rules::PLACEHOLDERS as PAYLOAD_PLACEHOLDERS;
+use crate::types::{ConfigIssue, ConfigValidation};
+
+/// Routing strategies understood by CLIProxyAPI
+pub const ROUTING_STRATEGIES: &[&str] = &["round-robin", "fill-first"];
+
+/// Thinking budget modes (see ThinkingBudgetSettings::get_budget_tokens)
+pub const THINKING_BUDGET_MODES: &[&str] = &["low", "medium", "high", "custom"];
+
+/// Amp routing modes offered in the Settings UI
+pub const AMP_ROUTING_MODES: &[&str] = &["mappings", "openai"];
+
+const PROXY_URL_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+#[derive(Default)]
+struct Collector {
+    errors: Vec<ConfigIssue>,
+    warnings: Vec<ConfigIssue>,
+}
+
+impl Collector {
+    fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(ConfigIssue {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn warning(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.warnings.push(ConfigIssue {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn check_enum(&mut self, field: &str, value: &str, allowed: &[&str]) {
+        // Empty strings fall back to the default at generation time
+        if !value.is_empty() && !allowed.contains(&value) {
+            self.error(
+                field,
+                format!(
+                    "Unknown value \"{}\". Expected one of: {}",
+                    value,
+                    allowed.join(", ")
+                ),
+            );
+        }
+    }
+
+    fn check_base_url(&mut self, field: String, value: Option<&str>) {
+        let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+            return;
+        };
+        match url::Url::parse(value) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                if url.host_str().is_none() {
+                    self.error(field, format!("\"{}\" has no host", value));
+                }
+            }
+            Ok(url) => self.error(
+                field,
+                format!("Unsupported scheme \"{}\", use http or https", url.scheme()),
+            ),
+            Err(e) => self.error(field, format!("\"{}\" is not a valid URL: {}", value, e)),
+        }
+    }
+
+    fn check_proxy_url(&mut self, field: String, value: Option<&str>) {
+        let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+            return;
+        };
+        match url::Url::parse(value) {
+            Ok(url) if PROXY_URL_SCHEMES.contains(&url.scheme()) => {
+                if url.host_str().is_none() {
+                    self.error(field, format!("\"{}\" has no host", value));
+                }
+            }
+            Ok(url) => self.error(
+                field,
+                format!(
+                    "Unsupported proxy scheme \"{}\". Expected one of: {}",
+                    url.scheme(),
+                    PROXY_URL_SCHEMES.join(", ")
+                ),
+            ),
+            Err(e) => self.error(
+                field,
+                format!("\"{}\" is not a valid proxy URL: {}", value, e),
+            ),
+        }
+    }
+}
+
+fn check_ports(c: &mut Collector, config: &AppConfig) {
+    if config.port == 0 {
+        c.error("port", "Port must be between 1 and 65535");
+    }
+    if config.port == config.copilot.port {
+        let message = format!(
+            "Port {} is also used by the Copilot bridge (copilot.port)",
+            config.port
+        );
+        if config.copilot.enabled {
+            c.error("copilot.port", message);
+        } else {
+            c.warning("copilot.port", format!("{} - enabling Copilot will fail", message));
+        }
+    }
+
+    // SSH `local_port` is the forwarding target of `ssh -R`, so it normally equals `port`.
+    // It's a problem when it points at something ProxyPal doesn't listen on, or when two
+    // enabled tunnels try to bind the same remote port on the same host.
+    let mut remote_binds: HashMap<(String, u16), usize> = HashMap::new();
+    for (i, ssh) in config.ssh_configs.iter().enumerate() {
+        let served = ssh.local_port == config.port
+            || (config.copilot.enabled && ssh.local_port == config.copilot.port);
+        if !served {
+            c.warning(
+                format!("sshConfigs[{}].localPort", i),
+                format!(
+                    "Tunnel forwards to local port {}, but the proxy listens on {}",
+                    ssh.local_port, config.port
+                ),
+            );
+        }
+        if ssh.enabled {
+            let key = (ssh.host.to_lowercase(), ssh.remote_port);
+            if let Some(first) = remote_binds.insert(key, i) {
+                c.error(
+                    format!("sshConfigs[{}].remotePort", i),
+                    format!(
+                        "Remote port {} on {} is already used by sshConfigs[{}]",
+                        ssh.remote_port, ssh.host, first
+                    ),
+                );
+            }
+        }
+    }
+
+    for (i, cf) in config.cloudflare_configs.iter().enumerate() {
+        if cf.local_port != config.port
+            && !(config.copilot.enabled && cf.local_port == config.copilot.port)
+        {
+            c.warning(
+                format!("cloudflareConfigs[{}].localPort", i),
+                format!(
+                    "Tunnel exposes local port {}, but the proxy listens on {}",
+                    cf.local_port, config.port
+                ),
+            );
+        }
+    }
+}
+
+fn check_urls(c: &mut Collector, config: &AppConfig) {
+    if !config.use_system_proxy {
+        c.check_proxy_url("proxyUrl".to_string(), Some(&config.proxy_url));
+    }
+
+    for (i, p) in config.amp_openai_providers.iter().enumerate() {
+        if p.base_url.trim().is_empty() {
+            c.warning(
+                format!("ampOpenaiProviders[{}].baseUrl", i),
+                "Provider has no base URL and will be skipped",
+            );
+        }
+        c.check_base_url(format!("ampOpenaiProviders[{}].baseUrl", i), Some(&p.base_url));
+    }
+    for (i, k) in config.claude_api_keys.iter().enumerate() {
+        c.check_base_url(format!("claudeApiKeys[{}].baseUrl", i), k.base_url.as_deref());
+        c.check_proxy_url(format!("claudeApiKeys[{}].proxyUrl", i), k.proxy_url.as_deref());
+    }
+    for (i, k) in config.gemini_api_keys.iter().enumerate() {
+        c.check_base_url(format!("geminiApiKeys[{}].baseUrl", i), k.base_url.as_deref());
+        c.check_proxy_url(format!("geminiApiKeys[{}].proxyUrl", i), k.proxy_url.as_deref());
+    }
+    for (i, k) in config.codex_api_keys.iter().enumerate() {
+        c.check_base_url(format!("codexApiKeys[{}].baseUrl", i), k.base_url.as_deref());
+        c.check_proxy_url(format!("codexApiKeys[{}].proxyUrl", i), k.proxy_url.as_deref());
+    }
+    for (i, k) in config.vertex_api_keys.iter().enumerate() {
+        c.check_base_url(format!("vertexApiKeys[{}].baseUrl", i), k.base_url.as_deref());
+    }
+}
+
+fn check_api_keys(c: &mut Collector, config: &AppConfig) {
+    let keys = config
+        .claude_api_keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (format!("claudeApiKeys[{}].apiKey", i), k.api_key.as_str()))
+        .chain(
+            config
+                .gemini_api_keys
+                .iter()
+                .enumerate()
+                .map(|(i, k)| (format!("geminiApiKeys[{}].apiKey", i), k.api_key.as_str())),
+        )
+        .chain(
+            config
+                .codex_api_keys
+                .iter()
+                .enumerate()
+                .map(|(i, k)| (format!("codexApiKeys[{}].apiKey", i), k.api_key.as_str())),
+        );
+
+    let mut seen: HashMap<&str, String> = HashMap::new();
+    for (field, key) in keys {
+        let key = key.trim();
+        if key.is_empty() {
+            c.error(field, "API key is empty");
+            continue;
+        }
+        match seen.get(key) {
+            Some(first) => c.error(field, format!("Duplicate API key, already used by {}", first)),
+            None => {
+                seen.insert(key, field);
+            }
+        }
+    }
+}
+
+fn check_amp(c: &mut Collector, config: &AppConfig) {
+    for (i, m) in config.amp_model_mappings.iter().enumerate() {
+        if m.name.trim().is_empty() {
+            c.error(format!("ampModelMappings[{}].name", i), "Mapping has no source model");
+        }
+        if m.alias.trim().is_empty() {
+            let field = format!("ampModelMappings[{}].alias", i);
+            let message = format!("Mapping for \"{}\" has no target model", m.name);
+            if m.enabled {
+                c.error(field, message);
+            } else {
+                c.warning(field, message);
+            }
+        }
+    }
+}
+
+fn check_payload_rules(c: &mut Collector, config: &AppConfig) {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    for (i, rule) in config.payload_rules.iter().enumerate() {
+        if let Some(first) = ids.insert(rule.id.as_str(), i) {
+            c.error(
+                format!("payloadRules[{}].id", i),
+                format!("Duplicate rule id \"{}\", already used by payloadRules[{}]", rule.id, first),
+            );
+        }
+        if rule.models.iter().all(|m| m.trim().is_empty()) {
+            c.error(format!("payloadRules[{}].models", i), "Rule matches no models");
+        }
+        if rule.params.is_empty() {
+            c.warning(format!("payloadRules[{}].params", i), "Rule sets no parameters");
+        }
+        for (path, value) in &rule.params {
+            let field = format!("payloadRules[{}].params.{}", i, path);
+            if path.trim().is_empty() || path.split('.').any(str::is_empty) {
+                c.error(field.clone(), format!("\"{}\" is not a valid JSON path", path));
+            }
+            // Placeholders must be the whole value, so they can be typed (budget is a number)
+            if let Some(s) = value.as_str().filter(|s| s.contains("{{")) {
+                if !PAYLOAD_PLACEHOLDERS.contains(&s) {
+                    c.error(
+                        field,
+                        format!(
+                            "Unknown placeholder \"{}\". Expected one of: {}",
+                            s,
+                            PAYLOAD_PLACEHOLDERS.join(", ")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Validate a config and return all errors and warnings with their field paths
+pub fn validate_config(config: &AppConfig) -> ConfigValidation {
+    let mut c = Collector::default();
+
+    check_ports(&mut c, config);
+    check_urls(&mut c, config);
+    check_api_keys(&mut c, config);
+    check_amp(&mut c, config);
+    check_payload_rules(&mut c, config);
+
+    c.check_enum("routingStrategy", &config.routing_strategy, ROUTING_STRATEGIES);
+    c.check_enum(
+        "thinkingBudgetMode",
+        &config.thinking_budget_mode,
+        THINKING_BUDGET_MODES,
+    );
+    c.check_enum("ampRoutingMode", &config.amp_routing_mode, AMP_ROUTING_MODES);
+    if config.auto_restart_proxy {
+        if config.crash_loop_max_exits == 0 {
+            c.error("crashLoopMaxExits", "Must be at least 1");
+        }
+        if config.crash_loop_window_minutes == 0 {
+            c.error("crashLoopWindowMinutes", "Must be at least 1 minute");
+        }
+    }
+    if config.proxy_ready_timeout_secs == 0 {
+        c.error("proxyReadyTimeoutSecs", "Must be at least 1 second");
+    }
+    if config.thinking_budget_mode == "custom" && config.thinking_budget_custom == 0 {
+        c.warning(
+            "thinkingBudgetCustom",
+            "Custom thinking budget is 0, 16000 tokens will be used",
+        );
+    }
+
+    ConfigValidation {
+        valid: c.errors.is_empty(),
+        errors: c.errors,
+        warnings: c.warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn config(fields: Value) -> AppConfig {
+        let mut config = serde_json::to_value(AppConfig::default()).unwrap();
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(config).unwrap()
+    }
+
+    fn fields(issues: &[ConfigIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.field.as_str()).collect()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        let result = validate_config(&AppConfig::default());
+        assert!(result.valid, "{:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn copilot_port_clash_blocks_only_when_copilot_is_enabled() {
+        let enabled = validate_config(&config(json!({ "port": 4141, "copilot": { "enabled": true, "port": 4141 } })));
+        assert!(!enabled.valid);
+        assert_eq!(fields(&enabled.errors), vec!["copilot.port"]);
+
+        let disabled = validate_config(&config(json!({ "port": 4141, "copilot": { "enabled": false, "port": 4141 } })));
+        assert!(disabled.valid);
+        assert_eq!(fields(&disabled.warnings), vec!["copilot.port"]);
+        assert!(disabled.warnings[0].message.contains("enabling Copilot will fail"));
+    }
+
+    #[test]
+    fn unparsable_urls_are_errors() {
+        let result = validate_config(&config(json!({
+            "proxyUrl": "not a url",
+            "claudeApiKeys": [{ "apiKey": "sk-1", "baseUrl": "http://", "proxyUrl": "ftp://proxy:21" }],
+            "ampOpenaiProviders": [
+                { "id": "p", "name": "p", "baseUrl": "localhost:8080", "apiKey": "k" }
+            ],
+        })));
+        assert_eq!(
+            fields(&result.errors),
+            vec!["proxyUrl", "ampOpenaiProviders[0].baseUrl", "claudeApiKeys[0].baseUrl", "claudeApiKeys[0].proxyUrl"]
+        );
+        assert!(result.errors[3].message.contains("Unsupported proxy scheme"));
+
+        // proxyUrl isn't used with the system proxy, so it isn't checked either
+        let system = validate_config(&config(json!({ "proxyUrl": "not a url", "useSystemProxy": true })));
+        assert!(system.valid);
+        let socks = validate_config(&config(json!({ "proxyUrl": "socks5://127.0.0.1:1080" })));
+        assert!(socks.valid);
+    }
+
+    #[test]
+    fn duplicate_keys_are_found_across_providers() {
+        let result = validate_config(&config(json!({
+            "claudeApiKeys": [{ "apiKey": "sk-shared" }],
+            "geminiApiKeys": [{ "apiKey": "AIza-1" }, { "apiKey": " sk-shared " }],
+            "codexApiKeys": [{ "apiKey": "AIza-1" }, { "apiKey": "" }],
+        })));
+        assert_eq!(
+            fields(&result.errors),
+            vec!["geminiApiKeys[1].apiKey", "codexApiKeys[0].apiKey", "codexApiKeys[1].apiKey"]
+        );
+        assert!(result.errors[0].message.contains("claudeApiKeys[0].apiKey"));
+        assert!(result.errors[1].message.contains("geminiApiKeys[0].apiKey"));
+        assert_eq!(result.errors[2].message, "API key is empty");
+    }
+
+    #[test]
+    fn unknown_enum_values_are_errors() {
+        let result = validate_config(&config(json!({
+            "routingStrategy": "random",
+            "thinkingBudgetMode": "huge",
+            "ampRoutingMode": "direct",
+        })));
+        assert_eq!(fields(&result.errors), vec!["routingStrategy", "thinkingBudgetMode", "ampRoutingMode"]);
+        assert!(result.errors[0].message.contains("round-robin, fill-first"));
+
+        // Empty means the default
+        assert!(validate_config(&config(json!({ "routingStrategy": "" }))).valid);
+    }
+
+    #[test]
+    fn empty_amp_alias_blocks_only_enabled_mappings() {
+        let result = validate_config(&config(json!({
+            "ampModelMappings": [
+                { "name": "claude-opus", "alias": "", "enabled": true },
+                { "name": "gpt-5", "alias": " ", "enabled": false },
+                { "name": "", "alias": "gemini-pro" },
+            ],
+        })));
+        assert_eq!(fields(&result.errors), vec!["ampModelMappings[0].alias", "ampModelMappings[2].name"]);
+        assert_eq!(fields(&result.warnings), vec!["ampModelMappings[1].alias"]);
+        assert!(result.errors[0].message.contains("claude-opus"));
+    }
+}
@@ -0,0 +1,236 @@
+//! ProxyPal's own loopback HTTP API, used by the `proxypal` CLI, editor extensions
+//! and dashboards. The app writes its port and a per-launch bearer token to
+//! control.json (owner-only); openapi_document describes the routes.
+
+use rand::RngCore;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+
+use crate::config::{get_proxypal_config_dir, AppConfig};
+use crate::types::{
+    AgentStatus, AuthFile, AvailableModel, ConfigApplyResult, LogChunk, LogEntry, ProcessOutput, ProxyStatus, QuotaReport,
+    RequestHistory, TunnelList, UsageSummary,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlEndpoint {
+    pub port: u16,
+    pub token: String,
+    /// Process serving the API, so a stale file can be told apart
+    pub pid: u32,
+}
+
+impl ControlEndpoint {
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", self.port, path)
+    }
+}
+
+pub fn get_control_file_path() -> PathBuf {
+    get_proxypal_config_dir().join("control.json")
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether an Authorization header carries `token`, compared in constant time so
+/// the response time doesn't tell how much of a guess was right
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn is_authorized(header: &str, token: &str) -> bool {
+    let Some(presented) = header.strip_prefix("Bearer ") else {
+        return false;
+    };
+    let (presented, token) = (presented.as_bytes(), token.as_bytes());
+    presented.len() == token.len()
+        && presented.iter().zip(token).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn write_control_file(endpoint: &ControlEndpoint) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(endpoint).map_err(|e| e.to_string())?;
+    crate::config::write_private(&get_control_file_path(), data.as_bytes())
+}
+
+pub fn read_control_file() -> Result<ControlEndpoint, String> {
+    let path = get_control_file_path();
+    let data = std::fs::read_to_string(&path)
+        .map_err(|_| "ProxyPal is not running (no control.json found)".to_string())?;
+    serde_json::from_str(&data).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Remove control.json if it still describes this process
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn remove_control_file() {
+    if read_control_file().is_ok_and(|endpoint| endpoint.pid == std::process::id()) {
+        let _ = std::fs::remove_file(get_control_file_path());
+    }
+}
+
+fn schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    serde_json::to_value(gen.subschema_for::<T>()).expect("schema serializes")
+}
+
+fn query_parameter(name: &str, description: &str, schema: Value) -> Value {
+    json!({ "name": name, "in": "query", "required": false, "description": description, "schema": schema })
+}
+
+fn path_parameter(name: &str, description: &str) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "description": description, "schema": { "type": "string" } })
+}
+
+fn operation(summary: &str, parameters: Vec<Value>, response: Value) -> Value {
+    json!({
+        "summary": summary,
+        "parameters": parameters,
+        "responses": {
+            "200": { "description": "OK", "content": { "application/json": { "schema": response } } },
+            "default": {
+                "description": "Error",
+                "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+            }
+        }
+    })
+}
+
+/// OpenAPI 3.0 description of the control API, served at GET /v1/openapi.json
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn openapi_document() -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let since = query_parameter(
+        "since",
+        "Only the last 30m, 12h, 7d, 2w, ...",
+        json!({ "type": "string", "example": "7d" }),
+    );
+    let tunnel_kind = json!({
+        "name": "kind", "in": "path", "required": true,
+        "schema": { "type": "string", "enum": ["ssh", "cloudflare"] }
+    });
+    let status = schema::<ProxyStatus>(&mut gen);
+    let tunnels = schema::<TunnelList>(&mut gen);
+
+    let mut patch_config = operation(
+        "Merge a partial config into config.json and apply it to the running proxy",
+        vec![],
+        schema::<ConfigApplyResult>(&mut gen),
+    );
+    patch_config["requestBody"] = json!({
+        "required": true,
+        "description": "Fields to change; objects merge key by key, anything else is replaced",
+        "content": { "application/json": { "schema": { "type": "object" } } }
+    });
+
+    let paths = json!({
+        "/v1/status": { "get": operation("Proxy status", vec![], status.clone()) },
+        "/v1/proxy/start": { "post": operation("Start the proxy", vec![], status.clone()) },
+        "/v1/proxy/stop": { "post": operation("Stop the proxy", vec![], status) },
+        "/v1/config": {
+            "get": operation("The current config.json", vec![], schema::<AppConfig>(&mut gen)),
+            "patch": patch_config
+        },
+        "/v1/usage": {
+            "get": operation("Requests and tokens", vec![since.clone()], schema::<UsageSummary>(&mut gen))
+        },
+        "/v1/history": {
+            "get": operation(
+                "Recorded requests, newest last",
+                vec![
+                    since,
+                    query_parameter("limit", "Only the newest N requests", json!({ "type": "integer", "minimum": 1 })),
+                ],
+                schema::<RequestHistory>(&mut gen),
+            )
+        },
+        "/v1/quota": { "get": operation("Quota of every connected account", vec![], schema::<QuotaReport>(&mut gen)) },
+        "/v1/auth-files": { "get": operation("Auth files the proxy uses", vec![], schema::<Vec<AuthFile>>(&mut gen)) },
+        "/v1/models": { "get": operation("Models the proxy serves", vec![], schema::<Vec<AvailableModel>>(&mut gen)) },
+        "/v1/agents": { "get": operation("Detected CLI agents", vec![], schema::<Vec<AgentStatus>>(&mut gen)) },
+        "/v1/agents/{agentId}/configure": {
+            "post": operation(
+                "Point a CLI agent at the proxy",
+                vec![path_parameter("agentId", "e.g. claude-code, codex, opencode")],
+                json!({ "type": "object" }),
+            )
+        },
+        "/v1/logs": {
+            "get": operation(
+                "Proxy log lines",
+                vec![query_parameter("lines", "How many lines (default 500)", json!({ "type": "integer", "minimum": 1 }))],
+                schema::<Vec<LogEntry>>(&mut gen),
+            )
+        },
+        "/v1/logs/tail": {
+            "get": operation(
+                "Lines of the proxy's log file, or those written after a cursor from an earlier call",
+                vec![
+                    query_parameter("after", "The cursor of an earlier call", json!({ "type": "integer", "minimum": 0 })),
+                    query_parameter("lines", "At most this many lines (default 1000)", json!({ "type": "integer", "minimum": 1 })),
+                ],
+                schema::<LogChunk>(&mut gen),
+            )
+        },
+        "/v1/processes/{process}/output": {
+            "get": operation(
+                "What a supervised process printed lately",
+                vec![
+                    json!({
+                        "name": "process", "in": "path", "required": true,
+                        "schema": { "type": "string", "enum": crate::process_output::PROCESSES }
+                    }),
+                    query_parameter("lines", "How many lines (default 200)", json!({ "type": "integer", "minimum": 1 })),
+                ],
+                schema::<ProcessOutput>(&mut gen),
+            )
+        },
+        "/v1/tunnels": { "get": operation("SSH and Cloudflare tunnels", vec![], tunnels.clone()) },
+        "/v1/tunnels/{kind}/{id}/enable": {
+            "post": operation("Connect a tunnel and keep it up", vec![tunnel_kind.clone(), path_parameter("id", "Tunnel id")], tunnels.clone())
+        },
+        "/v1/tunnels/{kind}/{id}/disable": {
+            "post": operation("Disconnect a tunnel", vec![tunnel_kind, path_parameter("id", "Tunnel id")], tunnels)
+        },
+        "/v1/openapi.json": {
+            "get": {
+                "summary": "This document",
+                "security": [],
+                "responses": { "200": { "description": "OpenAPI document" } }
+            }
+        }
+    });
+
+    let mut schemas: Map<String, Value> = gen
+        .take_definitions()
+        .into_iter()
+        .map(|(name, schema)| (name, serde_json::to_value(schema).expect("schema serializes")))
+        .collect();
+    schemas.insert(
+        "Error".to_string(),
+        json!({ "type": "object", "required": ["error"], "properties": { "error": { "type": "string" } } }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "ProxyPal control API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Loopback API of the running ProxyPal app. The port and bearer token are in control.json next to config.json."
+        },
+        "servers": [{
+            "url": "http://127.0.0.1:{port}",
+            "variables": { "port": { "default": "0", "description": "port from control.json" } }
+        }],
+        "security": [{ "bearer": [] }],
+        "components": {
+            "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } },
+            "schemas": schemas
+        },
+        "paths": paths
+    })
+}
@@ -0,0 +1,360 @@
+//! Headless mode for servers without a display: `proxypal-daemon` runs the sidecar
+//! from config.json, restarts it when it exits (and exits itself on a crash loop),
+//! records usage from its request log and keeps the enabled SSH and Cloudflare
+//! tunnels up, all without Tauri.
+//!
+//! Events the app would send to the webview are written to stderr instead.
+//! SIGTERM or Ctrl-C stops everything and exits.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::watch;
+
+use crate::cloudflare_manager::CloudflareManager;
+use crate::config::{get_proxypal_config_dir, load_config, AppConfig};
+use crate::events::{Events, LogEvents};
+use crate::process_output;
+use crate::proxy::custom_config::write_effective_config;
+use crate::proxy::pidfile::{forget_sidecar, record_sidecar, stop_stale_sidecar_async};
+use crate::proxy::ports::{ensure_port_free, next_free_port, port_in_use_error, PortOwner};
+use crate::proxy::readiness::{apply_management_settings, wait_for_port_free, wait_until_ready};
+use crate::proxy::sidecars::{describe, rollback, selected_sidecar};
+use crate::proxy::supervisor::{RestartDecision, RestartPolicy};
+use crate::proxy::version::SidecarVersion;
+use crate::ssh_manager::SshManager;
+use crate::types::OutputStream;
+
+/// Path of the CLIProxyAPI binary, when it isn't next to proxypal-daemon
+pub const SIDECAR_PATH_ENV: &str = "PROXYPAL_SIDECAR";
+
+const SIDECAR_NAME: &str = "cli-proxy-api";
+
+const USAGE: &str = "Usage: proxypal-daemon [--sidecar PATH] [--set key=value ...]
+
+Runs the ProxyPal proxy without the desktop app, using the same config.json.
+
+Options:
+  --sidecar PATH     CLIProxyAPI binary (default: the version selected in the
+                     app, then cli-proxy-api next to this binary, then on
+                     PATH; or set PROXYPAL_SIDECAR)
+  --set key=value    Override a setting for this run (see PROXYPAL_* variables)
+  -h, --help         Show this help";
+
+/// Entry point of the proxypal-daemon binary, returns the exit code
+pub fn main() -> i32 {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return 0;
+    }
+
+    let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("[ProxyPal] Failed to start the async runtime: {}", e);
+            return 1;
+        }
+    };
+    match runtime.block_on(run(&args)) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("[ProxyPal] {}", e);
+            1
+        }
+    }
+}
+
+async fn run(args: &[String]) -> Result<(), String> {
+    crate::usage::migrate_to_split_storage();
+
+    let mut config = load_config();
+    if let Some(error) = crate::config::get_config_load_status().error {
+        return Err(format!("Failed to load config.json: {}", error));
+    }
+    let validation = crate::config::validation::validate_config(&config);
+    if !validation.valid {
+        return Err(validation.error_summary());
+    }
+    let sidecar = resolve_sidecar(args)?;
+    process_output::set_persist(config.persist_process_output);
+
+    stop_stale_sidecar_async().await;
+    if let Some(change) = ensure_port_free(&mut config, PortOwner::Proxy).await? {
+        crate::config::baseline::check_locked(&config)?;
+        crate::config::save_config_to_file(&config)?;
+        println!(
+            "[ProxyPal] Moved the proxy from port {} to {}; point your clients at http://localhost:{}/v1",
+            change.from, change.to, change.to
+        );
+        // The daemon doesn't run tunnels, whoever does has to reconnect them
+        let tunnels: Vec<&String> = change.ssh_tunnels.iter().chain(&change.cloudflare_tunnels).collect();
+        if !tunnels.is_empty() {
+            println!(
+                "[ProxyPal] Tunnels now forwarding port {}, reconnect them: {}",
+                change.to,
+                tunnels.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        eprintln!("[ProxyPal] Shutting down...");
+        let _ = shutdown_tx.send(true);
+    });
+
+    let events: Events = Arc::new(LogEvents);
+    let ssh_manager = SshManager::new();
+    let cf_manager = CloudflareManager::new();
+    for ssh_config in config.ssh_configs.iter().filter(|c| c.enabled) {
+        ssh_manager.connect(events.clone(), ssh_config.clone());
+    }
+    for cf_config in config.cloudflare_configs.iter().filter(|c| c.enabled) {
+        println!("[Cloudflare] Auto-starting tunnel: {}", cf_config.name);
+        cf_manager.connect(events.clone(), cf_config.clone());
+    }
+
+    let log_watcher_running = Arc::new(AtomicBool::new(true));
+    crate::usage::start_log_watcher(
+        events.clone(),
+        get_proxypal_config_dir().join("logs").join("main.log"),
+        log_watcher_running.clone(),
+        Arc::new(AtomicU64::new(0)),
+    );
+
+    let result = supervise_sidecar(&config, sidecar, shutdown_rx).await;
+
+    log_watcher_running.store(false, Ordering::SeqCst);
+    ssh_manager.disconnect_all();
+    cf_manager.disconnect_all();
+    result
+}
+
+/// CLIProxyAPI binary the daemon runs
+struct Sidecar {
+    path: PathBuf,
+    /// Version installed and selected in the app, switched back from when it doesn't
+    /// become ready
+    installed: Option<String>,
+}
+
+/// --sidecar, then PROXYPAL_SIDECAR, then the version selected in the app, then
+/// cli-proxy-api next to this binary, then PATH
+fn resolve_sidecar(args: &[String]) -> Result<Sidecar, String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let path = if let Some(path) = arg.strip_prefix("--sidecar=") {
+            PathBuf::from(path)
+        } else if arg == "--sidecar" {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| "--sidecar needs a path".to_string())?
+        } else {
+            continue;
+        };
+        return Ok(Sidecar { path, installed: None });
+    }
+    if let Ok(path) = std::env::var(SIDECAR_PATH_ENV) {
+        return Ok(Sidecar { path: PathBuf::from(path), installed: None });
+    }
+    Ok(selected_or_bundled())
+}
+
+fn selected_or_bundled() -> Sidecar {
+    if let Some(selected) = selected_sidecar() {
+        return Sidecar {
+            path: selected.path,
+            installed: Some(selected.version),
+        };
+    }
+    let file_name = format!("{}{}", SIDECAR_NAME, std::env::consts::EXE_SUFFIX);
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&file_name)))
+        .filter(|path| path.exists());
+    Sidecar {
+        path: bundled.unwrap_or_else(|| PathBuf::from(file_name)),
+        installed: None,
+    }
+}
+
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = sigterm.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(e) => {
+                eprintln!("[ProxyPal] Can't listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Write proxy-config.yaml the way the app does on every start
+async fn write_proxy_config(config: &AppConfig) -> Result<PathBuf, String> {
+    if config.copilot.enabled {
+        if let Err(e) = crate::proxy::copilot_models::refresh_models(config.copilot.port).await {
+            eprintln!("[ProxyPal] Using cached copilot models: {}", e);
+        }
+    }
+    write_effective_config(config)
+}
+
+/// Run the sidecar until shutdown, restarting it with a growing delay when it exits.
+/// Fails when it exits and auto-restart is off, or on a crash loop, so a service
+/// manager sees the daemon fail.
+async fn supervise_sidecar(
+    config: &AppConfig,
+    mut sidecar: Sidecar,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), String> {
+    let mut policy = RestartPolicy::from_config(config);
+    loop {
+        stop_stale_sidecar_async().await;
+        if !wait_for_port_free(config.port, Duration::from_secs(3)).await {
+            return Err(port_in_use_error(
+                PortOwner::Proxy,
+                config.port,
+                next_free_port(config, config.port),
+            ));
+        }
+        let proxy_config_path = write_proxy_config(config).await?;
+        let mut child = Command::new(&sidecar.path)
+            .env("WRITABLE_PATH", get_proxypal_config_dir())
+            .arg("--config")
+            .arg(&proxy_config_path)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", sidecar.path.display(), e))?;
+        process_output::start_run(process_output::PROXY, None);
+        let started = Instant::now();
+        let started_at = chrono::Utc::now().timestamp_millis() as u64;
+        let pid = child.id().unwrap_or_default();
+        if let Err(e) = record_sidecar(pid) {
+            eprintln!("[ProxyPal] {}", e);
+        }
+        println!(
+            "[ProxyPal] Proxy started (pid {}), listening on http://localhost:{}/v1",
+            pid, config.port
+        );
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_output(stdout, "[CLIProxyAPI]", OutputStream::Stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_output(stderr, "[CLIProxyAPI ERROR]", OutputStream::Stderr));
+        }
+
+        let timeout = Duration::from_secs(config.proxy_ready_timeout_secs.into());
+        let ready = tokio::select! {
+            ready = wait_until_ready(config.port, &config.management_key, timeout, || {
+                match child.try_wait() {
+                    Ok(Some(status)) => Some(format!("exited ({})", status)),
+                    _ => None,
+                }
+            }) => ready,
+            _ = shutdown.changed() => {
+                let _ = child.kill().await;
+                forget_sidecar(pid);
+                process_output::save_last_run(process_output::PROXY, None);
+                return Ok(());
+            }
+        };
+        match ready {
+            Ok(reported) => {
+                let version = reported.as_deref().and_then(SidecarVersion::parse).or_else(|| {
+                    let output = process_output::texts_since(process_output::PROXY, started_at);
+                    SidecarVersion::from_output(output.iter().map(String::as_str))
+                });
+                match version {
+                    Some(version) => println!("[ProxyPal] Proxy ready (CLIProxyAPI {})", version),
+                    None => println!("[ProxyPal] Proxy ready"),
+                }
+                // Settings it may not have picked up from the file, as the app pushes them
+                for failure in apply_management_settings(config).await {
+                    eprintln!("[ProxyPal] Failed to apply {}: {}", failure.setting, failure.error);
+                }
+            }
+            Err(e) => {
+                // Handled like any other exit below
+                eprintln!("[ProxyPal] {}", e);
+                let _ = child.kill().await;
+                // A newly selected version that doesn't come up gives way to the one before
+                if let Some(rollback) = sidecar.installed.as_deref().and_then(|v| rollback(v, &e)) {
+                    eprintln!(
+                        "[ProxyPal] CLIProxyAPI {} didn't become ready, switching back to {}",
+                        rollback.from,
+                        describe(rollback.to.as_deref())
+                    );
+                    sidecar = selected_or_bundled();
+                }
+            }
+        }
+
+        let exit = tokio::select! {
+            status = child.wait() => {
+                match status {
+                    Ok(status) => status.to_string(),
+                    Err(e) => format!("lost track of it: {}", e),
+                }
+            }
+            _ = shutdown.changed() => {
+                println!("[ProxyPal] Stopping proxy...");
+                let _ = child.kill().await;
+                forget_sidecar(pid);
+                process_output::save_last_run(process_output::PROXY, None);
+                return Ok(());
+            }
+        };
+        forget_sidecar(pid);
+        process_output::save_last_run(process_output::PROXY, None);
+
+        if !config.auto_restart_proxy {
+            return Err(format!("Proxy exited ({}), autoRestartProxy is off", exit));
+        }
+        let delay = match policy.on_exit(Instant::now(), started.elapsed()) {
+            RestartDecision::Restart { delay, .. } => delay,
+            RestartDecision::CrashLoop { exits } => {
+                return Err(format!(
+                    "Proxy exited ({}), {} exits within {} minutes, giving up",
+                    exit, exits, config.crash_loop_window_minutes
+                ));
+            }
+        };
+        eprintln!("[ProxyPal] Proxy exited ({}), restarting in {}s", exit, delay.as_secs());
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.changed() => return Ok(()),
+        }
+    }
+}
+
+async fn forward_output(stream: impl AsyncRead + Unpin, prefix: &'static str, kind: OutputStream) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if kind == OutputStream::Stderr {
+            eprintln!("{} {}", prefix, line);
+        } else {
+            println!("{} {}", prefix, line);
+        }
+        process_output::record(None, process_output::PROXY, None, kind, &line);
+    }
+}
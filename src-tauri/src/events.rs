@@ -0,0 +1,46 @@
+//! Where background work (log watcher, tunnels) reports progress: the webview in the
+//! desktop app, stderr in the daemon.
+
+use serde::Serialize;
+use std::sync::Arc;
+
+pub trait EventSink: Send + Sync {
+    fn emit_value(&self, event: &str, payload: serde_json::Value);
+}
+
+pub type Events = Arc<dyn EventSink>;
+
+impl dyn EventSink + '_ {
+    pub fn emit<T: Serialize>(&self, event: &str, payload: T) {
+        match serde_json::to_value(payload) {
+            Ok(value) => self.emit_value(event, value),
+            Err(e) => eprintln!("[ProxyPal] Failed to serialize {} event: {}", event, e),
+        }
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl<R: tauri::Runtime> EventSink for tauri::AppHandle<R> {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) {
+        let _ = tauri::Emitter::emit(self, event, payload);
+    }
+}
+
+/// Daemon sink: one line per event on stderr
+pub struct LogEvents;
+
+impl EventSink for LogEvents {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) {
+        // Tunnel output is kept by process_output; ssh -v alone would flood the log
+        if event == "process-output" {
+            return;
+        }
+        eprintln!("[ProxyPal] {} {}", event, payload);
+    }
+}
+
+// Background tasks run on Tauri's runtime in the app and on the daemon's own Tokio runtime
+#[cfg(feature = "desktop")]
+pub use tauri::async_runtime::{spawn, JoinHandle};
+#[cfg(not(feature = "desktop"))]
+pub use tokio::task::{spawn, JoinHandle};
@@ -1,6 +1,11 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
+// CLI commands attach to the terminal's console instead (see cli::run).
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = proxypal_lib::cli::run(&args) {
+        std::process::exit(code);
+    }
     proxypal_lib::run()
 }
@@ -0,0 +1,314 @@
+//! The last lines each supervised process printed, kept in memory so they can be read
+//! in packaged builds (where stdout goes nowhere) and streamed to the UI as
+//! "process-output" events. When a process exits the lines of its last run are saved
+//! to logs/<process>.last-run.jsonl, for a look after ProxyPal itself was restarted.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::config::get_proxypal_config_dir;
+use crate::events::EventSink;
+use crate::types::{OutputLine, OutputStream, ProcessOutput};
+
+pub const PROXY: &str = "cli-proxy-api";
+pub const COPILOT: &str = "copilot-api";
+pub const SSH: &str = "ssh";
+pub const CLOUDFLARED: &str = "cloudflared";
+pub const PROCESSES: [&str; 4] = [PROXY, COPILOT, SSH, CLOUDFLARED];
+
+/// Lines kept per process
+const MAX_LINES: usize = 2000;
+/// Longer lines are cut
+const MAX_LINE_BYTES: usize = 4096;
+/// Lines get_process_output returns when not asked for a number
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub const DEFAULT_LINES: usize = 200;
+
+/// What one process printed, over all its runs since ProxyPal started
+struct Buffer {
+    lines: VecDeque<OutputLine>,
+    /// Lines recorded so far, evicted ones included, so `lines[i]` is line number
+    /// `recorded - lines.len() + i`
+    recorded: u64,
+    /// Number of the first line of each source's current run
+    run_starts: Vec<(Option<String>, u64)>,
+}
+
+impl Buffer {
+    const fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            recorded: 0,
+            run_starts: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, line: OutputLine) {
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.recorded += 1;
+    }
+
+    fn start_run(&mut self, source: Option<&str>) {
+        self.run_starts.retain(|(s, _)| s.as_deref() != source);
+        self.run_starts.push((source.map(str::to_string), self.recorded));
+    }
+
+    /// Lines of `source` since its run started, all of them if no start was recorded
+    fn last_run<'a>(&'a self, source: Option<&'a str>) -> impl Iterator<Item = &'a OutputLine> + 'a {
+        let start = self
+            .run_starts
+            .iter()
+            .find(|(s, _)| s.as_deref() == source)
+            .map_or(0, |(_, start)| *start);
+        let first = self.recorded - self.lines.len() as u64;
+        self.lines
+            .iter()
+            .skip(start.saturating_sub(first) as usize)
+            .filter(move |line| line.source.as_deref() == source)
+    }
+}
+
+/// One buffer per entry of PROCESSES
+static BUFFERS: Mutex<[Buffer; 4]> = Mutex::new([Buffer::new(), Buffer::new(), Buffer::new(), Buffer::new()]);
+/// AppConfig::persist_process_output
+static PERSIST: AtomicBool = AtomicBool::new(true);
+
+fn index(process: &str) -> Result<usize, String> {
+    PROCESSES.iter().position(|p| *p == process).ok_or_else(|| {
+        format!("Unknown process '{}' (expected one of: {})", process, PROCESSES.join(", "))
+    })
+}
+
+fn last_run_path(process: &str) -> PathBuf {
+    get_proxypal_config_dir()
+        .join("logs")
+        .join(format!("{}.last-run.jsonl", process))
+}
+
+/// Keep a line `process` printed and stream it through `sink`
+pub fn record(
+    sink: Option<&dyn EventSink>,
+    process: &'static str,
+    source: Option<&str>,
+    stream: OutputStream,
+    text: &str,
+) {
+    let Ok(index) = index(process) else {
+        return;
+    };
+    let text = truncate(text.trim_end());
+    let line = OutputLine {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        process: process.to_string(),
+        source: source.map(str::to_string),
+        stream,
+        text: text.to_string(),
+    };
+
+    BUFFERS.lock().unwrap()[index].push(line.clone());
+    if let Some(sink) = sink {
+        sink.emit("process-output", line);
+    }
+}
+
+/// Cut `text` to MAX_LINE_BYTES, at a char boundary
+fn truncate(text: &str) -> &str {
+    if text.len() <= MAX_LINE_BYTES {
+        return text;
+    }
+    let mut end = MAX_LINE_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Mark where a new run of `process` (the tunnel `source` for ssh and cloudflared)
+/// begins, called when it is spawned
+pub fn start_run(process: &str, source: Option<&str>) {
+    if let Ok(index) = index(process) {
+        BUFFERS.lock().unwrap()[index].start_run(source);
+    }
+}
+
+pub fn set_persist(enabled: bool) {
+    PERSIST.store(enabled, Ordering::Relaxed);
+}
+
+/// Save what `process` (the tunnel `source` for ssh and cloudflared) printed in its
+/// last run, called when it exits
+pub fn save_last_run(process: &str, source: Option<&str>) {
+    if !PERSIST.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(index) = index(process) else {
+        return;
+    };
+    let data: String = BUFFERS.lock().unwrap()[index]
+        .last_run(source)
+        .filter_map(|line| serde_json::to_string(line).ok())
+        .map(|json| json + "\n")
+        .collect();
+    if data.is_empty() {
+        return;
+    }
+
+    let path = last_run_path(process);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    // Output can echo tokens and account names
+    if let Err(e) = crate::config::write_private(&path, data.as_bytes()) {
+        eprintln!("[ProxyPal] Failed to save {} output: {}", process, e);
+    }
+}
+
+/// Text of the lines `process` printed since `since` (Unix ms), oldest first
+pub fn texts_since(process: &str, since: u64) -> Vec<String> {
+    let Ok(index) = index(process) else {
+        return Vec::new();
+    };
+    BUFFERS.lock().unwrap()[index]
+        .lines
+        .iter()
+        .filter(|line| line.timestamp >= since)
+        .map(|line| line.text.clone())
+        .collect()
+}
+
+/// The last `lines` lines of `process`, from the run saved on disk if it hasn't
+/// printed anything since ProxyPal started
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn output(process: &str, lines: usize) -> Result<ProcessOutput, String> {
+    let index = index(process)?;
+    let buffered: Vec<OutputLine> = {
+        let buffers = BUFFERS.lock().unwrap();
+        let buffer = &buffers[index].lines;
+        buffer.iter().skip(buffer.len().saturating_sub(lines)).cloned().collect()
+    };
+    if !buffered.is_empty() {
+        return Ok(ProcessOutput {
+            process: process.to_string(),
+            lines: buffered,
+            from_previous_run: false,
+        });
+    }
+
+    let saved: Vec<OutputLine> = std::fs::read_to_string(last_run_path(process))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = saved.len().saturating_sub(lines);
+    Ok(ProcessOutput {
+        process: process.to_string(),
+        from_previous_run: !saved.is_empty(),
+        lines: saved.into_iter().skip(skip).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(source: Option<&str>, text: &str) -> OutputLine {
+        OutputLine {
+            timestamp: 0,
+            process: SSH.to_string(),
+            source: source.map(str::to_string),
+            stream: OutputStream::Stderr,
+            text: text.to_string(),
+        }
+    }
+
+    fn texts<'a>(lines: impl Iterator<Item = &'a OutputLine>) -> Vec<&'a str> {
+        lines.map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn oldest_lines_are_evicted() {
+        let mut buffer = Buffer::new();
+        for i in 0..MAX_LINES + 5 {
+            buffer.push(line(None, &i.to_string()));
+        }
+        assert_eq!(buffer.lines.len(), MAX_LINES);
+        assert_eq!(buffer.lines.front().unwrap().text, "5");
+        assert_eq!(buffer.recorded, MAX_LINES as u64 + 5);
+    }
+
+    #[test]
+    fn long_lines_are_cut_at_a_char_boundary() {
+        assert_eq!(truncate("short"), "short");
+        let ascii = "a".repeat(MAX_LINE_BYTES + 10);
+        assert_eq!(truncate(&ascii).len(), MAX_LINE_BYTES);
+        // "é" is two bytes, so MAX_LINE_BYTES falls inside the last one kept
+        let text = format!("a{}", "é".repeat(MAX_LINE_BYTES));
+        let cut = truncate(&text);
+        assert_eq!(cut.len(), MAX_LINE_BYTES - 1);
+        assert!(cut.ends_with('é'));
+    }
+
+    #[test]
+    fn last_run_starts_at_the_latest_spawn() {
+        let mut buffer = Buffer::new();
+        buffer.push(line(None, "before any run"));
+        assert_eq!(texts(buffer.last_run(None)), vec!["before any run"]);
+
+        buffer.start_run(None);
+        buffer.push(line(None, "first run"));
+        buffer.start_run(None);
+        buffer.push(line(None, "second run"));
+        assert_eq!(texts(buffer.last_run(None)), vec!["second run"]);
+    }
+
+    #[test]
+    fn runs_are_kept_apart_per_tunnel() {
+        let mut buffer = Buffer::new();
+        buffer.start_run(Some("a"));
+        buffer.push(line(Some("a"), "a1"));
+        buffer.start_run(Some("b"));
+        buffer.push(line(Some("b"), "b1"));
+        buffer.push(line(Some("a"), "a2"));
+        buffer.start_run(Some("b"));
+        buffer.push(line(Some("b"), "b2"));
+
+        assert_eq!(texts(buffer.last_run(Some("a"))), vec!["a1", "a2"]);
+        assert_eq!(texts(buffer.last_run(Some("b"))), vec!["b2"]);
+    }
+
+    #[test]
+    fn run_start_survives_eviction() {
+        let mut buffer = Buffer::new();
+        buffer.start_run(None);
+        for i in 0..MAX_LINES + 1 {
+            buffer.push(line(None, &i.to_string()));
+        }
+        let run = texts(buffer.last_run(None));
+        assert_eq!(run.len(), MAX_LINES);
+        assert_eq!(run[0], "1");
+    }
+
+    #[test]
+    fn output_falls_back_to_the_saved_run() {
+        let _dir = crate::config::test_config_dir();
+        assert!(output(CLOUDFLARED, 10).unwrap().lines.is_empty());
+
+        let saved: String = ["one", "two", "three"]
+            .iter()
+            .map(|text| serde_json::to_string(&line(Some("tunnel"), text)).unwrap() + "\n")
+            .collect();
+        let path = last_run_path(CLOUDFLARED);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, saved).unwrap();
+
+        let output = output(CLOUDFLARED, 2).unwrap();
+        assert!(output.from_previous_run);
+        assert_eq!(texts(output.lines.iter()), vec!["two", "three"]);
+        assert!(super::output("nope", 2).is_err());
+    }
+}
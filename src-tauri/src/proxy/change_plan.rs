@@ -0,0 +1,195 @@
+//! Works out the least disruptive way to bring a running sidecar in line with a new AppConfig.
+
+use serde_json::Value;
+
+use crate::config::AppConfig;
+use crate::types::{ConfigApplyMethod, ConfigFieldChange};
+
+/// Fields with a Management API setter: (AppConfig field, endpoint under /v0/management/)
+const MANAGEMENT_API_FIELDS: &[(&str, &str)] = &[
+    ("usageStatsEnabled", "usage-statistics-enabled"),
+    ("forceModelMappings", "ampcode/force-model-mappings"),
+    ("maxRetryInterval", "max-retry-interval"),
+    ("wsAuth", "ws-auth"),
+];
+
+/// Fields the sidecar only reads at startup. The management key is among them because
+/// CLIProxyAPI hashes it in place on load, so a reload would leave us without the plaintext.
+const RESTART_FIELDS: &[&str] = &["port", "managementKey"];
+
+/// Fields that only matter to ProxyPal itself and never reach proxy-config.yaml
+const PROXYPAL_ONLY_FIELDS: &[&str] = &[
+    "autoStart",
+    "launchAtLogin",
+    "closeToTray",
+    "sidebarPinned",
+    "sshConfigs",
+    "cloudflareConfigs",
+    "configVersion",
+    "reasoningEffortLevel",
+    "snapshotLimit",
+    "allowedModels",
+    "autoRestartProxy",
+    "crashLoopMaxExits",
+    "crashLoopWindowMinutes",
+    "proxyReadyTimeoutSecs",
+    "autoSelectPort",
+    "persistProcessOutput",
+];
+
+/// A Management API PUT that applies one changed field
+#[derive(Debug, Clone)]
+pub struct ManagementUpdate {
+    pub field: String,
+    pub endpoint: &'static str,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangePlan {
+    pub changes: Vec<ConfigFieldChange>,
+    pub management_updates: Vec<ManagementUpdate>,
+}
+
+impl ChangePlan {
+    /// The most disruptive method any change needs
+    pub fn method(&self) -> ConfigApplyMethod {
+        self.changes
+            .iter()
+            .map(|c| c.method)
+            .max()
+            .unwrap_or(ConfigApplyMethod::None)
+    }
+}
+
+fn method_for(field: &str) -> ConfigApplyMethod {
+    if RESTART_FIELDS.contains(&field) {
+        ConfigApplyMethod::Restart
+    } else if MANAGEMENT_API_FIELDS.iter().any(|(f, _)| *f == field) {
+        ConfigApplyMethod::ManagementApi
+    } else if PROXYPAL_ONLY_FIELDS.contains(&field) {
+        ConfigApplyMethod::None
+    } else {
+        // Anything else feeds the generated config; regenerating it is always safe
+        ConfigApplyMethod::FileReload
+    }
+}
+
+/// Compare two configs field by field and decide how each change gets applied
+pub fn plan_config_change(old: &AppConfig, new: &AppConfig) -> ChangePlan {
+    let old = serde_json::to_value(old).unwrap_or_default();
+    let new = serde_json::to_value(new).unwrap_or_default();
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return ChangePlan {
+            changes: Vec::new(),
+            management_updates: Vec::new(),
+        };
+    };
+
+    let mut changes = Vec::new();
+    let mut management_updates = Vec::new();
+    for (field, value) in &new {
+        if old.get(field) == Some(value) {
+            continue;
+        }
+        let method = method_for(field);
+        if method == ConfigApplyMethod::ManagementApi {
+            if let Some((_, endpoint)) = MANAGEMENT_API_FIELDS.iter().find(|(f, _)| f == field) {
+                management_updates.push(ManagementUpdate {
+                    field: field.clone(),
+                    endpoint,
+                    value: value.clone(),
+                });
+            }
+        }
+        changes.push(ConfigFieldChange {
+            field: field.clone(),
+            method,
+        });
+    }
+
+    ChangePlan {
+        changes,
+        management_updates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_for(change: impl FnOnce(&mut AppConfig)) -> ChangePlan {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        change(&mut new);
+        plan_config_change(&old, &new)
+    }
+
+    fn method_of(plan: &ChangePlan, field: &str) -> Option<ConfigApplyMethod> {
+        plan.changes.iter().find(|c| c.field == field).map(|c| c.method)
+    }
+
+    #[test]
+    fn unchanged_config_needs_nothing() {
+        let plan = plan_for(|_| {});
+        assert!(plan.changes.is_empty());
+        assert!(plan.management_updates.is_empty());
+        assert_eq!(plan.method(), ConfigApplyMethod::None);
+    }
+
+    #[test]
+    fn port_change_restarts() {
+        let plan = plan_for(|c| c.port += 1);
+        assert_eq!(method_of(&plan, "port"), Some(ConfigApplyMethod::Restart));
+        assert_eq!(plan.method(), ConfigApplyMethod::Restart);
+        assert!(plan.management_updates.is_empty());
+    }
+
+    #[test]
+    fn usage_stats_toggle_goes_through_management_api() {
+        let plan = plan_for(|c| c.usage_stats_enabled = !c.usage_stats_enabled);
+        assert_eq!(plan.method(), ConfigApplyMethod::ManagementApi);
+        assert_eq!(plan.management_updates.len(), 1);
+        let update = &plan.management_updates[0];
+        assert_eq!(update.field, "usageStatsEnabled");
+        assert_eq!(update.endpoint, "usage-statistics-enabled");
+        assert_eq!(update.value, Value::Bool(!AppConfig::default().usage_stats_enabled));
+    }
+
+    #[test]
+    fn routing_change_reloads_the_file() {
+        let plan = plan_for(|c| c.routing_strategy = "fill-first".to_string());
+        assert_eq!(
+            method_of(&plan, "routingStrategy"),
+            Some(ConfigApplyMethod::FileReload)
+        );
+        assert_eq!(plan.method(), ConfigApplyMethod::FileReload);
+        assert!(plan.management_updates.is_empty());
+    }
+
+    #[test]
+    fn proxypal_only_change_needs_nothing() {
+        let plan = plan_for(|c| c.close_to_tray = !c.close_to_tray);
+        assert_eq!(method_of(&plan, "closeToTray"), Some(ConfigApplyMethod::None));
+        assert_eq!(plan.method(), ConfigApplyMethod::None);
+    }
+
+    #[test]
+    fn strongest_method_wins() {
+        let plan = plan_for(|c| {
+            c.usage_stats_enabled = !c.usage_stats_enabled;
+            c.routing_strategy = "fill-first".to_string();
+        });
+        assert_eq!(plan.method(), ConfigApplyMethod::FileReload);
+        // The Management API update is still planned for the field that has one
+        assert_eq!(plan.management_updates.len(), 1);
+
+        let plan = plan_for(|c| {
+            c.usage_stats_enabled = !c.usage_stats_enabled;
+            c.routing_strategy = "fill-first".to_string();
+            c.port += 1;
+        });
+        assert_eq!(plan.changes.len(), 3);
+        assert_eq!(plan.method(), ConfigApplyMethod::Restart);
+    }
+}
@@ -0,0 +1,187 @@
+//! Models listed in the proxy's "copilot" openai-compatibility entry.
+//!
+//! The list comes from copilot-api's /v1/models. The last list fetched is kept in
+//! copilot-models.json for sessions where copilot-api isn't reachable when the proxy
+//! starts; before anything was ever fetched, a built-in list is used. The user's
+//! hidden models, extra aliases and reasoning suffixes are applied on top.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use super::sidecar_config::{ModelAlias, GPT5_BASE_MODELS};
+use crate::config::{get_proxypal_config_dir, wildcard_match};
+use crate::types::{CopilotConfig, CopilotModelList, CopilotModelSource};
+
+// Other models exposed through copilot-api, besides the GPT-5 family
+const COPILOT_EXTRA_MODELS: &[&str] = &[
+    // OpenAI GPT models - use direct names (no prefix) for CLIProxyAPI compatibility
+    "gpt-4.1",
+    // Legacy OpenAI models (may still work)
+    "gpt-4o",
+    "gpt-4",
+    "gpt-4-turbo",
+    "o1",
+    "o1-mini",
+    // xAI Grok model
+    "grok-code-fast-1",
+    // Fine-tuned models
+    "raptor-mini",
+    // Google Gemini models (via OpenAI-compat)
+    "gemini-2.5-pro",
+    "gemini-3-pro-preview",
+    // Claude models (GA)
+    "claude-haiku-4.5",
+    "claude-opus-4.1",
+    "claude-sonnet-4",
+    "claude-sonnet-4.5",
+    // Claude models (Preview)
+    "claude-opus-4.5",
+    "claude-opus-4.6",
+];
+
+/// The list in effect: fetched this session, else cached, else built in
+static CURRENT: Mutex<Option<CopilotModelList>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheFile {
+    fetched_at: u64,
+    models: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelsResponseEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponseEntry {
+    id: String,
+}
+
+pub fn get_copilot_models_cache_path() -> std::path::PathBuf {
+    get_proxypal_config_dir().join("copilot-models.json")
+}
+
+fn builtin_models() -> Vec<String> {
+    std::iter::once(COPILOT_EXTRA_MODELS[0])
+        .chain(GPT5_BASE_MODELS.iter().copied())
+        .chain(COPILOT_EXTRA_MODELS[1..].iter().copied())
+        .map(str::to_string)
+        .collect()
+}
+
+fn read_cache() -> Option<CopilotModelList> {
+    let data = std::fs::read_to_string(get_copilot_models_cache_path()).ok()?;
+    let cache: CacheFile = serde_json::from_str(&data).ok()?;
+    if cache.models.is_empty() {
+        return None;
+    }
+    Some(CopilotModelList {
+        models: cache.models,
+        source: CopilotModelSource::Cache,
+        fetched_at: Some(cache.fetched_at),
+    })
+}
+
+pub fn current_models() -> CopilotModelList {
+    let mut current = CURRENT.lock().unwrap();
+    current
+        .get_or_insert_with(|| {
+            read_cache().unwrap_or_else(|| CopilotModelList {
+                models: builtin_models(),
+                source: CopilotModelSource::Builtin,
+                fetched_at: None,
+            })
+        })
+        .clone()
+}
+
+/// Fetch the model list from copilot-api on `port`, cache it and make it current.
+/// Returns whether the list differs from the one in effect before.
+pub async fn refresh_models(port: u16) -> Result<(CopilotModelList, bool), String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(3))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get(format!("http://127.0.0.1:{}/v1/models", port))
+        .send()
+        .await
+        .map_err(|e| format!("copilot-api is not reachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("copilot-api returned status {}", response.status()));
+    }
+    let body: ModelsResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse copilot-api models: {}", e))?;
+
+    let mut models: Vec<String> = Vec::new();
+    for entry in body.data {
+        let id = entry.id.trim().to_string();
+        if !id.is_empty() && !models.contains(&id) {
+            models.push(id);
+        }
+    }
+    if models.is_empty() {
+        return Err("copilot-api returned no models".to_string());
+    }
+
+    let fetched_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let cache = CacheFile { fetched_at, models: models.clone() };
+    if let Err(e) = serde_json::to_string_pretty(&cache)
+        .map_err(|e| e.to_string())
+        .and_then(|data| std::fs::write(get_copilot_models_cache_path(), data).map_err(|e| e.to_string()))
+    {
+        eprintln!("[ProxyPal] Failed to cache copilot models: {}", e);
+    }
+
+    let list = CopilotModelList {
+        models,
+        source: CopilotModelSource::Live,
+        fetched_at: Some(fetched_at),
+    };
+    let changed = current_models().models != list.models;
+    *CURRENT.lock().unwrap() = Some(list.clone());
+    Ok((list, changed))
+}
+
+/// The copilot entry's model list: every model that isn't hidden, its reasoning
+/// variants, then the extra aliases
+pub fn entry_models(copilot: &CopilotConfig, models: &[String]) -> Vec<ModelAlias> {
+    let hidden = |name: &str| copilot.hidden_models.iter().any(|p| wildcard_match(p, name));
+    let same_name = |name: String| ModelAlias {
+        alias: name.clone(),
+        name,
+    };
+
+    let mut entries = Vec::new();
+    for model in models.iter().filter(|m| !hidden(m)) {
+        entries.push(same_name(model.clone()));
+        let family = copilot
+            .reasoning_suffixes
+            .iter()
+            .find(|f| wildcard_match(&f.pattern, model));
+        for suffix in family.map(|f| f.suffixes.as_slice()).unwrap_or_default() {
+            let variant = format!("{}({})", model, suffix);
+            if !hidden(&variant) {
+                entries.push(same_name(variant));
+            }
+        }
+    }
+    for extra in &copilot.extra_aliases {
+        let (name, alias) = (extra.name.trim(), extra.alias.trim());
+        if name.is_empty() || alias.is_empty() || entries.iter().any(|e| e.alias == alias) {
+            continue;
+        }
+        entries.push(ModelAlias {
+            alias: alias.to_string(),
+            name: name.to_string(),
+        });
+    }
+    entries
+}
@@ -0,0 +1,452 @@
+//! Structured merge of proxy-config-custom.yaml into the generated sidecar config.
+//!
+//! Merge rules:
+//! - mappings merge key by key (recursively)
+//! - sequences append the user's items after the generated ones
+//! - scalars (and values of a different type) from the user override ours
+//! - a value tagged `!replace` replaces the generated value wholesale, e.g.
+//!   `api-keys: !replace ["only-this-key"]` or `payload: !replace { default: [] }`
+
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::sidecar_config::{build_sidecar_config, SidecarConfig, GENERATED_HEADER};
+use crate::config::{
+    get_custom_proxy_config_path, get_proxy_config_path, get_proxypal_config_dir, write_private, AppConfig,
+};
+use crate::types::ProxyConfigPreviewLine;
+
+const REPLACE_TAG: &str = "replace";
+
+/// One step of a path into the YAML document
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Path into the YAML document, displayed as `payload.default[2].params`
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct YamlPath(pub Vec<PathSegment>);
+
+impl YamlPath {
+    fn key(&self, key: &Value) -> YamlPath {
+        let mut segments = self.0.clone();
+        segments.push(PathSegment::Key(key_to_string(key)));
+        YamlPath(segments)
+    }
+
+    fn index(&self, index: usize) -> YamlPath {
+        let mut segments = self.0.clone();
+        segments.push(PathSegment::Index(index));
+        YamlPath(segments)
+    }
+}
+
+impl fmt::Display for YamlPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The generated config with user overrides merged in, plus the paths that came from the user
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub value: Value,
+    pub custom_paths: BTreeSet<YamlPath>,
+}
+
+impl EffectiveConfig {
+    fn is_custom(&self, path: &YamlPath) -> bool {
+        // A node comes from the override file if it, or any ancestor, was taken from it
+        (0..=path.0.len()).any(|len| {
+            self.custom_paths
+                .contains(&YamlPath(path.0[..len].to_vec()))
+        })
+    }
+}
+
+fn key_to_string(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|s| s.trim_end().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Remove our merge tags from a value that is taken over as a whole
+fn strip_tags(value: Value) -> Result<Value, String> {
+    match value {
+        Value::Tagged(tagged) => {
+            if tagged.tag != REPLACE_TAG {
+                return Err(format!(
+                    "Unsupported tag {} (only !{} is understood)",
+                    tagged.tag, REPLACE_TAG
+                ));
+            }
+            strip_tags(tagged.value)
+        }
+        Value::Mapping(map) => map
+            .into_iter()
+            .map(|(k, v)| Ok((k, strip_tags(v)?)))
+            .collect::<Result<Mapping, String>>()
+            .map(Value::Mapping),
+        Value::Sequence(seq) => seq
+            .into_iter()
+            .map(strip_tags)
+            .collect::<Result<Vec<_>, String>>()
+            .map(Value::Sequence),
+        other => Ok(other),
+    }
+}
+
+fn merge_value(
+    base: Value,
+    custom: Value,
+    path: &YamlPath,
+    custom_paths: &mut BTreeSet<YamlPath>,
+) -> Result<Value, String> {
+    match (base, custom) {
+        (_, Value::Tagged(tagged)) => {
+            if tagged.tag != REPLACE_TAG {
+                return Err(format!(
+                    "{}: unsupported tag {} (only !{} is understood)",
+                    path, tagged.tag, REPLACE_TAG
+                ));
+            }
+            custom_paths.insert(path.clone());
+            strip_tags(tagged.value).map_err(|e| format!("{}: {}", path, e))
+        }
+        (Value::Mapping(mut base), Value::Mapping(custom)) => {
+            for (key, custom_value) in custom {
+                let child = path.key(&key);
+                // Merge in place so generated keys keep their position in the document
+                match base.get_mut(&key) {
+                    Some(slot) => {
+                        let base_value = std::mem::take(slot);
+                        *slot = merge_value(base_value, custom_value, &child, custom_paths)?;
+                    }
+                    None => {
+                        custom_paths.insert(child.clone());
+                        let value = strip_tags(custom_value).map_err(|e| format!("{}: {}", child, e))?;
+                        base.insert(key, value);
+                    }
+                }
+            }
+            Ok(Value::Mapping(base))
+        }
+        (Value::Sequence(mut base), Value::Sequence(custom)) => {
+            for item in custom {
+                custom_paths.insert(path.index(base.len()));
+                base.push(strip_tags(item).map_err(|e| format!("{}: {}", path, e))?);
+            }
+            Ok(Value::Sequence(base))
+        }
+        (_, custom) => {
+            custom_paths.insert(path.clone());
+            strip_tags(custom).map_err(|e| format!("{}: {}", path, e))
+        }
+    }
+}
+
+/// Deep-merge the user's override document into the generated one
+pub fn merge_custom_config(generated: &SidecarConfig, custom: Option<Value>) -> Result<EffectiveConfig, String> {
+    let value = serde_yaml::to_value(generated)
+        .map_err(|e| format!("Failed to serialize proxy config: {}", e))?;
+    let mut custom_paths = BTreeSet::new();
+
+    let value = match custom {
+        // An empty override file parses as null
+        None | Some(Value::Null) => value,
+        Some(custom @ Value::Mapping(_)) => {
+            merge_value(value, custom, &YamlPath::default(), &mut custom_paths)?
+        }
+        Some(_) => {
+            return Err("proxy-config-custom.yaml must contain a mapping at the top level".to_string())
+        }
+    };
+
+    Ok(EffectiveConfig {
+        value,
+        custom_paths,
+    })
+}
+
+/// Read and parse proxy-config-custom.yaml, if present
+pub fn load_custom_config(path: &Path) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_yaml::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Generate the sidecar config for `config` and merge proxy-config-custom.yaml into it
+pub fn build_effective_config(config: &AppConfig, proxy_url: Option<String>) -> Result<EffectiveConfig, String> {
+    let generated = build_sidecar_config(config, proxy_url);
+    let custom = load_custom_config(&get_custom_proxy_config_path())?;
+    merge_custom_config(&generated, custom)
+}
+
+/// Serialize the effective config to the text written to proxy-config.yaml
+pub fn render_effective_config(effective: &EffectiveConfig) -> Result<String, String> {
+    let body = serde_yaml::to_string(&effective.value)
+        .map_err(|e| format!("Failed to serialize proxy config: {}", e))?;
+    Ok(format!("{}{}", GENERATED_HEADER, body))
+}
+
+/// Generate proxy-config.yaml for `config` and write it owner-only: it holds the
+/// provider keys, the management key and the proxy password in plaintext
+pub fn write_effective_config(config: &AppConfig) -> Result<PathBuf, String> {
+    let proxy_url = crate::proxy::upstream::effective_proxy_url(config);
+    let effective = build_effective_config(config, Some(proxy_url))?;
+    let yaml = render_effective_config(&effective)?;
+    std::fs::create_dir_all(get_proxypal_config_dir()).map_err(|e| e.to_string())?;
+    let path = get_proxy_config_path();
+    write_private(&path, yaml.as_bytes()).map_err(|e| format!("Failed to write proxy config: {}", e))?;
+    Ok(path)
+}
+
+// Annotated rendering for the preview. Scalars are serialized by serde_yaml; the
+// block layout mirrors serde_yaml's own (2-space maps, sequences flush with their key).
+
+fn scalar_lines(value: &Value) -> Vec<String> {
+    let text = match value {
+        Value::Mapping(m) if m.is_empty() => "{}".to_string(),
+        Value::Sequence(s) if s.is_empty() => "[]".to_string(),
+        other => serde_yaml::to_string(other).unwrap_or_default(),
+    };
+    text.trim_end_matches('\n').lines().map(str::to_string).collect()
+}
+
+fn is_block(value: &Value) -> bool {
+    matches!(value, Value::Mapping(m) if !m.is_empty())
+        || matches!(value, Value::Sequence(s) if !s.is_empty())
+}
+
+struct Annotator<'a> {
+    effective: &'a EffectiveConfig,
+    lines: Vec<ProxyConfigPreviewLine>,
+}
+
+impl Annotator<'_> {
+    fn push(&mut self, text: String, path: &YamlPath) {
+        self.lines.push(ProxyConfigPreviewLine {
+            text,
+            path: path.to_string(),
+            from_custom: self.effective.is_custom(path),
+        });
+    }
+
+    /// Emit `prefix` followed by a scalar; continuation lines of block scalars are indented
+    fn push_scalar(&mut self, prefix: String, value: &Value, indent: usize, path: &YamlPath) {
+        let mut lines = scalar_lines(value).into_iter();
+        let first = lines.next().unwrap_or_default();
+        self.push(format!("{}{}", prefix, first), path);
+        for line in lines {
+            self.push(format!("{}{}", " ".repeat(indent), line), path);
+        }
+    }
+
+    fn mapping(&mut self, map: &Mapping, indent: usize, path: &YamlPath) {
+        let pad = " ".repeat(indent);
+        for (key, value) in map {
+            let child = path.key(key);
+            let key_text = scalar_lines(key).join(" ");
+            match value {
+                Value::Mapping(m) if !m.is_empty() => {
+                    self.push(format!("{}{}:", pad, key_text), &child);
+                    self.mapping(m, indent + 2, &child);
+                }
+                Value::Sequence(s) if !s.is_empty() => {
+                    self.push(format!("{}{}:", pad, key_text), &child);
+                    self.sequence(s, indent, &child);
+                }
+                scalar => self.push_scalar(format!("{}{}: ", pad, key_text), scalar, indent, &child),
+            }
+        }
+    }
+
+    fn sequence(&mut self, seq: &[Value], indent: usize, path: &YamlPath) {
+        let pad = " ".repeat(indent);
+        for (i, item) in seq.iter().enumerate() {
+            let child = path.index(i);
+            if is_block(item) {
+                // Render the nested block one level deeper, then fold its first line onto the dash
+                let start = self.lines.len();
+                match item {
+                    Value::Mapping(m) => self.mapping(m, indent + 2, &child),
+                    Value::Sequence(s) => self.sequence(s, indent + 2, &child),
+                    _ => unreachable!(),
+                }
+                if let Some(first) = self.lines.get_mut(start) {
+                    let nested_pad = " ".repeat(indent + 2);
+                    let text = first
+                        .text
+                        .strip_prefix(nested_pad.as_str())
+                        .unwrap_or_else(|| first.text.trim_start())
+                        .to_string();
+                    first.text = format!("{}- {}", pad, text);
+                }
+            } else {
+                self.push_scalar(format!("{}- ", pad), item, indent, &child);
+            }
+        }
+    }
+}
+
+/// Line-by-line view of the effective config, marking which lines came from the user override
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn annotate_effective_config(effective: &EffectiveConfig) -> Vec<ProxyConfigPreviewLine> {
+    let mut annotator = Annotator {
+        effective,
+        lines: Vec::new(),
+    };
+    let root = YamlPath::default();
+    for line in GENERATED_HEADER.lines() {
+        annotator.push(line.to_string(), &root);
+    }
+    match &effective.value {
+        Value::Mapping(map) => annotator.mapping(map, 0, &root),
+        other => annotator.push_scalar(String::new(), other, 0, &root),
+    }
+    annotator.lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    fn merge(base: &str, custom: &str) -> Result<EffectiveConfig, String> {
+        let mut custom_paths = BTreeSet::new();
+        let value = merge_value(yaml(base), yaml(custom), &YamlPath::default(), &mut custom_paths)?;
+        Ok(EffectiveConfig { value, custom_paths })
+    }
+
+    fn path(text: &str) -> YamlPath {
+        YamlPath(
+            text.split('.')
+                .map(|segment| match segment.parse() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(segment.to_string()),
+                })
+                .collect(),
+        )
+    }
+
+    fn keys(value: &Value) -> Vec<String> {
+        value.as_mapping().unwrap().keys().map(key_to_string).collect()
+    }
+
+    #[test]
+    fn mappings_merge_in_place() {
+        let effective = merge(
+            "a: 1\nb:\n  x: 1\n  y: 2\nc: 3\n",
+            "d: 4\nb:\n  z: 6\n  y: 5\n",
+        )
+        .unwrap();
+        assert_eq!(keys(&effective.value), ["a", "b", "c", "d"]);
+        assert_eq!(keys(&effective.value["b"]), ["x", "y", "z"]);
+        assert_eq!(effective.value["b"]["y"], yaml("5"));
+
+        assert!(effective.is_custom(&path("b.y")));
+        assert!(effective.is_custom(&path("b.z")));
+        assert!(effective.is_custom(&path("d")));
+        assert!(!effective.is_custom(&path("a")));
+        assert!(!effective.is_custom(&path("b")));
+        assert!(!effective.is_custom(&path("b.x")));
+    }
+
+    #[test]
+    fn sequences_append() {
+        let effective = merge("list: [1, 2]\n", "list: [3]\n").unwrap();
+        assert_eq!(effective.value["list"], yaml("[1, 2, 3]"));
+        assert!(!effective.is_custom(&path("list.1")));
+        assert!(effective.is_custom(&path("list.2")));
+    }
+
+    #[test]
+    fn replace_tag_takes_over_the_value() {
+        let effective = merge("list: [1, 2]\nkeep: true\n", "list: !replace [9]\n").unwrap();
+        assert_eq!(effective.value["list"], yaml("[9]"));
+        assert_eq!(effective.value["keep"], yaml("true"));
+        assert!(effective.is_custom(&path("list")));
+        // Descendants of a replaced value come from the override file too
+        assert!(effective.is_custom(&path("list.0")));
+
+        let effective = merge(
+            "outer:\n  inner:\n    a: 1\n    k: 0\n  other: 2\n",
+            "outer:\n  inner: !replace\n    k: v\n",
+        )
+        .unwrap();
+        assert_eq!(effective.value["outer"]["inner"], yaml("k: v"));
+        assert_eq!(effective.value["outer"]["other"], yaml("2"));
+        assert!(effective.is_custom(&path("outer.inner.k")));
+        assert!(!effective.is_custom(&path("outer.other")));
+
+        // Inside a replaced value a nested !replace is just dropped
+        let effective = merge("a: 1\n", "a: !replace\n  b: !replace [1]\n").unwrap();
+        assert_eq!(effective.value["a"], yaml("b: [1]"));
+    }
+
+    #[test]
+    fn unknown_tag_is_an_error() {
+        let error = merge("a: 1\n", "a: !merge 2\n").unwrap_err();
+        assert!(error.starts_with("a: unsupported tag !merge"), "{}", error);
+
+        // Also in a value that is new to the generated config
+        let error = merge("a: 1\n", "b:\n  c: !keep 1\n").unwrap_err();
+        assert!(error.contains("!keep"), "{}", error);
+        assert!(error.starts_with("b:"), "{}", error);
+    }
+
+    #[test]
+    fn root_has_to_be_a_mapping() {
+        let generated = build_sidecar_config(&AppConfig::default(), None);
+        let error = merge_custom_config(&generated, Some(yaml("[1, 2]"))).unwrap_err();
+        assert!(error.contains("mapping at the top level"), "{}", error);
+
+        // An empty file is no override at all
+        let effective = merge_custom_config(&generated, Some(Value::Null)).unwrap();
+        assert!(effective.custom_paths.is_empty());
+    }
+
+    #[test]
+    fn annotation_matches_the_rendered_file() {
+        let effective = merge(
+            "port: 8317\npayload:\n  default:\n    - models:\n        - name: gpt\n          protocol: openai\n      params:\n        a: 1\n",
+            "payload:\n  default:\n    - models:\n        - name: claude\n          nested:\n            - [1, 2]\n            - x: y\n      params:\n        b: \"two\\nlines\"\n",
+        )
+        .unwrap();
+        let lines = annotate_effective_config(&effective);
+        let rendered = render_effective_config(&effective).unwrap();
+        let annotated: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(annotated, rendered.lines().collect::<Vec<_>>());
+
+        let line = |text: &str| lines.iter().find(|l| l.text.trim() == text).unwrap();
+        assert!(!line("- name: gpt").from_custom);
+        assert!(line("- name: claude").from_custom);
+        assert_eq!(line("- name: claude").path, "payload.default[1].models[0].name");
+        assert!(!line("port: 8317").from_custom);
+    }
+}
@@ -0,0 +1,245 @@
+//! The proxy's own log file, logs/main.log under the config dir. Reading it by byte
+//! offset lets `proxypal logs --follow` pick up exactly where it left off, so no line
+//! is printed twice or dropped between polls.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::config::get_proxypal_config_dir;
+use crate::types::{LogChunk, LogEntry};
+
+/// Lines /v1/logs/tail returns when not asked for a number
+pub const DEFAULT_TAIL_LINES: usize = 1000;
+
+/// Where CLIProxyAPI writes its log when logging-to-file is on
+pub fn main_log_path() -> PathBuf {
+    get_proxypal_config_dir().join("logs").join("main.log")
+}
+
+/// The last `max_lines` complete lines of the log, or with `after` (a cursor from an
+/// earlier read) the ones written since. A line still being written is left for the
+/// next read. A file shorter than `after` was rotated or cleared and is read from the
+/// start.
+pub fn read_log_tail(path: &Path, after: Option<u64>, max_lines: usize) -> Result<LogChunk, String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(LogChunk { entries: Vec::new(), cursor: 0, skipped: 0 });
+        }
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let start = after.filter(|&cursor| cursor <= len).unwrap_or(0);
+    file.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+
+    let max_lines = max_lines.max(1);
+    let mut reader = BufReader::new(file);
+    let mut cursor = start;
+    let mut entries = VecDeque::new();
+    let mut total = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 || line.last() != Some(&b'\n') {
+            break;
+        }
+        cursor += read as u64;
+        let text = String::from_utf8_lossy(&line);
+        if text.trim().is_empty() {
+            continue;
+        }
+        total += 1;
+        if entries.len() == max_lines {
+            entries.pop_front();
+        }
+        entries.push_back(parse_log_line(&text));
+    }
+
+    // Without a cursor the caller asked for the last lines only, nothing was skipped
+    let skipped = if after.is_some() { total - entries.len() } else { 0 };
+    Ok(LogChunk { entries: entries.into(), cursor, skipped })
+}
+
+// Parse a log line into a LogEntry struct
+// Expected formats from CLIProxyAPI:
+// - "[2025-12-02 22:12:52] [info] [gin_logger.go:58] message"
+// - "[2025-12-02 22:12:52] [info] message"
+// - "2024-01-15T10:30:45.123Z [INFO] message"
+pub fn parse_log_line(line: &str) -> LogEntry {
+    let line = line.trim();
+
+    // Format: [timestamp] [level] [source] message
+    // or: [timestamp] [level] message
+    if line.starts_with('[') {
+        let mut parts = Vec::new();
+        let mut current_start = 0;
+        let mut in_bracket = false;
+
+        for (i, c) in line.char_indices() {
+            if c == '[' && !in_bracket {
+                in_bracket = true;
+                current_start = i + 1;
+            } else if c == ']' && in_bracket {
+                in_bracket = false;
+                parts.push(&line[current_start..i]);
+                current_start = i + 1;
+            }
+        }
+
+        // Get the message (everything after the last bracket)
+        let message_start = line.rfind(']').map(|i| i + 1).unwrap_or(0);
+        let message = line[message_start..].trim();
+
+        if parts.len() >= 2 {
+            let timestamp = parts[0].to_string();
+            let level = parts[1].to_uppercase();
+
+            return LogEntry {
+                timestamp,
+                level: normalize_log_level(&level),
+                message: message.to_string(),
+            };
+        }
+    }
+
+    // Try ISO timestamp format: "2024-01-15T10:30:45.123Z [INFO] message"
+    if line.len() > 20 && (line.chars().nth(4) == Some('-') || line.chars().nth(10) == Some('T')) {
+        if let Some(bracket_start) = line.find('[') {
+            if let Some(bracket_end) = line[bracket_start..].find(']') {
+                let timestamp = line[..bracket_start].trim().to_string();
+                let level = line[bracket_start + 1..bracket_start + bracket_end].to_string();
+                let message = line[bracket_start + bracket_end + 1..].trim().to_string();
+
+                return LogEntry {
+                    timestamp,
+                    level: normalize_log_level(&level),
+                    message,
+                };
+            }
+        }
+    }
+
+    // Try "LEVEL: message" format
+    for level in &["ERROR", "WARN", "INFO", "DEBUG", "TRACE"] {
+        if line.to_uppercase().starts_with(level) {
+            let rest = &line[level.len()..];
+            if rest.starts_with(':') || rest.starts_with(' ') {
+                return LogEntry {
+                    timestamp: String::new(),
+                    level: level.to_string(),
+                    message: rest.trim_start_matches([':', ' ']).to_string(),
+                };
+            }
+        }
+    }
+
+    // Default: plain text as INFO
+    LogEntry {
+        timestamp: String::new(),
+        level: "INFO".to_string(),
+        message: line.to_string(),
+    }
+}
+
+// Normalize log level to standard format
+fn normalize_log_level(level: &str) -> String {
+    match level.to_uppercase().as_str() {
+        "ERROR" | "ERR" | "E" => "ERROR".to_string(),
+        "WARN" | "WARNING" | "W" => "WARN".to_string(),
+        "INFO" | "I" => "INFO".to_string(),
+        "DEBUG" | "DBG" | "D" => "DEBUG".to_string(),
+        "TRACE" | "T" => "TRACE".to_string(),
+        _ => level.to_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn messages(chunk: &LogChunk) -> Vec<&str> {
+        chunk.entries.iter().map(|entry| entry.message.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_cliproxyapi_lines() {
+        let entry = parse_log_line("[2025-12-02 22:12:52] [info] [gin_logger.go:58] 200 | POST /v1/messages");
+        assert_eq!(entry.timestamp, "2025-12-02 22:12:52");
+        assert_eq!(entry.level, "INFO");
+        assert_eq!(entry.message, "200 | POST /v1/messages");
+
+        let entry = parse_log_line("2024-01-15T10:30:45.123Z [warning] slow upstream");
+        assert_eq!((entry.level.as_str(), entry.message.as_str()), ("WARN", "slow upstream"));
+        let entry = parse_log_line("plain text");
+        assert_eq!((entry.level.as_str(), entry.message.as_str()), ("INFO", "plain text"));
+    }
+
+    #[test]
+    fn missing_log_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunk = read_log_tail(&dir.path().join("main.log"), Some(10), 10).unwrap();
+        assert!(chunk.entries.is_empty());
+        assert_eq!(chunk.cursor, 0);
+    }
+
+    #[test]
+    fn following_returns_every_line_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.log");
+        append(&path, "[2025-12-02 22:12:52] [info] one\n[2025-12-02 22:12:52] [info] two\n");
+
+        let first = read_log_tail(&path, None, 1).unwrap();
+        assert_eq!(messages(&first), vec!["two"]);
+        assert_eq!(first.skipped, 0);
+
+        // The same line twice in the same second is printed twice, a half written
+        // line waits for its newline
+        append(&path, "[2025-12-02 22:12:52] [info] two\n[2025-12-02 22:12:53] [info] thr");
+        let second = read_log_tail(&path, Some(first.cursor), 10).unwrap();
+        assert_eq!(messages(&second), vec!["two"]);
+
+        append(&path, "ee\n");
+        let third = read_log_tail(&path, Some(second.cursor), 10).unwrap();
+        assert_eq!(messages(&third), vec!["three"]);
+        let fourth = read_log_tail(&path, Some(third.cursor), 10).unwrap();
+        assert!(fourth.entries.is_empty());
+        assert_eq!(fourth.cursor, third.cursor);
+    }
+
+    #[test]
+    fn overflow_is_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.log");
+        append(&path, "start\n");
+        let cursor = read_log_tail(&path, None, 10).unwrap().cursor;
+
+        append(&path, "a\nb\n\nc\nd\n");
+        let chunk = read_log_tail(&path, Some(cursor), 3).unwrap();
+        assert_eq!(messages(&chunk), vec!["b", "c", "d"]);
+        assert_eq!(chunk.skipped, 1);
+    }
+
+    #[test]
+    fn rotated_log_is_read_from_the_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.log");
+        append(&path, "an old line that is fairly long\n");
+        let cursor = read_log_tail(&path, None, 10).unwrap().cursor;
+
+        std::fs::write(&path, "new\n").unwrap();
+        let chunk = read_log_tail(&path, Some(cursor), 10).unwrap();
+        assert_eq!(messages(&chunk), vec!["new"]);
+        assert_eq!(chunk.cursor, 4);
+    }
+}
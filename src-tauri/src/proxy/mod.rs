@@ -1 +1,19 @@
 // Proxy-specific helpers (config generation, log watcher, etc.) will live here.
+
+// Used by the desktop app only, kept in the headless build for its tests
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub mod change_plan;
+pub mod copilot_models;
+pub mod custom_config;
+// Used by the desktop app only, kept in the headless build for its tests
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub mod logs;
+pub mod payload_rules;
+pub mod pidfile;
+pub mod ports;
+pub mod readiness;
+pub mod sidecar_config;
+pub mod sidecars;
+pub mod supervisor;
+pub mod upstream;
+pub mod version;
@@ -0,0 +1,133 @@
+//! Payload rules: the built-in set and how rules render into proxy-config.yaml.
+
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use super::sidecar_config::{thinking_budget_tokens, Payload, PayloadModel, PayloadRuleEntry};
+use crate::config::AppConfig;
+use crate::types::{PayloadRule, PayloadRuleMode};
+
+pub const THINKING_BUDGET_PLACEHOLDER: &str = "{{thinkingBudget}}";
+pub const GEMINI_THINKING_LEVEL_PLACEHOLDER: &str = "{{geminiThinkingLevel}}";
+pub const PLACEHOLDERS: &[&str] = &[THINKING_BUDGET_PLACEHOLDER, GEMINI_THINKING_LEVEL_PLACEHOLDER];
+
+/// Antigravity Claude models: direct names and gemini-prefixed variants
+fn claude_model_names(bases: &[&str]) -> Vec<String> {
+    bases
+        .iter()
+        .flat_map(|base| {
+            [
+                base.to_string(),
+                format!("{}-thinking", base),
+                format!("gemini-{}", base),
+                format!("gemini-{}-thinking", base),
+            ]
+        })
+        .collect()
+}
+
+/// Rules new configs start with (and reset_payload_rules restores)
+pub fn default_payload_rules() -> Vec<PayloadRule> {
+    // Note: GPT/Codex reasoning_effort is NOT injected via payload config because it would
+    // apply to ALL requests matching gpt-5*, including those routed to Claude via model mapping.
+    // Users should use model suffix like gpt-5(high) to specify reasoning effort, which
+    // CLIProxyAPI handles via applyReasoningEffortMetadata() from request metadata.
+    let budget = || BTreeMap::from([("thinking.budget_tokens".to_string(), json!(THINKING_BUDGET_PLACEHOLDER))]);
+    let level = || {
+        BTreeMap::from([(
+            "generationConfig.thinkingConfig.thinkingLevel".to_string(),
+            json!(GEMINI_THINKING_LEVEL_PLACEHOLDER),
+        )])
+    };
+
+    vec![
+        PayloadRule {
+            id: "claude-sonnet-4-5-thinking-budget".to_string(),
+            description: "Thinking budget for Claude Sonnet 4.5".to_string(),
+            enabled: true,
+            models: claude_model_names(&["claude-sonnet-4-5"]),
+            protocol: Some("claude".to_string()),
+            mode: PayloadRuleMode::Default,
+            params: budget(),
+        },
+        PayloadRule {
+            id: "claude-opus-4-thinking-budget".to_string(),
+            description: "Thinking budget for Claude Opus 4.5 and 4.6".to_string(),
+            enabled: true,
+            models: claude_model_names(&["claude-opus-4-5", "claude-opus-4-6"]),
+            protocol: Some("claude".to_string()),
+            mode: PayloadRuleMode::Default,
+            params: budget(),
+        },
+        PayloadRule {
+            id: "gemini-3-pro-thinking-level".to_string(),
+            description: "Gemini 3 Pro thinking level from the thinking budget".to_string(),
+            enabled: true,
+            models: vec!["gemini-3-pro-preview*".to_string()],
+            protocol: None,
+            mode: PayloadRuleMode::Override,
+            params: level(),
+        },
+        PayloadRule {
+            id: "gemini-3-flash-thinking-level".to_string(),
+            description: "Gemini 3 Flash thinking level from the thinking budget".to_string(),
+            enabled: true,
+            models: vec!["gemini-3-flash-preview*".to_string()],
+            protocol: None,
+            mode: PayloadRuleMode::Override,
+            params: level(),
+        },
+    ]
+}
+
+fn uses_placeholder(rule: &PayloadRule, placeholder: &str) -> bool {
+    rule.params.values().any(|v| v.as_str() == Some(placeholder))
+}
+
+fn resolve_placeholders(value: &Value, thinking_budget: u32, gemini_level: &str) -> Value {
+    match value.as_str() {
+        Some(THINKING_BUDGET_PLACEHOLDER) => json!(thinking_budget),
+        Some(GEMINI_THINKING_LEVEL_PLACEHOLDER) => json!(gemini_level),
+        _ => value.clone(),
+    }
+}
+
+/// Render the enabled rules into the sidecar's payload section
+pub fn build_payload(config: &AppConfig) -> Payload {
+    let thinking_budget = thinking_budget_tokens(config);
+    let gemini_level = match thinking_budget {
+        2048 => "low",
+        8192 => "medium",
+        _ => "high", // 32768 or custom -> high
+    };
+
+    let mut payload = Payload {
+        default: Vec::new(),
+        override_rules: Vec::new(),
+    };
+    for rule in config.payload_rules.iter().filter(|r| r.enabled) {
+        if !config.gemini_thinking_injection && uses_placeholder(rule, GEMINI_THINKING_LEVEL_PLACEHOLDER) {
+            continue;
+        }
+        let entry = PayloadRuleEntry {
+            models: rule
+                .models
+                .iter()
+                .map(|name| PayloadModel {
+                    name: name.clone(),
+                    protocol: rule.protocol.clone(),
+                })
+                .collect(),
+            params: rule
+                .params
+                .iter()
+                .map(|(path, value)| (path.clone(), resolve_placeholders(value, thinking_budget, gemini_level)))
+                .collect(),
+        };
+        match rule.mode {
+            PayloadRuleMode::Default => payload.default.push(entry),
+            PayloadRuleMode::Override => payload.override_rules.push(entry),
+        }
+    }
+    payload
+}
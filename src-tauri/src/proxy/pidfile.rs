@@ -0,0 +1,157 @@
+//! Tracking the sidecar ProxyPal started, so that only it is ever killed. Its pid and
+//! executable go to cli-proxy-api.pid in the config dir; a leftover from a crashed
+//! run is only signalled while that pid still runs the same executable, so other
+//! users' proxies and whatever else holds the port are left alone.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::get_proxypal_config_dir;
+
+const PID_FILE: &str = "cli-proxy-api.pid";
+/// Name the sidecar executable starts with, bundled (cli-proxy-api) or in dev builds
+/// (cli-proxy-api-<target triple>)
+const SIDECAR_NAME: &str = "cli-proxy-api";
+/// How long a killed leftover gets to go away
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarPid {
+    pub pid: u32,
+    /// Executable the pid was running when recorded, if it could be looked up
+    pub exe: Option<PathBuf>,
+}
+
+pub fn pid_file_path() -> PathBuf {
+    get_proxypal_config_dir().join(PID_FILE)
+}
+
+fn read_pid_file() -> Option<SidecarPid> {
+    let content = std::fs::read_to_string(pid_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Remember `pid` as the running sidecar
+pub fn record_sidecar(pid: u32) -> Result<(), String> {
+    let entry = SidecarPid {
+        pid,
+        exe: process_exe(pid),
+    };
+    let json = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+    std::fs::write(pid_file_path(), json)
+        .map_err(|e| format!("Failed to write {}: {}", PID_FILE, e))
+}
+
+/// Forget `pid` once it has exited or been stopped. Leaves a newer sidecar's entry alone.
+pub fn forget_sidecar(pid: u32) {
+    if read_pid_file().is_some_and(|entry| entry.pid == pid) {
+        let _ = std::fs::remove_file(pid_file_path());
+    }
+}
+
+/// Whether `entry.pid` is still alive and running the sidecar it was recorded for
+pub fn is_our_sidecar(entry: &SidecarPid) -> bool {
+    let Some(exe) = process_exe(entry.pid) else {
+        return false;
+    };
+    match &entry.exe {
+        Some(recorded) => exe == *recorded,
+        None => file_name(&exe).starts_with(SIDECAR_NAME),
+    }
+}
+
+/// Kill the sidecar a previous run left behind, if the pid file names one that is still
+/// ours. Returns its pid. A pid file pointing anywhere else is dropped.
+pub fn stop_stale_sidecar() -> Option<u32> {
+    let entry = read_pid_file()?;
+    if !is_our_sidecar(&entry) {
+        let _ = std::fs::remove_file(pid_file_path());
+        return None;
+    }
+
+    println!("[ProxyPal] Stopping proxy left over from a previous run (pid {})", entry.pid);
+    kill_process(entry.pid);
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while is_our_sidecar(&entry) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    if is_our_sidecar(&entry) {
+        eprintln!("[ProxyPal] Proxy pid {} is still running after being killed", entry.pid);
+    } else {
+        let _ = std::fs::remove_file(pid_file_path());
+    }
+    Some(entry.pid)
+}
+
+/// stop_stale_sidecar for async callers, waiting for the process to exit off the
+/// runtime's worker threads
+pub async fn stop_stale_sidecar_async() -> Option<u32> {
+    tokio::task::spawn_blocking(stop_stale_sidecar)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("[ProxyPal] Failed to stop the leftover proxy: {}", e);
+            None
+        })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Executable `pid` is running, None if there is no such process or it isn't visible to
+/// us (on Linux, another user's)
+#[cfg(target_os = "linux")]
+fn process_exe(pid: u32) -> Option<PathBuf> {
+    let exe = std::fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+    // Replaced on disk since it started, e.g. by an update
+    let exe = exe.to_string_lossy();
+    Some(PathBuf::from(exe.strip_suffix(" (deleted)").unwrap_or(&exe)))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_exe(pid: u32) -> Option<PathBuf> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let exe = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !exe.is_empty()).then(|| PathBuf::from(exe))
+}
+
+#[cfg(windows)]
+fn process_exe(pid: u32) -> Option<PathBuf> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // Prints `"image.exe","pid",...`, or an INFO line when there is no such process
+    let output = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let image = stdout.trim().strip_prefix('"')?.split('"').next()?;
+    Some(PathBuf::from(image))
+}
+
+#[cfg(unix)]
+fn kill_process(pid: u32) {
+    let _ = std::process::Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .output();
+}
+
+#[cfg(windows)]
+fn kill_process(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let _ = std::process::Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+}
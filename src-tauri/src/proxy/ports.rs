@@ -0,0 +1,197 @@
+//! Moving the proxy or the Copilot bridge off a port some other program holds. The
+//! move goes into the config together with every tunnel that forwarded the old port;
+//! the caller saves it and re-points agents.
+
+use std::time::Duration;
+
+use crate::config::AppConfig;
+use crate::proxy::readiness::wait_for_port_free;
+use crate::types::PortChange;
+
+/// How many ports after the configured one are tried
+const SEARCH_RANGE: u16 = 100;
+/// How long a port gets to be released by a process that is just going away
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortOwner {
+    Proxy,
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+    Copilot,
+}
+
+impl PortOwner {
+    pub fn id(self) -> &'static str {
+        match self {
+            PortOwner::Proxy => "proxy",
+            PortOwner::Copilot => "copilot",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PortOwner::Proxy => "Proxy",
+            PortOwner::Copilot => "Copilot bridge",
+        }
+    }
+
+    pub fn port(self, config: &AppConfig) -> u16 {
+        match self {
+            PortOwner::Proxy => config.port,
+            PortOwner::Copilot => config.copilot.port,
+        }
+    }
+}
+
+pub fn is_port_free(port: u16) -> bool {
+    std::net::TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// First free port after `port` that ProxyPal doesn't already use for something else,
+/// including the local ports enabled tunnels forward to
+pub fn next_free_port(config: &AppConfig, port: u16) -> Option<u16> {
+    let reserved: Vec<u16> = [config.port, config.copilot.port]
+        .into_iter()
+        .chain(config.ssh_configs.iter().filter(|c| c.enabled).map(|c| c.local_port))
+        .chain(config.cloudflare_configs.iter().filter(|c| c.enabled).map(|c| c.local_port))
+        .collect();
+    (port.saturating_add(1)..=port.saturating_add(SEARCH_RANGE))
+        .filter(|p| !reserved.contains(p))
+        .find(|&p| is_port_free(p))
+}
+
+/// Point `owner` at port `to` in `config`, along with the tunnels that forwarded its old port
+pub fn move_port(config: &mut AppConfig, owner: PortOwner, to: u16) -> PortChange {
+    let from = owner.port(config);
+    match owner {
+        PortOwner::Proxy => config.port = to,
+        PortOwner::Copilot => config.copilot.port = to,
+    }
+
+    let mut ssh_tunnels = Vec::new();
+    for ssh in config.ssh_configs.iter_mut().filter(|c| c.local_port == from) {
+        ssh.local_port = to;
+        ssh_tunnels.push(ssh.id.clone());
+    }
+    let mut cloudflare_tunnels = Vec::new();
+    for cf in config.cloudflare_configs.iter_mut().filter(|c| c.local_port == from) {
+        cf.local_port = to;
+        cloudflare_tunnels.push(cf.id.clone());
+    }
+
+    PortChange {
+        service: owner.id().to_string(),
+        from,
+        to,
+        ssh_tunnels,
+        cloudflare_tunnels,
+        reconfigured_agents: Vec::new(),
+        agent_errors: Vec::new(),
+    }
+}
+
+/// Error for a port another program is listening on, suggesting `free` if there is one
+pub fn port_in_use_error(owner: PortOwner, port: u16, free: Option<u16>) -> String {
+    let what = format!("{} port {} is already in use by another program", owner.label(), port);
+    match free {
+        Some(free) => format!(
+            "{}. Quit it, or switch to port {} (free) in Settings or turn on automatic port selection.",
+            what, free
+        ),
+        None => format!("{}. Quit it or choose a different port in Settings.", what),
+    }
+}
+
+/// Make sure `owner` can listen on its port. When another program holds it, moves it
+/// in `config` with auto_select_port on, fails otherwise. The returned change still
+/// has to be saved.
+pub async fn ensure_port_free(
+    config: &mut AppConfig,
+    owner: PortOwner,
+) -> Result<Option<PortChange>, String> {
+    let port = owner.port(config);
+    if wait_for_port_free(port, RELEASE_TIMEOUT).await {
+        return Ok(None);
+    }
+    match next_free_port(config, port) {
+        Some(to) if config.auto_select_port => {
+            let change = move_port(config, owner, to);
+            println!(
+                "[ProxyPal] Port {} is in use by another program, {} moves to port {}",
+                port,
+                owner.label(),
+                to
+            );
+            Ok(Some(change))
+        }
+        free => Err(port_in_use_error(owner, port, free)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CloudflareConfig, SshConfig};
+
+    fn ssh(id: &str, local_port: u16, enabled: bool) -> SshConfig {
+        SshConfig {
+            id: id.to_string(),
+            host: "example.com".to_string(),
+            port: 22,
+            username: "me".to_string(),
+            key_file: None,
+            remote_port: 8317,
+            local_port,
+            enabled,
+        }
+    }
+
+    fn cloudflare(id: &str, local_port: u16, enabled: bool) -> CloudflareConfig {
+        CloudflareConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            tunnel_token: String::new(),
+            local_port,
+            enabled,
+        }
+    }
+
+    #[test]
+    fn next_free_port_skips_ports_enabled_tunnels_forward_to() {
+        let port = 41700;
+        let mut config = AppConfig {
+            port,
+            ssh_configs: vec![ssh("a", port + 2, true)],
+            cloudflare_configs: vec![cloudflare("b", port + 3, true)],
+            ..Default::default()
+        };
+        config.copilot.port = port + 1;
+        let free = next_free_port(&config, port).unwrap();
+        assert!(free > port + 3, "{} is taken", free);
+
+        config.ssh_configs[0].enabled = false;
+        config.cloudflare_configs[0].enabled = false;
+        let free = next_free_port(&config, port).unwrap();
+        assert!(free == port + 2 || !is_port_free(port + 2));
+    }
+
+    #[test]
+    fn move_port_takes_the_tunnels_of_the_old_port_along() {
+        let mut config = AppConfig {
+            port: 8317,
+            ssh_configs: vec![ssh("follows", 8317, true), ssh("elsewhere", 9000, true)],
+            cloudflare_configs: vec![cloudflare("cf", 8317, false)],
+            ..Default::default()
+        };
+
+        let change = move_port(&mut config, PortOwner::Proxy, 8320);
+
+        assert_eq!((change.from, change.to), (8317, 8320));
+        assert_eq!(config.port, 8320);
+        assert_eq!(change.ssh_tunnels, vec!["follows"]);
+        assert_eq!(change.cloudflare_tunnels, vec!["cf"]);
+        assert_eq!(config.ssh_configs[0].local_port, 8320);
+        assert_eq!(config.ssh_configs[1].local_port, 9000);
+        assert_eq!(config.cloudflare_configs[0].local_port, 8320);
+    }
+}
@@ -0,0 +1,125 @@
+//! Waiting for a freshly spawned sidecar to serve requests, instead of sleeping and
+//! hoping: it counts as ready once its port accepts connections and the Management
+//! API answers with our key. Then the settings it may not have picked up from its config
+//! file are pushed through the Management API, by the app and the daemon alike.
+
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+use crate::types::ManagementSettingError;
+
+/// How often the port and Management API are polled while starting
+const POLL_INTERVAL: Duration = Duration::from_millis(150);
+/// Management endpoint asked during startup, cheap and always present
+const PROBE_ENDPOINT: &str = "usage-statistics-enabled";
+
+/// Poll until the sidecar on `port` is ready and return the version it reports, if
+/// any. `exited` is checked on every round and returns why the process is gone, which
+/// ends the wait early.
+pub async fn wait_until_ready(
+    port: u16,
+    management_key: &str,
+    timeout: Duration,
+    mut exited: impl FnMut() -> Option<String>,
+) -> Result<Option<String>, String> {
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let url = format!("http://127.0.0.1:{}/v0/management/{}", port, PROBE_ENDPOINT);
+    let deadline = Instant::now() + timeout;
+    let mut last_problem = format!("nothing is listening on port {}", port);
+
+    loop {
+        if let Some(reason) = exited() {
+            return Err(format!("The proxy {} while starting", reason));
+        }
+        match client
+            .get(&url)
+            .header("X-Management-Key", management_key)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => {
+                let version = response
+                    .headers()
+                    .get(crate::proxy::version::VERSION_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                return Ok(version);
+            }
+            Ok(response) => {
+                last_problem = format!("the Management API answered {}", response.status());
+            }
+            Err(e) if e.is_connect() => {
+                last_problem = format!("nothing is listening on port {}", port);
+            }
+            Err(e) => last_problem = format!("the Management API didn't answer: {}", e),
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "The proxy wasn't ready after {}s: {}",
+                timeout.as_secs(),
+                last_problem
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Wait up to `timeout` for `port` to be free to bind; false if it's still taken
+pub async fn wait_for_port_free(port: u16, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if std::net::TcpListener::bind(("0.0.0.0", port)).is_ok() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Settings pushed through the Management API once the sidecar is ready:
+/// (setting, endpoint under /v0/management/, value)
+fn management_settings(config: &AppConfig) -> [(&'static str, &'static str, serde_json::Value); 3] {
+    [
+        ("usage statistics", "usage-statistics-enabled", serde_json::json!(config.usage_stats_enabled)),
+        ("force model mappings", "ampcode/force-model-mappings", serde_json::json!(config.force_model_mappings)),
+        ("max retry interval", "max-retry-interval", serde_json::json!(config.max_retry_interval)),
+    ]
+}
+
+/// Push management_settings to the ready sidecar, returning the ones it refused
+pub async fn apply_management_settings(config: &AppConfig) -> Vec<ManagementSettingError> {
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let mut failures = Vec::new();
+    for (setting, endpoint, value) in management_settings(config) {
+        let result = client
+            .put(format!("http://127.0.0.1:{}/v0/management/{}", config.port, endpoint))
+            .header("X-Management-Key", &config.management_key)
+            .json(&serde_json::json!({ "value": value }))
+            .send()
+            .await;
+        let error = match result {
+            Ok(response) if response.status().is_success() => continue,
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                format!("{} {}", status, body.trim())
+            }
+            Err(e) => e.to_string(),
+        };
+        failures.push(ManagementSettingError {
+            setting: setting.to_string(),
+            error,
+        });
+    }
+    failures
+}
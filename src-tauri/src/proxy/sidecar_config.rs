@@ -0,0 +1,785 @@
+//! Typed model of CLIProxyAPI's proxy-config.yaml.
+//!
+//! Every value we emit goes through serde_yaml, so API keys, provider names or
+//! URLs containing quotes, backslashes or newlines can't break out of their field.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use super::copilot_models;
+use crate::config::AppConfig;
+use crate::types::ModelMapping;
+
+// GPT-5 base models that support reasoning level suffixes (single source of truth)
+// Used by both backend (proxy config generation) and frontend (Settings UI)
+pub const GPT5_BASE_MODELS: &[&str] = &[
+    "gpt-5",
+    "gpt-5-mini",
+    "gpt-5-codex",
+    "gpt-5-codex-mini",
+    "gpt-5.1",
+    "gpt-5.1-codex",
+    "gpt-5.1-codex-mini",
+    "gpt-5.1-codex-max",
+    "gpt-5.2",
+    "gpt-5.2-codex",
+    "gpt-5.3-codex",
+];
+
+// GPT-5 reasoning level suffixes
+pub const GPT5_REASONING_SUFFIXES: &[&str] = &["minimal", "low", "medium", "high", "xhigh"];
+
+pub(crate) const GENERATED_HEADER: &str = "# ProxyPal generated config - regenerated every time the proxy starts.\n\
+# Put your own settings in proxy-config-custom.yaml instead of editing this file;\n\
+# it is deep-merged into this document (see proxy/custom_config.rs for the rules).\n";
+
+/// Root of proxy-config.yaml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct SidecarConfig {
+    pub port: u16,
+    pub auth_dir: String,
+    pub api_keys: Vec<String>,
+    pub debug: bool,
+    pub usage_statistics_enabled: bool,
+    pub logging_to_file: bool,
+    pub logs_max_total_size_mb: u32,
+    pub request_retry: u16,
+    pub max_retry_interval: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    pub quota_exceeded: QuotaExceeded,
+    pub remote_management: RemoteManagement,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub openai_compatibility: Vec<OpenAICompatibilityEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub claude_api_key: Vec<ClaudeKeyEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gemini_api_key: Vec<GeminiKeyEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codex_api_key: Vec<CodexKeyEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vertex_api_key: Vec<VertexKeyEntry>,
+    pub routing: Routing,
+    pub payload: Payload,
+    pub ampcode: Ampcode,
+    pub request_log: bool,
+    pub commercial_mode: bool,
+    pub ws_auth: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct QuotaExceeded {
+    pub switch_project: bool,
+    pub switch_preview_model: bool,
+}
+
+/// Management API settings (needed for OAuth flows and runtime setters)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteManagement {
+    pub allow_remote: bool,
+    pub secret_key: String,
+    pub disable_control_panel: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct OpenAICompatibilityEntry {
+    pub name: String,
+    pub base_url: String,
+    pub schema_cleaner: bool,
+    pub api_key_entries: Vec<ApiKeyEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<ModelAlias>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ApiKeyEntry {
+    pub api_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ModelAlias {
+    pub alias: String,
+    pub name: String,
+}
+
+/// Model entry of a provider API key (`name` is the upstream model, `alias` what clients request)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct KeyModelEntry {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ClaudeKeyEntry {
+    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<KeyModelEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct GeminiKeyEntry {
+    pub api_key: String,
+    pub signature_cache: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct CodexKeyEntry {
+    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct VertexKeyEntry {
+    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// Routing strategy for multiple API keys
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct Routing {
+    #[schemars(schema_with = "crate::config::schema::routing_strategy")]
+    pub strategy: String,
+}
+
+/// Payload injection rules (thinking budgets etc.)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct Payload {
+    #[serde(default)]
+    pub default: Vec<PayloadRuleEntry>,
+    #[serde(default, rename = "override", skip_serializing_if = "Vec::is_empty")]
+    pub override_rules: Vec<PayloadRuleEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct PayloadRuleEntry {
+    pub models: Vec<PayloadModel>,
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct PayloadModel {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+}
+
+/// Amp CLI integration - enables amp login and management routes
+/// See: https://help.router-for.me/agent-client/amp-cli.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct Ampcode {
+    pub upstream_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub model_mappings: Vec<AmpModelMappingEntry>,
+    pub restrict_management_to_localhost: bool,
+    pub force_model_mappings: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct AmpModelMappingEntry {
+    pub from: String,
+    pub to: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fork: bool,
+}
+
+/// Thinking budget in tokens for the configured mode
+pub fn thinking_budget_tokens(config: &AppConfig) -> u32 {
+    let custom = if config.thinking_budget_custom == 0 {
+        16000
+    } else {
+        config.thinking_budget_custom
+    };
+    match config.thinking_budget_mode.as_str() {
+        "low" => 2048,
+        "medium" | "" => 8192,
+        "high" => 32768,
+        "custom" => custom,
+        _ => 8192,
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().filter(|v| !v.is_empty()).cloned()
+}
+
+fn headers(value: &Option<HashMap<String, String>>) -> BTreeMap<String, String> {
+    value
+        .iter()
+        .flatten()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+fn excluded_models(value: &Option<Vec<String>>) -> Vec<String> {
+    value
+        .iter()
+        .flatten()
+        .filter(|m| !m.is_empty())
+        .cloned()
+        .collect()
+}
+
+fn key_models(value: &Option<Vec<ModelMapping>>) -> Vec<KeyModelEntry> {
+    value
+        .iter()
+        .flatten()
+        .map(|m| KeyModelEntry {
+            name: m.name.clone(),
+            alias: non_empty(&m.alias),
+        })
+        .collect()
+}
+
+/// Build the sidecar config from the app config.
+/// `proxy_url` is the already-resolved upstream proxy (system proxy and credentials applied).
+pub fn build_sidecar_config(config: &AppConfig, proxy_url: Option<String>) -> SidecarConfig {
+    // Custom OpenAI-compatible providers (multiple providers support), then copilot
+    let mut openai_compatibility: Vec<OpenAICompatibilityEntry> = config
+        .amp_openai_providers
+        .iter()
+        .filter(|p| !p.name.is_empty() && !p.base_url.is_empty() && !p.api_key.is_empty())
+        .map(|provider| OpenAICompatibilityEntry {
+            name: provider.name.clone(),
+            base_url: provider.base_url.clone(),
+            schema_cleaner: true,
+            api_key_entries: vec![ApiKeyEntry {
+                api_key: provider.api_key.clone(),
+            }],
+            models: provider
+                .models
+                .iter()
+                .map(|m| ModelAlias {
+                    alias: m.alias.clone(),
+                    name: m.name.clone(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    // GitHub Copilot GPT/OpenAI models (via copilot-api)
+    if config.copilot.enabled {
+        openai_compatibility.push(OpenAICompatibilityEntry {
+            name: "copilot".to_string(),
+            base_url: format!("http://localhost:{}/v1", config.copilot.port),
+            schema_cleaner: true,
+            api_key_entries: vec![ApiKeyEntry {
+                api_key: "dummy".to_string(),
+            }],
+            models: copilot_models::entry_models(&config.copilot, &copilot_models::current_models().models),
+        });
+    }
+
+    let claude_api_key = config
+        .claude_api_keys
+        .iter()
+        .map(|key| ClaudeKeyEntry {
+            api_key: key.api_key.clone(),
+            prefix: non_empty(&key.prefix),
+            base_url: key.base_url.clone(),
+            proxy_url: non_empty(&key.proxy_url),
+            headers: headers(&key.headers),
+            models: key_models(&key.models),
+            excluded_models: excluded_models(&key.excluded_models),
+        })
+        .collect();
+
+    let gemini_api_key = config
+        .gemini_api_keys
+        .iter()
+        .map(|key| GeminiKeyEntry {
+            api_key: key.api_key.clone(),
+            signature_cache: false,
+            prefix: non_empty(&key.prefix),
+            base_url: key.base_url.clone(),
+            proxy_url: non_empty(&key.proxy_url),
+            headers: headers(&key.headers),
+            excluded_models: excluded_models(&key.excluded_models),
+        })
+        .collect();
+
+    let codex_api_key = config
+        .codex_api_keys
+        .iter()
+        .map(|key| CodexKeyEntry {
+            api_key: key.api_key.clone(),
+            prefix: non_empty(&key.prefix),
+            base_url: key.base_url.clone(),
+            proxy_url: non_empty(&key.proxy_url),
+            headers: headers(&key.headers),
+        })
+        .collect();
+
+    let vertex_api_key = config
+        .vertex_api_keys
+        .iter()
+        .map(|key| VertexKeyEntry {
+            api_key: key.api_key.clone(),
+            prefix: non_empty(&key.prefix),
+            base_url: non_empty(&key.base_url),
+            project_id: non_empty(&key.project_id),
+            location: non_empty(&key.location),
+        })
+        .collect();
+
+    // Model mappings route Amp model requests to other models available in the proxy
+    // (e.g., name: claude-opus-4-6 -> alias: copilot-gpt-5-mini). Only enabled ones are emitted.
+    let model_mappings = config
+        .amp_model_mappings
+        .iter()
+        .filter(|m| m.enabled)
+        .map(|m| AmpModelMappingEntry {
+            from: m.name.clone(),
+            to: m.alias.clone(),
+            fork: m.fork,
+        })
+        .collect();
+
+    SidecarConfig {
+        port: config.port,
+        auth_dir: "~/.cli-proxy-api".to_string(),
+        api_keys: vec![config.proxy_api_key.clone()],
+        debug: config.debug,
+        usage_statistics_enabled: config.usage_stats_enabled,
+        logging_to_file: config.logging_to_file,
+        logs_max_total_size_mb: config.logs_max_total_size_mb,
+        request_retry: config.request_retry,
+        max_retry_interval: config.max_retry_interval,
+        proxy_url: proxy_url.filter(|u| !u.is_empty()),
+        quota_exceeded: QuotaExceeded {
+            switch_project: config.quota_switch_project,
+            switch_preview_model: config.quota_switch_preview_model,
+        },
+        remote_management: RemoteManagement {
+            allow_remote: true,
+            secret_key: config.management_key.clone(),
+            disable_control_panel: config.disable_control_panel,
+        },
+        openai_compatibility,
+        claude_api_key,
+        gemini_api_key,
+        codex_api_key,
+        vertex_api_key,
+        routing: Routing {
+            strategy: config.routing_strategy.clone(),
+        },
+        payload: super::payload_rules::build_payload(config),
+        ampcode: Ampcode {
+            upstream_url: "https://ampcode.com".to_string(),
+            upstream_api_key: Some(config.amp_api_key.clone()).filter(|k| !k.is_empty()),
+            model_mappings,
+            restrict_management_to_localhost: false,
+            force_model_mappings: config.force_model_mappings,
+        },
+        request_log: config.request_logging,
+        commercial_mode: config.commercial_mode,
+        ws_auth: config.ws_auth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::custom_config::{merge_custom_config, render_effective_config};
+    use crate::types::{
+        AmpModelMapping, AmpOpenAIModel, AmpOpenAIProvider, ClaudeApiKey, CodexApiKey,
+        GeminiApiKey, VertexApiKey,
+    };
+
+    const HOSTILE: &[&str] = &[
+        "plain",
+        "with \"double\" quotes",
+        "with 'single' quotes",
+        "back\\slash\\",
+        "new\nline",
+        "trailing newline\n",
+        "tab\there",
+        "\"\nport: 1\ninjected: \"",
+        "key: value",
+        "# not a comment",
+        "- not a list",
+        "{ not: a map }",
+        "&anchor *alias !tag",
+        "null",
+        "true",
+        "8317",
+        "  leading and trailing spaces  ",
+        "unicode ✓ ünïcødé",
+        "",
+    ];
+
+    fn render(sidecar: &SidecarConfig) -> String {
+        render_effective_config(&merge_custom_config(sidecar, None).unwrap()).unwrap()
+    }
+
+    fn round_trip(sidecar: &SidecarConfig) -> SidecarConfig {
+        let yaml = render(sidecar);
+        serde_yaml::from_str(&yaml).unwrap_or_else(|e| panic!("invalid YAML ({}):\n{}", e, yaml))
+    }
+
+    #[test]
+    fn default_config_round_trips() {
+        let sidecar = build_sidecar_config(&AppConfig::default(), None);
+        assert_eq!(round_trip(&sidecar), sidecar);
+    }
+
+    #[test]
+    fn hostile_strings_survive_round_trip() {
+        for hostile in HOSTILE {
+            let config = AppConfig {
+                proxy_api_key: hostile.to_string(),
+                management_key: hostile.to_string(),
+                amp_api_key: format!("amp-{}", hostile),
+                routing_strategy: hostile.to_string(),
+                amp_model_mappings: vec![AmpModelMapping {
+                    name: hostile.to_string(),
+                    alias: hostile.to_string(),
+                    enabled: true,
+                    fork: true,
+                }],
+                amp_openai_providers: vec![AmpOpenAIProvider {
+                    id: "id".to_string(),
+                    name: format!("name {}", hostile),
+                    base_url: format!("https://example.com/{}", hostile),
+                    api_key: format!("sk-{}", hostile),
+                    models: vec![AmpOpenAIModel {
+                        name: hostile.to_string(),
+                        alias: hostile.to_string(),
+                    }],
+                }],
+                claude_api_keys: vec![ClaudeApiKey {
+                    api_key: format!("sk-ant-{}", hostile),
+                    base_url: Some(hostile.to_string()),
+                    proxy_url: Some(format!("http://{}", hostile)),
+                    headers: None,
+                    models: None,
+                    excluded_models: None,
+                    prefix: None,
+                }],
+                ..AppConfig::default()
+            };
+
+            let sidecar = build_sidecar_config(&config, Some(format!("http://proxy/{}", hostile)));
+            let parsed = round_trip(&sidecar);
+            assert_eq!(parsed, sidecar, "round trip failed for {:?}", hostile);
+            assert_eq!(parsed.api_keys, vec![hostile.to_string()]);
+            assert_eq!(parsed.port, config.port);
+            assert_eq!(parsed.remote_management.secret_key, *hostile);
+        }
+    }
+
+    #[test]
+    fn injected_keys_do_not_appear_at_top_level() {
+        let config = AppConfig {
+            proxy_api_key: "x\"\nport: 1\nauth-dir: /tmp/evil\n".to_string(),
+            ..AppConfig::default()
+        };
+        let yaml = render(&build_sidecar_config(&config, None));
+        let value: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(value["port"], serde_yaml::Value::from(config.port));
+        assert_eq!(value["auth-dir"], serde_yaml::Value::from("~/.cli-proxy-api"));
+    }
+
+    fn sample_headers() -> Option<HashMap<String, String>> {
+        Some(HashMap::from([
+            ("X-Team".to_string(), "platform \"core\"".to_string()),
+            ("anthropic-beta".to_string(), "a,b\nc".to_string()),
+        ]))
+    }
+
+    fn yaml_value(config: &AppConfig) -> serde_yaml::Value {
+        let yaml = render(&build_sidecar_config(config, None));
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn claude_key_emits_every_field() {
+        let config = AppConfig {
+            claude_api_keys: vec![ClaudeApiKey {
+                api_key: "sk-ant-1".to_string(),
+                base_url: Some("https://api.anthropic.com".to_string()),
+                proxy_url: Some("socks5://127.0.0.1:1080".to_string()),
+                headers: sample_headers(),
+                models: Some(vec![
+                    ModelMapping {
+                        name: "claude-sonnet-4-5-20250929".to_string(),
+                        alias: Some("sonnet".to_string()),
+                    },
+                    ModelMapping {
+                        name: "claude-haiku-4-5".to_string(),
+                        alias: None,
+                    },
+                ]),
+                excluded_models: Some(vec!["claude-3-*".to_string()]),
+                prefix: Some("team".to_string()),
+            }],
+            ..AppConfig::default()
+        };
+
+        let sidecar = build_sidecar_config(&config, None);
+        assert_eq!(round_trip(&sidecar), sidecar);
+
+        let value = yaml_value(&config);
+        let entry = &value["claude-api-key"][0];
+        assert_eq!(entry["api-key"], "sk-ant-1");
+        assert_eq!(entry["prefix"], "team");
+        assert_eq!(entry["base-url"], "https://api.anthropic.com");
+        assert_eq!(entry["proxy-url"], "socks5://127.0.0.1:1080");
+        assert_eq!(entry["headers"]["X-Team"], "platform \"core\"");
+        assert_eq!(entry["headers"]["anthropic-beta"], "a,b\nc");
+        assert_eq!(entry["models"][0]["name"], "claude-sonnet-4-5-20250929");
+        assert_eq!(entry["models"][0]["alias"], "sonnet");
+        assert!(entry["models"][1].get("alias").is_none());
+        assert_eq!(entry["excluded-models"][0], "claude-3-*");
+    }
+
+    #[test]
+    fn gemini_key_emits_every_field() {
+        let config = AppConfig {
+            gemini_api_keys: vec![GeminiApiKey {
+                api_key: "AIza-1".to_string(),
+                base_url: Some("https://generativelanguage.googleapis.com".to_string()),
+                proxy_url: Some(String::new()),
+                headers: sample_headers(),
+                excluded_models: Some(vec!["gemini-1.5-*".to_string(), String::new()]),
+                prefix: Some("g".to_string()),
+            }],
+            ..AppConfig::default()
+        };
+
+        let sidecar = build_sidecar_config(&config, None);
+        assert_eq!(round_trip(&sidecar), sidecar);
+
+        let value = yaml_value(&config);
+        let entry = &value["gemini-api-key"][0];
+        assert_eq!(entry["api-key"], "AIza-1");
+        assert_eq!(entry["signature-cache"], false);
+        assert_eq!(entry["prefix"], "g");
+        assert!(entry.get("proxy-url").is_none());
+        assert_eq!(entry["headers"]["X-Team"], "platform \"core\"");
+        assert_eq!(
+            entry["excluded-models"],
+            serde_yaml::to_value(vec!["gemini-1.5-*"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn codex_key_emits_every_field() {
+        let config = AppConfig {
+            codex_api_keys: vec![CodexApiKey {
+                api_key: "sk-codex".to_string(),
+                base_url: Some("https://chatgpt.com/backend-api/codex".to_string()),
+                proxy_url: Some("http://proxy:3128".to_string()),
+                headers: sample_headers(),
+                prefix: Some("work".to_string()),
+            }],
+            ..AppConfig::default()
+        };
+
+        let sidecar = build_sidecar_config(&config, None);
+        assert_eq!(round_trip(&sidecar), sidecar);
+
+        let value = yaml_value(&config);
+        let entry = &value["codex-api-key"][0];
+        assert_eq!(entry["api-key"], "sk-codex");
+        assert_eq!(entry["prefix"], "work");
+        assert_eq!(entry["base-url"], "https://chatgpt.com/backend-api/codex");
+        assert_eq!(entry["proxy-url"], "http://proxy:3128");
+        assert_eq!(entry["headers"]["anthropic-beta"], "a,b\nc");
+    }
+
+    #[test]
+    fn vertex_key_emits_every_field() {
+        let config = AppConfig {
+            vertex_api_keys: vec![
+                VertexApiKey {
+                    api_key: "vertex-key".to_string(),
+                    project_id: Some("my-project".to_string()),
+                    location: Some("us-central1".to_string()),
+                    base_url: Some("https://aiplatform.googleapis.com".to_string()),
+                    prefix: Some("vx".to_string()),
+                },
+                VertexApiKey {
+                    api_key: "bare".to_string(),
+                    project_id: None,
+                    location: None,
+                    base_url: None,
+                    prefix: None,
+                },
+            ],
+            ..AppConfig::default()
+        };
+
+        let sidecar = build_sidecar_config(&config, None);
+        assert_eq!(round_trip(&sidecar), sidecar);
+
+        let value = yaml_value(&config);
+        let entry = &value["vertex-api-key"][0];
+        assert_eq!(entry["api-key"], "vertex-key");
+        assert_eq!(entry["project-id"], "my-project");
+        assert_eq!(entry["location"], "us-central1");
+        assert_eq!(entry["base-url"], "https://aiplatform.googleapis.com");
+        assert_eq!(entry["prefix"], "vx");
+        let bare = value["vertex-api-key"][1].as_mapping().unwrap();
+        assert_eq!(bare.len(), 1);
+    }
+
+    #[test]
+    fn empty_key_lists_are_omitted() {
+        let value = yaml_value(&AppConfig::default());
+        for section in ["claude-api-key", "gemini-api-key", "codex-api-key", "vertex-api-key"] {
+            assert!(value.get(section).is_none(), "{} should be omitted", section);
+        }
+    }
+
+    #[test]
+    fn copilot_entry_lists_reasoning_variants() {
+        let mut config = AppConfig::default();
+        config.copilot.enabled = true;
+
+        let sidecar = build_sidecar_config(&config, None);
+        let copilot = sidecar
+            .openai_compatibility
+            .iter()
+            .find(|e| e.name == "copilot")
+            .expect("copilot entry");
+        assert_eq!(copilot.base_url, "http://localhost:4141/v1");
+        assert!(copilot.models.iter().any(|m| m.name == "gpt-5(high)"));
+        assert!(copilot.models.iter().any(|m| m.name == "claude-opus-4.6"));
+    }
+
+    #[test]
+    fn copilot_entry_hides_and_aliases_models() {
+        let mut copilot = crate::types::CopilotConfig {
+            hidden_models: vec!["gpt-4*".to_string(), "gpt-5(minimal)".to_string()],
+            ..Default::default()
+        };
+        copilot.extra_aliases.push(crate::types::CopilotModelAlias {
+            name: "claude-sonnet-4.5".to_string(),
+            alias: "sonnet".to_string(),
+        });
+        let models: Vec<String> = ["gpt-4.1", "gpt-5", "claude-sonnet-4.5"].iter().map(|m| m.to_string()).collect();
+
+        let entries = copilot_models::entry_models(&copilot, &models);
+        let aliases: Vec<&str> = entries.iter().map(|m| m.alias.as_str()).collect();
+        assert!(!aliases.contains(&"gpt-4.1"));
+        assert!(aliases.contains(&"gpt-5(high)"));
+        assert!(!aliases.contains(&"gpt-5(minimal)"));
+        assert!(!aliases.iter().any(|a| a.starts_with("claude-sonnet-4.5(")));
+        assert_eq!(entries.last().map(|m| m.name.as_str()), Some("claude-sonnet-4.5"));
+        assert_eq!(aliases.last(), Some(&"sonnet"));
+    }
+
+    #[test]
+    fn payload_rules_fill_placeholders() {
+        let mut config = AppConfig {
+            thinking_budget_mode: "low".to_string(),
+            ..AppConfig::default()
+        };
+
+        let value = yaml_value(&config);
+        let default = &value["payload"]["default"];
+        assert_eq!(default[0]["models"][0]["name"], "claude-sonnet-4-5");
+        assert_eq!(default[0]["models"][0]["protocol"], "claude");
+        assert_eq!(default[1]["models"].as_sequence().unwrap().len(), 8);
+        assert_eq!(default[1]["params"]["thinking.budget_tokens"], 2048);
+        let overrides = &value["payload"]["override"];
+        assert_eq!(overrides[0]["models"][0]["name"], "gemini-3-pro-preview*");
+        assert_eq!(
+            overrides[1]["params"]["generationConfig.thinkingConfig.thinkingLevel"],
+            "low"
+        );
+
+        // Gemini thinking-level rules are skipped when injection is off; others stay
+        config.gemini_thinking_injection = false;
+        config.payload_rules.push(crate::types::PayloadRule {
+            id: "temperature-cap".to_string(),
+            description: String::new(),
+            enabled: true,
+            models: vec!["gpt-5*".to_string()],
+            protocol: None,
+            mode: crate::types::PayloadRuleMode::Override,
+            params: BTreeMap::from([("temperature".to_string(), serde_json::json!(0.2))]),
+        });
+        let value = yaml_value(&config);
+        let overrides = value["payload"]["override"].as_sequence().unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0]["params"]["temperature"], 0.2);
+    }
+
+    #[test]
+    fn generated_config_matches_schema() {
+        let mut config = AppConfig::default();
+        config.copilot.enabled = true;
+        config.claude_api_keys.push(ClaudeApiKey {
+            api_key: "sk-ant".to_string(),
+            base_url: Some("https://api.anthropic.com".to_string()),
+            proxy_url: None,
+            headers: None,
+            models: None,
+            excluded_models: None,
+            prefix: None,
+        });
+        let schema = crate::config::schema::validate_proxy_config_yaml;
+
+        assert_eq!(schema(&render(&build_sidecar_config(&config, None))), Ok(()));
+        assert_eq!(schema("routing:\n  strategy: fill-first\nsome-new-key: 1\n"), Ok(()));
+        let error = schema("port: \"8317\"\nrouting:\n  strategy: random\n").unwrap_err();
+        assert!(error.contains("/port"), "{}", error);
+        assert!(error.contains("/routing/strategy"), "{}", error);
+    }
+}
@@ -0,0 +1,470 @@
+//! CLIProxyAPI binaries installed next to the bundled one, so another release can be
+//! tried without rebuilding ProxyPal. Each version lives in sidecars/<version>/ in the
+//! config dir; sidecars.json lists them with their SHA-256 and says which one
+//! start_proxy launches. The selection before the last switch is kept, and switched
+//! back to when the new version doesn't become ready.
+
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::get_proxypal_config_dir;
+use crate::proxy::version::SidecarVersion;
+use crate::types::{InstalledSidecar, SidecarInventory, SidecarRollback};
+
+const INVENTORY_FILE: &str = "sidecars.json";
+const BINARY_NAME: &str = "cli-proxy-api";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+/// ELF, Mach-O (64/32 bit, universal), PE, and wrapper scripts
+const EXECUTABLE_MAGICS: [&[u8]; 6] = [
+    b"\x7fELF",
+    &[0xcf, 0xfa, 0xed, 0xfe],
+    &[0xce, 0xfa, 0xed, 0xfe],
+    &[0xca, 0xfe, 0xba, 0xbe],
+    b"MZ",
+    b"#!",
+];
+
+/// Serializes read-modify-write of sidecars.json
+static INVENTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// An installed version that checked out and can be launched
+pub struct SelectedSidecar {
+    pub version: String,
+    pub path: PathBuf,
+}
+
+pub fn sidecars_dir() -> PathBuf {
+    get_proxypal_config_dir().join("sidecars")
+}
+
+fn binary_path(version: &str) -> PathBuf {
+    sidecars_dir()
+        .join(version)
+        .join(format!("{}{}", BINARY_NAME, std::env::consts::EXE_SUFFIX))
+}
+
+pub fn load_inventory() -> SidecarInventory {
+    std::fs::read_to_string(sidecars_dir().join(INVENTORY_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Load sidecars.json, apply `change` and save it, with other changes held off
+fn update_inventory<T>(
+    change: impl FnOnce(&mut SidecarInventory) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = INVENTORY_LOCK.lock().unwrap();
+    let mut inventory = load_inventory();
+    let result = change(&mut inventory)?;
+    std::fs::create_dir_all(sidecars_dir())
+        .map_err(|e| format!("Failed to create sidecars directory: {}", e))?;
+    let json = serde_json::to_string_pretty(&inventory).map_err(|e| e.to_string())?;
+    std::fs::write(sidecars_dir().join(INVENTORY_FILE), json)
+        .map_err(|e| format!("Failed to save {}: {}", INVENTORY_FILE, e))?;
+    Ok(result)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Last path segment of a file path or URL, without a query
+fn source_name(source: &str) -> &str {
+    let path = source.split(['?', '#']).next().unwrap_or(source);
+    path.trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(path)
+}
+
+fn is_url(source: &str) -> bool {
+    source.contains("://")
+}
+
+/// Download an https URL, read anything else as a file
+async fn read_source(source: &str) -> Result<Vec<u8>, String> {
+    if !is_url(source) {
+        return std::fs::read(source).map_err(|e| format!("Failed to read {}: {}", source, e));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get(source)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", source, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download {}: HTTP {}", source, response.status()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", source, e))?;
+    Ok(bytes.to_vec())
+}
+
+/// Put `data` in place as the binary of `version`, replacing an earlier install of it
+fn write_binary(version: &str, data: &[u8]) -> Result<(), String> {
+    let path = binary_path(version);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    // Written aside and renamed, so a running copy of the same version isn't cut short
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, data).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {} executable: {}", partial.display(), e))?;
+    }
+    std::fs::rename(&partial, &path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial);
+        format!("Failed to install CLIProxyAPI {}: {}", version, e)
+    })
+}
+
+/// Install the binary at `source`, a file path or https URL, as `version` or else the
+/// version its file name carries. Downloads have to match `expected_sha256`; local files
+/// only when it is given. Doesn't change which version is selected.
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub async fn install(
+    source: &str,
+    expected_sha256: Option<&str>,
+    version: Option<&str>,
+) -> Result<InstalledSidecar, String> {
+    let source = source.trim();
+    let name = source_name(source);
+    let version = match version.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(version) => SidecarVersion::parse(version)
+            .ok_or_else(|| format!("'{}' isn't a version like 6.7.18", version))?,
+        None => SidecarVersion::parse(name).ok_or_else(|| {
+            format!("Couldn't tell the version from '{}', enter it along with the source", name)
+        })?,
+    }
+    .to_string();
+
+    let expected_sha256 = expected_sha256.map(str::trim).filter(|s| !s.is_empty());
+    if let Some(expected) = expected_sha256 {
+        if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("'{}' isn't a SHA-256 (64 hex digits)", expected));
+        }
+    }
+    if is_url(source) {
+        if !source.to_ascii_lowercase().starts_with("https://") {
+            return Err(format!("Only https:// downloads can be installed, not {}", source));
+        }
+        if expected_sha256.is_none() {
+            return Err(
+                "Downloads need the SHA-256 of the binary to be checked against, enter it along with the URL"
+                    .to_string(),
+            );
+        }
+    }
+
+    let data = read_source(source).await?;
+    let sha256 = sha256_hex(&data);
+    if let Some(expected) = expected_sha256 {
+        if !expected.eq_ignore_ascii_case(&sha256) {
+            return Err(format!(
+                "SHA-256 of {} is {}, expected {}. Nothing was installed.",
+                name,
+                sha256,
+                expected.to_lowercase()
+            ));
+        }
+    }
+    if !EXECUTABLE_MAGICS.iter().any(|magic| data.starts_with(magic)) {
+        return Err(format!(
+            "{} isn't an executable. Release archives have to be unpacked first.",
+            name
+        ));
+    }
+
+    update_inventory(|inventory| {
+        write_binary(&version, &data)?;
+        let entry = InstalledSidecar {
+            version: version.clone(),
+            sha256,
+            source: source.to_string(),
+            size: data.len() as u64,
+            installed_at: chrono::Utc::now().timestamp_millis() as u64,
+        };
+        inventory.installed.retain(|s| s.version != version);
+        inventory.installed.push(entry.clone());
+        // Newest first
+        inventory
+            .installed
+            .sort_by_key(|s| std::cmp::Reverse(SidecarVersion::parse(&s.version)));
+        println!("[ProxyPal] Installed CLIProxyAPI {} from {}", version, source);
+        Ok(entry)
+    })
+}
+
+/// Launch `version` from the next start on, or the bundled binary for None
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn select(version: Option<&str>) -> Result<SidecarInventory, String> {
+    update_inventory(|inventory| {
+        if let Some(version) = version {
+            if !inventory.installed.iter().any(|s| s.version == version) {
+                return Err(format!("CLIProxyAPI {} isn't installed", version));
+            }
+        }
+        if inventory.selected.as_deref() != version {
+            inventory.previous =
+                std::mem::replace(&mut inventory.selected, version.map(str::to_string));
+        }
+        Ok(inventory.clone())
+    })
+}
+
+/// Delete an installed version that isn't selected
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn remove(version: &str) -> Result<SidecarInventory, String> {
+    update_inventory(|inventory| {
+        if inventory.selected.as_deref() == Some(version) {
+            return Err(format!(
+                "CLIProxyAPI {} is selected, select another version before removing it",
+                version
+            ));
+        }
+        // Only names from the inventory reach the filesystem
+        if !inventory.installed.iter().any(|s| s.version == version) {
+            return Err(format!("CLIProxyAPI {} isn't installed", version));
+        }
+        match std::fs::remove_dir_all(sidecars_dir().join(version)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed to remove CLIProxyAPI {}: {}", version, e));
+            }
+            _ => {}
+        }
+        inventory.installed.retain(|s| s.version != version);
+        if inventory.previous.as_deref() == Some(version) {
+            inventory.previous = None;
+        }
+        Ok(inventory.clone())
+    })
+}
+
+/// The selected version, if its binary is still there unchanged. None launches the
+/// bundled binary.
+pub fn selected_sidecar() -> Option<SelectedSidecar> {
+    let inventory = load_inventory();
+    let version = inventory.selected?;
+    let expected = inventory
+        .installed
+        .iter()
+        .find(|s| s.version == version)
+        .map(|s| s.sha256.clone());
+    let path = binary_path(&version);
+    match (std::fs::read(&path), expected) {
+        (Ok(data), Some(expected)) if sha256_hex(&data) == expected => {
+            Some(SelectedSidecar { version, path })
+        }
+        (Ok(_), Some(_)) => {
+            eprintln!(
+                "[ProxyPal] CLIProxyAPI {} changed since it was installed (SHA-256 mismatch), starting the bundled one",
+                version
+            );
+            None
+        }
+        _ => {
+            eprintln!("[ProxyPal] CLIProxyAPI {} is missing, starting the bundled one", version);
+            None
+        }
+    }
+}
+
+/// Go back to the previous selection after `failed` didn't become ready. None when
+/// `failed` isn't the selected version (anymore), so there is nothing to go back from.
+pub fn rollback(failed: &str, error: &str) -> Option<SidecarRollback> {
+    let result = update_inventory(|inventory| {
+        if inventory.selected.as_deref() != Some(failed) {
+            return Ok(None);
+        }
+        inventory.selected = inventory.previous.take();
+        Ok(Some(SidecarRollback {
+            from: failed.to_string(),
+            to: inventory.selected.clone(),
+            error: error.to_string(),
+        }))
+    });
+    match result {
+        Ok(rollback) => rollback,
+        Err(e) => {
+            eprintln!("[ProxyPal] {}", e);
+            None
+        }
+    }
+}
+
+/// "CLIProxyAPI 6.7.18" or "the bundled CLIProxyAPI"
+pub fn describe(version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!("CLIProxyAPI {}", version),
+        None => "the bundled CLIProxyAPI".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A "binary" at `<dir>/CLIProxyAPI_<version>` with distinct content per version
+    fn release_file(dir: &std::path::Path, version: &str) -> (String, Vec<u8>) {
+        let path = dir.join(format!("CLIProxyAPI_{}", version));
+        let data = format!("#!/bin/sh\necho {}\n", version).into_bytes();
+        std::fs::write(&path, &data).unwrap();
+        (path.to_string_lossy().to_string(), data)
+    }
+
+    #[tokio::test]
+    async fn install_takes_the_version_from_the_file_name() {
+        let dir = crate::config::test_config_dir();
+        let (source, data) = release_file(dir.path(), "6.7.18");
+
+        let installed = install(&source, None, None).await.unwrap();
+
+        assert_eq!(installed.version, "6.7.18");
+        assert_eq!(installed.sha256, sha256_hex(&data));
+        assert_eq!(std::fs::read(binary_path("6.7.18")).unwrap(), data);
+        let inventory = load_inventory();
+        assert_eq!(inventory.installed.len(), 1);
+        // Installing doesn't switch to it
+        assert_eq!(inventory.selected, None);
+    }
+
+    #[tokio::test]
+    async fn install_checks_the_expected_hash() {
+        let dir = crate::config::test_config_dir();
+        let (source, data) = release_file(dir.path(), "6.7.18");
+
+        let error = install(&source, Some(&"0".repeat(64)), None).await.unwrap_err();
+        assert!(error.contains("Nothing was installed"), "{}", error);
+        assert!(!binary_path("6.7.18").exists());
+        assert!(load_inventory().installed.is_empty());
+
+        let error = install(&source, Some("abc"), None).await.unwrap_err();
+        assert!(error.contains("isn't a SHA-256"), "{}", error);
+
+        let expected = sha256_hex(&data).to_uppercase();
+        assert!(install(&source, Some(&expected), None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn install_refuses_unchecked_downloads() {
+        let _dir = crate::config::test_config_dir();
+        let hash = "0".repeat(64);
+
+        let error = install("http://example.com/CLIProxyAPI_6.7.18", Some(&hash), None)
+            .await
+            .unwrap_err();
+        assert!(error.contains("Only https://"), "{}", error);
+        let error = install("https://example.com/CLIProxyAPI_6.7.18", None, None)
+            .await
+            .unwrap_err();
+        assert!(error.contains("SHA-256"), "{}", error);
+        assert!(load_inventory().installed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn install_refuses_archives_and_unknown_versions() {
+        let dir = crate::config::test_config_dir();
+        let archive = dir.path().join("CLIProxyAPI_6.7.18_linux_amd64.tar.gz");
+        std::fs::write(&archive, b"\x1f\x8b\x08\x00").unwrap();
+        let error = install(&archive.to_string_lossy(), None, None).await.unwrap_err();
+        assert!(error.contains("isn't an executable"), "{}", error);
+
+        let (source, _) = release_file(dir.path(), "6.7.18");
+        let renamed = dir.path().join("cli-proxy-api");
+        std::fs::rename(&source, &renamed).unwrap();
+        let renamed = renamed.to_string_lossy().to_string();
+        let error = install(&renamed, None, None).await.unwrap_err();
+        assert!(error.contains("Couldn't tell the version"), "{}", error);
+        assert_eq!(install(&renamed, None, Some("6.7.20")).await.unwrap().version, "6.7.20");
+    }
+
+    #[tokio::test]
+    async fn select_keeps_the_previous_selection() {
+        let dir = crate::config::test_config_dir();
+        for version in ["6.7.18", "6.7.20"] {
+            install(&release_file(dir.path(), version).0, None, None).await.unwrap();
+        }
+
+        let inventory = select(Some("6.7.18")).unwrap();
+        assert_eq!((inventory.selected.as_deref(), inventory.previous.as_deref()), (Some("6.7.18"), None));
+        let inventory = select(Some("6.7.20")).unwrap();
+        assert_eq!(
+            (inventory.selected.as_deref(), inventory.previous.as_deref()),
+            (Some("6.7.20"), Some("6.7.18"))
+        );
+        // Selecting it again keeps what to go back to
+        let inventory = select(Some("6.7.20")).unwrap();
+        assert_eq!(inventory.previous.as_deref(), Some("6.7.18"));
+
+        assert!(select(Some("6.8.0")).unwrap_err().contains("isn't installed"));
+        assert_eq!(load_inventory().selected.as_deref(), Some("6.7.20"));
+        assert_eq!(selected_sidecar().unwrap().version, "6.7.20");
+    }
+
+    #[tokio::test]
+    async fn rollback_goes_back_to_the_previous_selection() {
+        let dir = crate::config::test_config_dir();
+        for version in ["6.7.18", "6.7.20"] {
+            install(&release_file(dir.path(), version).0, None, None).await.unwrap();
+        }
+        select(Some("6.7.18")).unwrap();
+        select(Some("6.7.20")).unwrap();
+
+        // Not the selected version: nothing to go back from
+        assert!(rollback("6.7.18", "not ready").is_none());
+
+        let rolled_back = rollback("6.7.20", "not ready").unwrap();
+        assert_eq!(rolled_back.from, "6.7.20");
+        assert_eq!(rolled_back.to.as_deref(), Some("6.7.18"));
+        assert_eq!(rolled_back.error, "not ready");
+        let inventory = load_inventory();
+        assert_eq!(inventory.selected.as_deref(), Some("6.7.18"));
+        assert_eq!(inventory.previous, None);
+
+        // With nothing before it, back to the bundled binary
+        let rolled_back = rollback("6.7.18", "not ready").unwrap();
+        assert_eq!(rolled_back.to, None);
+        assert!(selected_sidecar().is_none());
+    }
+
+    #[tokio::test]
+    async fn changed_binary_isnt_launched() {
+        let dir = crate::config::test_config_dir();
+        install(&release_file(dir.path(), "6.7.18").0, None, None).await.unwrap();
+        select(Some("6.7.18")).unwrap();
+        assert!(selected_sidecar().is_some());
+
+        std::fs::write(binary_path("6.7.18"), b"#!/bin/sh\necho tampered\n").unwrap();
+        assert!(selected_sidecar().is_none());
+    }
+
+    #[tokio::test]
+    async fn remove_refuses_the_selected_version() {
+        let dir = crate::config::test_config_dir();
+        for version in ["6.7.18", "6.7.20"] {
+            install(&release_file(dir.path(), version).0, None, None).await.unwrap();
+        }
+        select(Some("6.7.18")).unwrap();
+        select(Some("6.7.20")).unwrap();
+
+        assert!(remove("6.7.20").unwrap_err().contains("is selected"));
+        let inventory = remove("6.7.18").unwrap();
+        assert_eq!(inventory.installed.len(), 1);
+        // Nothing to roll back to anymore
+        assert_eq!(inventory.previous, None);
+        assert!(!sidecars_dir().join("6.7.18").exists());
+        assert!(remove("../6.7.20").unwrap_err().contains("isn't installed"));
+    }
+}
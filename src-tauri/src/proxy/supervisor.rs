@@ -0,0 +1,206 @@
+//! What to do when the sidecar exits without being asked to: restart it after a delay
+//! that doubles with every exit, or give up once it keeps crashing (crash loop).
+//! Shared by the desktop app and proxypal-daemon.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+
+/// Restart delays after the sidecar exits: doubling from the first to the last
+pub const RESTART_DELAY_MIN: Duration = Duration::from_secs(1);
+pub const RESTART_DELAY_MAX: Duration = Duration::from_secs(60);
+/// A sidecar that ran this long gets the shortest restart delay again
+pub const STABLE_RUN: Duration = Duration::from_secs(60);
+/// stderr lines kept per run and reported with a crash
+pub const STDERR_TAIL_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartDecision {
+    /// Restart after `delay`; `exits` is how many exits the crash-loop window holds
+    Restart { delay: Duration, exits: u32 },
+    /// Too many exits in the window, leave the sidecar stopped
+    CrashLoop { exits: u32 },
+}
+
+#[derive(Debug)]
+pub struct RestartPolicy {
+    max_exits: u32,
+    window: Duration,
+    exits: VecDeque<Instant>,
+    delay: Duration,
+}
+
+impl RestartPolicy {
+    pub fn new(max_exits: u32, window: Duration) -> Self {
+        Self {
+            max_exits: max_exits.max(1),
+            window,
+            exits: VecDeque::new(),
+            delay: RESTART_DELAY_MIN,
+        }
+    }
+
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut policy = Self::new(1, Duration::ZERO);
+        policy.configure(config);
+        policy
+    }
+
+    /// Take the crash-loop limits from `config`, keeping the exits seen so far
+    pub fn configure(&mut self, config: &AppConfig) {
+        self.max_exits = config.crash_loop_max_exits.max(1);
+        self.window = Duration::from_secs(u64::from(config.crash_loop_window_minutes) * 60);
+    }
+
+    /// Record an exit at `now` after the sidecar ran for `ran_for`
+    pub fn on_exit(&mut self, now: Instant, ran_for: Duration) -> RestartDecision {
+        while self
+            .exits
+            .front()
+            .is_some_and(|exit| now.duration_since(*exit) > self.window)
+        {
+            self.exits.pop_front();
+        }
+        self.exits.push_back(now);
+        let exits = self.exits.len() as u32;
+        if exits >= self.max_exits {
+            // Whoever starts it again next gets a fresh budget
+            self.reset();
+            return RestartDecision::CrashLoop { exits };
+        }
+
+        if ran_for >= STABLE_RUN {
+            self.delay = RESTART_DELAY_MIN;
+        }
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(RESTART_DELAY_MAX);
+        RestartDecision::Restart { delay, exits }
+    }
+
+    pub fn reset(&mut self) {
+        self.exits.clear();
+        self.delay = RESTART_DELAY_MIN;
+    }
+}
+
+/// The last lines a process wrote to stderr
+#[derive(Debug, Default, Clone)]
+pub struct StderrTail {
+    lines: VecDeque<String>,
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+impl StderrTail {
+    pub fn push(&mut self, line: &str) {
+        if self.lines.len() == STDERR_TAIL_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.trim_end().to_string());
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT_RUN: Duration = Duration::from_secs(2);
+
+    fn delay(decision: RestartDecision) -> Duration {
+        match decision {
+            RestartDecision::Restart { delay, .. } => delay,
+            other => panic!("expected a restart, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_maximum() {
+        let mut policy = RestartPolicy::new(100, Duration::from_secs(600));
+        let start = Instant::now();
+        let delays: Vec<u64> = (0..9)
+            .map(|i| delay(policy.on_exit(start + Duration::from_secs(i), SHORT_RUN)).as_secs())
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60, 60]);
+    }
+
+    #[test]
+    fn stable_run_resets_the_delay() {
+        let mut policy = RestartPolicy::new(100, Duration::from_secs(600));
+        let start = Instant::now();
+        for i in 0..4 {
+            policy.on_exit(start + Duration::from_secs(i), SHORT_RUN);
+        }
+        let decision = policy.on_exit(start + Duration::from_secs(100), STABLE_RUN);
+        assert_eq!(delay(decision), RESTART_DELAY_MIN);
+        let decision = policy.on_exit(start + Duration::from_secs(101), SHORT_RUN);
+        assert_eq!(delay(decision), RESTART_DELAY_MIN * 2);
+    }
+
+    #[test]
+    fn crash_loop_within_the_window() {
+        let mut policy = RestartPolicy::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+        assert_eq!(
+            policy.on_exit(start, SHORT_RUN),
+            RestartDecision::Restart { delay: Duration::from_secs(1), exits: 1 }
+        );
+        assert_eq!(
+            policy.on_exit(start + Duration::from_secs(10), SHORT_RUN),
+            RestartDecision::Restart { delay: Duration::from_secs(2), exits: 2 }
+        );
+        assert_eq!(
+            policy.on_exit(start + Duration::from_secs(20), SHORT_RUN),
+            RestartDecision::CrashLoop { exits: 3 }
+        );
+        // Given up on, the next start begins with a fresh budget and delay
+        assert_eq!(
+            policy.on_exit(start + Duration::from_secs(30), SHORT_RUN),
+            RestartDecision::Restart { delay: Duration::from_secs(1), exits: 1 }
+        );
+    }
+
+    #[test]
+    fn exits_outside_the_window_are_forgotten() {
+        let mut policy = RestartPolicy::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+        policy.on_exit(start, SHORT_RUN);
+        policy.on_exit(start + Duration::from_secs(30), SHORT_RUN);
+        // The first exit is more than a window ago
+        let decision = policy.on_exit(start + Duration::from_secs(61), SHORT_RUN);
+        assert!(matches!(decision, RestartDecision::Restart { exits: 2, .. }), "{:?}", decision);
+        let decision = policy.on_exit(start + Duration::from_secs(62), SHORT_RUN);
+        assert_eq!(decision, RestartDecision::CrashLoop { exits: 3 });
+    }
+
+    #[test]
+    fn limits_come_from_the_config() {
+        let config = AppConfig {
+            crash_loop_max_exits: 2,
+            crash_loop_window_minutes: 1,
+            ..Default::default()
+        };
+        let mut policy = RestartPolicy::from_config(&config);
+        let start = Instant::now();
+        assert!(matches!(policy.on_exit(start, SHORT_RUN), RestartDecision::Restart { .. }));
+        assert_eq!(
+            policy.on_exit(start + Duration::from_secs(59), SHORT_RUN),
+            RestartDecision::CrashLoop { exits: 2 }
+        );
+    }
+
+    #[test]
+    fn stderr_tail_keeps_the_last_lines() {
+        let mut tail = StderrTail::default();
+        for i in 0..STDERR_TAIL_LINES + 5 {
+            tail.push(&format!("line {}\n", i));
+        }
+        let lines = tail.lines();
+        assert_eq!(lines.len(), STDERR_TAIL_LINES);
+        assert_eq!(lines[0], "line 5");
+        assert_eq!(lines.last().unwrap(), &format!("line {}", STDERR_TAIL_LINES + 4));
+    }
+}
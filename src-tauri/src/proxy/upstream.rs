@@ -0,0 +1,62 @@
+//! Upstream proxy the sidecar sends provider traffic through.
+
+use sysproxy::Sysproxy;
+use url::Url;
+
+use crate::config::AppConfig;
+
+/// Proxy configured through the environment or the OS, if any
+pub fn detect_system_proxy() -> Result<Option<String>, String> {
+    // 1. Check environment variables first (common in Linux/Dev environments)
+    // We'll check for a common URL like google.com to see if a proxy is configured for HTTPS
+    if let Ok(target_url) = Url::parse("https://www.google.com") {
+        let proxy = env_proxy::for_url(&target_url);
+        if let Some((host, port)) = proxy.host_port() {
+            return Ok(Some(format!("http://{}:{}", host, port)));
+        }
+    }
+
+    // 2. Check OS-level system proxy settings
+    let sys_proxy = Sysproxy::get_system_proxy();
+
+    match sys_proxy {
+        Ok(proxy) if proxy.enable => {
+            let protocol = if proxy.host.contains("socks") {
+                "socks5"
+            } else {
+                "http"
+            };
+            Ok(Some(format!(
+                "{}://{}:{}",
+                protocol, proxy.host, proxy.port
+            )))
+        }
+        Ok(_) => Ok(None),
+        Err(e) => Err(format!("Failed to detect system proxy: {}", e)),
+    }
+}
+
+/// Upstream proxy URL for the sidecar: the system proxy or the configured one,
+/// with proxy credentials applied. Empty when no proxy is used.
+pub fn effective_proxy_url(config: &AppConfig) -> String {
+    // If system proxy is enabled, try to detect it
+    let mut effective_proxy_url = if config.use_system_proxy {
+        detect_system_proxy().ok().flatten().unwrap_or_default()
+    } else {
+        config.proxy_url.clone()
+    };
+
+    // Handle proxy authentication if provided
+    if !effective_proxy_url.is_empty()
+        && !config.proxy_username.is_empty()
+        && !config.proxy_password.is_empty()
+    {
+        if let Ok(mut url) = Url::parse(&effective_proxy_url) {
+            let _ = url.set_username(&config.proxy_username);
+            let _ = url.set_password(Some(&config.proxy_password));
+            effective_proxy_url = url.to_string();
+        }
+    }
+
+    effective_proxy_url
+}
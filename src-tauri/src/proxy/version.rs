@@ -0,0 +1,195 @@
+//! Which CLIProxyAPI version is running, and which ProxyPal features it can serve.
+//! The version comes from the X-CPA-VERSION header of Management API responses, or
+//! from the banner the sidecar prints at startup. Features that need a newer sidecar
+//! are refused with a clear message instead of an opaque HTTP error; with the
+//! version unknown everything is tried.
+
+use std::fmt;
+
+use crate::types::SidecarFeatureSupport;
+
+/// Response header the Management API reports the sidecar version in
+pub const VERSION_HEADER: &str = "X-CPA-VERSION";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SidecarVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SidecarVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// The first x.y.z in `text`, e.g. "v6.7.18", "6.7.18-0" or
+    /// "CLIProxyAPI Version: 6.7.18, Commit: abc123"
+    pub fn parse(text: &str) -> Option<Self> {
+        lazy_static::lazy_static! {
+            static ref VERSION_REGEX: regex::Regex =
+                regex::Regex::new(r"(\d+)\.(\d+)\.(\d+)").unwrap();
+        }
+        let caps = VERSION_REGEX.captures(text)?;
+        Some(Self::new(
+            caps[1].parse().ok()?,
+            caps[2].parse().ok()?,
+            caps[3].parse().ok()?,
+        ))
+    }
+
+    /// Version from the startup banner among the lines the sidecar printed
+    pub fn from_output<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        lines
+            .into_iter()
+            .filter(|line| line.to_lowercase().contains("version"))
+            .find_map(Self::parse)
+    }
+}
+
+impl fmt::Display for SidecarVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub enum SidecarFeature {
+    /// GET /api/auth/status, behind verify_proxy_auth_status
+    AuthStatus,
+    /// PATCH /v0/management/auth-files/status, behind toggle_auth_file
+    AuthFileToggle,
+}
+
+/// Feature, id used by the UI, description, minimum sidecar version
+const FEATURES: &[(SidecarFeature, &str, &str, SidecarVersion)] = &[
+    (
+        SidecarFeature::AuthStatus,
+        "auth-status",
+        "Checking account status in the proxy",
+        SidecarVersion::new(6, 6, 72),
+    ),
+    (
+        SidecarFeature::AuthFileToggle,
+        "auth-file-toggle",
+        "Turning auth files on and off without renaming them",
+        SidecarVersion::new(6, 7, 18),
+    ),
+];
+
+fn entry(feature: SidecarFeature) -> &'static (SidecarFeature, &'static str, &'static str, SidecarVersion) {
+    FEATURES
+        .iter()
+        .find(|(f, ..)| *f == feature)
+        .expect("every feature is in FEATURES")
+}
+
+/// Ok when `version` can serve `feature` or isn't known, the reason otherwise
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn require(version: Option<SidecarVersion>, feature: SidecarFeature) -> Result<(), String> {
+    let (_, _, description, minimum) = entry(feature);
+    match version {
+        Some(version) if version < *minimum => Err(format!(
+            "{} needs CLIProxyAPI {} or newer, the running proxy is {}",
+            description, minimum, version
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Every feature with its minimum version and whether `version` has it
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn feature_support(version: Option<SidecarVersion>) -> Vec<SidecarFeatureSupport> {
+    FEATURES
+        .iter()
+        .map(|(feature, id, description, minimum)| {
+            let unsupported = require(version, *feature).err();
+            SidecarFeatureSupport {
+                id: id.to_string(),
+                description: description.to_string(),
+                min_version: minimum.to_string(),
+                supported: version.map(|_| unsupported.is_none()),
+                message: unsupported,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_takes_the_first_version_in_the_text() {
+        assert_eq!(SidecarVersion::parse("6.7.18"), Some(SidecarVersion::new(6, 7, 18)));
+        // X-CPA-VERSION values
+        assert_eq!(SidecarVersion::parse("v6.7.18"), Some(SidecarVersion::new(6, 7, 18)));
+        assert_eq!(SidecarVersion::parse("6.7.18-0"), Some(SidecarVersion::new(6, 7, 18)));
+        // --version output
+        assert_eq!(
+            SidecarVersion::parse("CLIProxyAPI Version: 6.6.72, Commit: abc123, BuiltAt: 2025-10-01"),
+            Some(SidecarVersion::new(6, 6, 72))
+        );
+        // Release file names
+        assert_eq!(
+            SidecarVersion::parse("CLIProxyAPI_6.7.18_linux_amd64"),
+            Some(SidecarVersion::new(6, 7, 18))
+        );
+        assert_eq!(SidecarVersion::parse("dev"), None);
+        assert_eq!(SidecarVersion::parse("6.7"), None);
+        assert_eq!(SidecarVersion::parse("99999999999.0.0"), None);
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(SidecarVersion::new(6, 10, 0) > SidecarVersion::new(6, 9, 99));
+        assert!(SidecarVersion::new(7, 0, 0) > SidecarVersion::new(6, 99, 99));
+        assert_eq!(SidecarVersion::new(6, 7, 18).to_string(), "6.7.18");
+    }
+
+    #[test]
+    fn from_output_finds_the_startup_banner() {
+        let lines = [
+            "listening on 127.0.0.1:8317",
+            "loaded 3 auth files from 1.2.3.4",
+            "CLIProxyAPI Version: 6.7.18, Commit: abc123, BuiltAt: 2025-10-01",
+        ];
+        assert_eq!(SidecarVersion::from_output(lines), Some(SidecarVersion::new(6, 7, 18)));
+        assert_eq!(
+            SidecarVersion::from_output(["cli-proxy-api version 6.6.72"]),
+            Some(SidecarVersion::new(6, 6, 72))
+        );
+        assert_eq!(SidecarVersion::from_output(["server started on 1.2.3.4"]), None);
+        assert_eq!(SidecarVersion::from_output(["version unknown"]), None);
+    }
+
+    #[test]
+    fn require_checks_the_minimum_version() {
+        let feature = SidecarFeature::AuthFileToggle;
+        let minimum = entry(feature).3;
+
+        let error = require(Some(SidecarVersion::new(6, 7, 17)), feature).unwrap_err();
+        assert!(error.contains("needs CLIProxyAPI 6.7.18 or newer"), "{}", error);
+        assert!(error.contains("6.7.17"), "{}", error);
+        assert!(require(Some(minimum), feature).is_ok());
+        assert!(require(Some(SidecarVersion::new(6, 8, 0)), feature).is_ok());
+        // Unknown version: tried anyway
+        assert!(require(None, feature).is_ok());
+    }
+
+    #[test]
+    fn feature_support_reports_every_feature() {
+        let support = feature_support(Some(SidecarVersion::new(6, 7, 0)));
+        assert_eq!(support.len(), FEATURES.len());
+        let auth_status = support.iter().find(|s| s.id == "auth-status").unwrap();
+        assert_eq!(auth_status.supported, Some(true));
+        assert!(auth_status.message.is_none());
+        let toggle = support.iter().find(|s| s.id == "auth-file-toggle").unwrap();
+        assert_eq!(toggle.supported, Some(false));
+        assert_eq!(toggle.min_version, "6.7.18");
+        assert!(toggle.message.is_some());
+
+        assert!(feature_support(None).iter().all(|s| s.supported.is_none() && s.message.is_none()));
+    }
+}
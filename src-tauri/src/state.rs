@@ -3,8 +3,28 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64};
 use tauri_plugin_shell::process::CommandChild;
 
-use crate::types::{ProxyStatus, AuthStatus, OAuthState, CopilotStatus};
+use crate::types::{ProxyStatus, AuthStatus, OAuthState, CopilotStatus, ProxyCrash};
 use crate::config::AppConfig;
+use crate::proxy::supervisor::RestartPolicy;
+
+/// Restart bookkeeping for the proxy sidecar (see app::supervisor)
+pub struct ProxySupervisor {
+    pub policy: RestartPolicy,
+    /// Most recent crashes, oldest first
+    pub crashes: Vec<ProxyCrash>,
+    /// Bumped to cancel a restart that is waiting out its delay
+    pub restart_generation: u64,
+}
+
+impl ProxySupervisor {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            policy: RestartPolicy::from_config(config),
+            crashes: Vec::new(),
+            restart_generation: 0,
+        }
+    }
+}
 
 /// App state shared across all Tauri commands
 pub struct AppState {
@@ -17,6 +37,7 @@ pub struct AppState {
     pub copilot_process: Mutex<Option<CommandChild>>,
     pub log_watcher_running: Arc<AtomicBool>,
     pub request_counter: Arc<AtomicU64>,
+    pub proxy_supervisor: Mutex<ProxySupervisor>,
 }
 
 impl Default for AppState {
@@ -31,6 +52,7 @@ impl Default for AppState {
             copilot_process: Mutex::new(None),
             log_watcher_running: Arc::new(AtomicBool::new(false)),
             request_counter: Arc::new(AtomicU64::new(0)),
+            proxy_supervisor: Mutex::new(ProxySupervisor::new(&AppConfig::default())),
         }
     }
 }
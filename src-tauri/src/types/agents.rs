@@ -1,8 +1,10 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 // Detected AI coding tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct DetectedTool {
     pub id: String,
     pub name: String,
@@ -12,7 +14,7 @@ pub struct DetectedTool {
 }
 
 // CLI Agent configuration status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentStatus {
     pub id: String,
@@ -1,8 +1,9 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 // API Key types matching Management API schema
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GeminiApiKey {
     pub api_key: String,
@@ -18,7 +19,7 @@ pub struct GeminiApiKey {
     pub prefix: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct VertexApiKey {
     pub api_key: String,
@@ -33,7 +34,7 @@ pub struct VertexApiKey {
 }
 
 // Model mapping with alias and name (used by Claude and OpenAI-compatible providers)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelMapping {
     pub name: String,
@@ -41,7 +42,7 @@ pub struct ModelMapping {
     pub alias: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ClaudeApiKey {
     pub api_key: String,
@@ -59,7 +60,7 @@ pub struct ClaudeApiKey {
     pub prefix: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CodexApiKey {
     pub api_key: String,
@@ -75,6 +76,7 @@ pub struct CodexApiKey {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct OpenAICompatibleApiKeyEntry {
     pub api_key: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -83,6 +85,7 @@ pub struct OpenAICompatibleApiKeyEntry {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct OpenAICompatibleProvider {
     pub name: String,
     pub base_url: String,
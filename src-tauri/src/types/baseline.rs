@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Layer a config value comes from, lowest first (Locked wins over everything)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigValueSource {
+    /// Built-in default
+    Default,
+    /// Organization baseline, the user may change it
+    Baseline,
+    /// The user's config.json
+    User,
+    /// PROXYPAL_* environment variable or `--set` argument, for this launch only
+    Override,
+    /// Organization baseline, the user can't change it
+    Locked,
+}
+
+/// A per-launch override in effect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigOverride {
+    /// camelCase path, e.g. "port" or "copilot.port"
+    pub field: String,
+    /// "PROXYPAL_PORT" or "--set port"
+    pub source: String,
+}
+
+/// Result of get_config_sources
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSources {
+    /// Baseline file in use, if any
+    pub baseline_path: Option<String>,
+    /// Set when a baseline file exists but could not be read (it is ignored)
+    pub baseline_error: Option<String>,
+    /// camelCase fields the UI must show as read-only
+    pub locked: Vec<String>,
+    /// Environment and command-line overrides; they are never saved
+    pub overrides: Vec<ConfigOverride>,
+    /// Source of every top-level camelCase field
+    pub fields: BTreeMap<String, ConfigValueSource>,
+}
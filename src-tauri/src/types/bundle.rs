@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Shareable subset of AppConfig, written by export_config_bundle.
+/// Each section holds camelCase AppConfig fields, e.g. sections["ssh"]["sshConfigs"].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundle {
+    /// Always "proxypal-config-bundle"
+    pub format: String,
+    pub version: u32,
+    pub created_at: u64,
+    pub app_version: String,
+    /// True when API keys and tokens are included (in plaintext)
+    pub includes_secrets: bool,
+    pub sections: BTreeMap<String, serde_json::Value>,
+}
+
+/// How a bundle section is imported
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleImportMode {
+    /// Add new entries, update matching ones, keep local-only ones
+    Merge,
+    /// Replace the local fields with the bundle's
+    Replace,
+    /// Leave the local fields alone
+    Skip,
+}
+
+/// A local entry or value the bundle would change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleConflict {
+    pub field: String,
+    /// Entry name for list fields (provider name, mapping source model, ...)
+    pub item: Option<String>,
+    /// What differs, with secrets masked
+    pub changes: Vec<super::ConfigFieldDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSectionPreview {
+    pub id: String,
+    pub label: String,
+    /// Entries that don't exist locally yet
+    pub new_items: Vec<String>,
+    pub conflicts: Vec<BundleConflict>,
+    /// Local entries that "replace" would remove
+    pub removed_on_replace: Vec<String>,
+}
+
+/// Result of preview_config_bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePreview {
+    pub version: u32,
+    pub created_at: u64,
+    pub app_version: String,
+    pub includes_secrets: bool,
+    pub sections: Vec<BundleSectionPreview>,
+}
@@ -0,0 +1,72 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::quota::{
+    AntigravityQuotaResult, ClaudeQuotaResult, CodexQuotaResult, CopilotQuotaResult,
+    KiroQuotaResult,
+};
+use super::usage::{TimeSeriesPoint, UsageStats};
+
+/// Quota of every account of one provider, or why it couldn't be fetched
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderQuotas<T> {
+    pub accounts: Vec<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<T> From<Result<Vec<T>, String>> for ProviderQuotas<T> {
+    fn from(result: Result<Vec<T>, String>) -> Self {
+        match result {
+            Ok(accounts) => Self { accounts, error: None },
+            Err(error) => Self { accounts: Vec::new(), error: Some(error) },
+        }
+    }
+}
+
+/// Quotas of all providers, as returned by GET /v1/quota
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaReport {
+    pub antigravity: ProviderQuotas<AntigravityQuotaResult>,
+    pub codex: ProviderQuotas<CodexQuotaResult>,
+    pub copilot: ProviderQuotas<CopilotQuotaResult>,
+    pub claude: ProviderQuotas<ClaudeQuotaResult>,
+    pub kiro: ProviderQuotas<KiroQuotaResult>,
+}
+
+/// Usage over a time window, as returned by GET /v1/usage
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// First day ("2025-01-31") or hour ("2025-01-31T09") counted; None for all time
+    pub since: Option<String>,
+    pub requests: u64,
+    pub tokens: u64,
+    /// Requests per day (or per hour for windows shorter than a day)
+    pub requests_series: Vec<TimeSeriesPoint>,
+    pub tokens_series: Vec<TimeSeriesPoint>,
+    /// All-time statistics (per-model and per-provider numbers aren't kept per day)
+    pub stats: UsageStats,
+}
+
+/// An SSH or Cloudflare tunnel and its connection state
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TunnelStatus {
+    pub id: String,
+    pub name: String,
+    /// Whether the tunnel is kept up, also across restarts
+    pub enabled: bool,
+    /// "active" while the tunnel manager runs it, else "inactive"
+    pub status: String,
+}
+
+/// All tunnels, as returned by GET /v1/tunnels
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TunnelList {
+    pub ssh: Vec<TunnelStatus>,
+    pub cloudflare: Vec<TunnelStatus>,
+}
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CopilotConfig {
     #[serde(default)]
@@ -8,6 +9,7 @@ pub struct CopilotConfig {
     #[serde(default = "default_copilot_port")]
     pub port: u16,
     #[serde(default)]
+    #[schemars(schema_with = "crate::config::schema::copilot_account_type")]
     pub account_type: String,
     #[serde(default)]
     pub github_token: String,
@@ -15,12 +17,48 @@ pub struct CopilotConfig {
     pub rate_limit: Option<u16>,
     #[serde(default)]
     pub rate_limit_wait: bool,
+    /// Models left out of the proxy's copilot entry, "*" wildcards allowed
+    #[serde(default)]
+    pub hidden_models: Vec<String>,
+    /// Extra names clients can use for copilot models
+    #[serde(default)]
+    pub extra_aliases: Vec<CopilotModelAlias>,
+    /// Reasoning-level variants listed for matching models, e.g. gpt-5(high)
+    #[serde(default = "default_reasoning_suffixes")]
+    pub reasoning_suffixes: Vec<CopilotReasoningSuffixes>,
+}
+
+/// `alias` is what clients request, `name` the copilot model it goes to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CopilotModelAlias {
+    pub name: String,
+    pub alias: String,
+}
+
+/// Suffixes listed as `model(suffix)` for every model matching `pattern`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CopilotReasoningSuffixes {
+    /// Model name, "*" wildcards allowed (e.g. "gpt-5*")
+    pub pattern: String,
+    pub suffixes: Vec<String>,
 }
 
 fn default_copilot_port() -> u16 {
     4141
 }
 
+fn default_reasoning_suffixes() -> Vec<CopilotReasoningSuffixes> {
+    vec![CopilotReasoningSuffixes {
+        pattern: "gpt-5*".to_string(),
+        suffixes: crate::proxy::sidecar_config::GPT5_REASONING_SUFFIXES
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    }]
+}
+
 impl Default for CopilotConfig {
     fn default() -> Self {
         Self {
@@ -30,12 +68,37 @@ impl Default for CopilotConfig {
             github_token: String::new(),
             rate_limit: None,
             rate_limit_wait: false,
+            hidden_models: Vec::new(),
+            extra_aliases: Vec::new(),
+            reasoning_suffixes: default_reasoning_suffixes(),
         }
     }
 }
 
+/// Where the copilot model list came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CopilotModelSource {
+    /// copilot-api's /v1/models, this session
+    Live,
+    /// Last list fetched in an earlier session (copilot-models.json)
+    Cache,
+    /// Built-in list, nothing fetched yet
+    Builtin,
+}
+
+/// Models copilot-api serves, before hiding, aliases and reasoning variants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopilotModelList {
+    pub models: Vec<String>,
+    pub source: CopilotModelSource,
+    pub fetched_at: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct CopilotStatus {
     pub running: bool,
     pub port: u16,
@@ -56,6 +119,7 @@ impl Default for CopilotStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct CopilotApiDetection {
     pub installed: bool,
     pub version: Option<String>,
@@ -72,6 +136,7 @@ pub struct CopilotApiDetection {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct CopilotApiInstallResult {
     pub success: bool,
     pub message: String,
@@ -1,10 +1,22 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 // Log entry structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LogEntry {
     pub timestamp: String,
     pub level: String,
     pub message: String,
 }
+
+/// Lines of the proxy's log file, see /v1/logs/tail
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LogChunk {
+    pub entries: Vec<LogEntry>,
+    /// Pass as `after` to get the lines written since
+    pub cursor: u64,
+    /// New lines left out because more were written than asked for
+    pub skipped: usize,
+}
@@ -1,16 +1,28 @@
 pub mod agents;
 pub mod amp;
 pub mod api_keys;
+#[cfg(feature = "desktop")]
 pub mod auth;
 pub mod auth_files;
+pub mod baseline;
+pub mod bundle;
+pub mod control;
 pub mod copilot;
+#[cfg(feature = "desktop")]
 pub mod health;
 pub mod logs;
 pub mod models;
+pub mod payload;
+pub mod process;
+pub mod profile;
 pub mod proxy;
 pub mod quota;
+pub mod schema;
 pub mod settings;
+pub mod sidecar;
+pub mod snapshot;
 pub mod usage;
+pub mod validation;
 
 pub mod ssh;
 pub mod cloudflare;
@@ -18,16 +30,30 @@ pub mod cloudflare;
 pub use agents::*;
 pub use amp::*;
 pub use api_keys::*;
+#[cfg(feature = "desktop")]
 pub use auth::*;
 pub use auth_files::*;
+pub use baseline::*;
+pub use bundle::*;
+pub use control::*;
 pub use copilot::*;
+#[cfg(feature = "desktop")]
 pub use health::*;
 pub use logs::*;
 pub use models::*;
+pub use payload::*;
+pub use process::*;
+pub use profile::*;
 pub use proxy::*;
+#[cfg(feature = "desktop")]
 pub use quota::*;
+pub use schema::*;
+#[cfg(feature = "desktop")]
 pub use settings::*;
+pub use sidecar::*;
+pub use snapshot::*;
 pub use usage::*;
+pub use validation::*;
 pub use ssh::*;
 #[allow(unused_imports)]
 pub use cloudflare::*;
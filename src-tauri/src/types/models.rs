@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 // Get available models from CLIProxyAPI /v1/models endpoint
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AvailableModel {
     pub id: String,
@@ -15,6 +16,7 @@ pub struct AvailableModel {
 // Test connection to a custom OpenAI-compatible provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct ProviderTestResult {
     pub success: bool,
     pub message: String,
@@ -25,6 +27,7 @@ pub struct ProviderTestResult {
 // Models fetched from an OpenAI-compatible provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct OpenAICompatibleProviderModels {
     pub provider_name: String,
     pub base_url: String,
@@ -34,6 +37,7 @@ pub struct OpenAICompatibleProviderModels {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct OpenAICompatibleModel {
     pub id: String,
     #[serde(default)]
@@ -0,0 +1,43 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::amp::generate_uuid;
+
+/// Which payload list of proxy-config.yaml a rule goes to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadRuleMode {
+    /// Set the params only when the request doesn't
+    Default,
+    /// Always set the params, replacing the request's values
+    Override,
+}
+
+/// A persisted payload rule, rendered into the `payload` section of proxy-config.yaml.
+///
+/// String param values may be a placeholder filled from the thinking settings:
+/// `{{thinkingBudget}}` (token count) or `{{geminiThinkingLevel}}` (low/medium/high).
+/// Rules using `{{geminiThinkingLevel}}` are skipped when geminiThinkingInjection is off.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadRule {
+    #[serde(default = "generate_uuid")]
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Model names, "*" wildcards allowed (e.g. "gemini-3-pro-preview*")
+    pub models: Vec<String>,
+    /// Restrict to one request protocol (claude, openai, gemini, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    pub mode: PayloadRuleMode,
+    /// JSON path in the request body -> value, e.g. "thinking.budget_tokens" -> "{{thinkingBudget}}"
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
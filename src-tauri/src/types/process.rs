@@ -0,0 +1,34 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line a supervised process printed, also streamed as the "process-output" event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputLine {
+    /// Unix milliseconds
+    pub timestamp: u64,
+    /// cli-proxy-api, copilot-api, ssh or cloudflared
+    pub process: String,
+    /// Tunnel id for ssh and cloudflared, which can run several at once
+    pub source: Option<String>,
+    pub stream: OutputStream,
+    pub text: String,
+}
+
+/// Result of get_process_output
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutput {
+    pub process: String,
+    /// Oldest first
+    pub lines: Vec<OutputLine>,
+    /// Nothing was printed since ProxyPal started, these lines were saved by the run before
+    pub from_previous_run: bool,
+}
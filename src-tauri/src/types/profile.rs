@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// A named configuration profile stored in profiles/<name>.json.
+/// `overlay` is a full or partial AppConfig (camelCase JSON) merged over the current config
+/// on activation; it may hold secrets such as API keys or proxy credentials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub overlay: serde_json::Value,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Profile as shown in the profile picker (without the overlay values)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+    pub name: String,
+    pub description: String,
+    /// Top-level AppConfig fields the profile sets
+    pub fields: Vec<String>,
+    pub active: bool,
+    pub updated_at: u64,
+}
+
+/// Result of activate_profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub struct ProfileActivation {
+    pub profile: String,
+    pub apply: super::ConfigApplyResult,
+    /// Agents whose settings were rewritten for the new config
+    pub reconfigured_agents: Vec<String>,
+    /// Agents that could not be reconfigured, with the error
+    pub agent_errors: Vec<String>,
+}
@@ -1,10 +1,14 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProxyStatus {
     pub running: bool,
     pub port: u16,
     pub endpoint: String,
+    /// CLIProxyAPI version detected when the proxy last started, if it could be
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 impl Default for ProxyStatus {
@@ -13,6 +17,152 @@ impl Default for ProxyStatus {
             running: false,
             port: 8317,
             endpoint: "http://localhost:8317/v1".to_string(),
+            version: None,
         }
     }
 }
+
+/// A ProxyPal feature that needs a minimum CLIProxyAPI version, see proxy::version
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarFeatureSupport {
+    pub id: String,
+    pub description: String,
+    pub min_version: String,
+    /// None while the running version is unknown
+    pub supported: Option<bool>,
+    /// Why the feature is unavailable
+    pub message: Option<String>,
+}
+
+/// One line of the effective proxy-config.yaml preview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyConfigPreviewLine {
+    pub text: String,
+    /// Dotted path of the node this line belongs to (e.g. "payload.default[2].params")
+    pub path: String,
+    /// True when the value on this line came from proxy-config-custom.yaml
+    pub from_custom: bool,
+}
+
+/// Effective sidecar config: generated settings with proxy-config-custom.yaml merged in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub struct ProxyConfigPreview {
+    pub yaml: String,
+    pub lines: Vec<ProxyConfigPreviewLine>,
+    /// Paths set or replaced by the user override
+    pub overrides: Vec<String>,
+    pub custom_config_path: String,
+}
+
+/// Emitted as "proxy-crashed" when the sidecar exits without being stopped, or a
+/// restart after a crash fails
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub struct ProxyCrash {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub exit_code: Option<i32>,
+    /// Signal that ended the process (Unix)
+    pub signal: Option<i32>,
+    /// "exited with code 1", "failed to restart: ...", ...
+    pub message: String,
+    /// Last lines the sidecar wrote to stderr
+    pub stderr_tail: Vec<String>,
+    /// Exits within the crash-loop window, this one included
+    pub recent_exits: u32,
+    /// Milliseconds until the next start; None when the proxy stays stopped
+    pub restart_in_ms: Option<u64>,
+    /// The proxy stays stopped because it exited too often
+    pub crash_loop: bool,
+}
+
+/// Emitted as "proxy-restarted" when the sidecar is back after a crash
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub struct ProxyRestart {
+    pub status: ProxyStatus,
+    /// Exit code of the crash that caused the restart
+    pub exit_code: Option<i32>,
+    pub recent_exits: u32,
+}
+
+/// A Management API setting start_proxy couldn't push, emitted in a list as
+/// "proxy-settings-failed"
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagementSettingError {
+    pub setting: String,
+    pub error: String,
+}
+
+/// How a config change reaches a running sidecar, from least to most disruptive
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigApplyMethod {
+    /// ProxyPal-only setting, the sidecar doesn't see it
+    None,
+    /// Pushed through a Management API setter
+    ManagementApi,
+    /// proxy-config.yaml is rewritten and the sidecar's file watcher picks it up
+    FileReload,
+    /// The sidecar has to be restarted
+    Restart,
+}
+
+/// A changed top-level AppConfig field (camelCase name) and how it gets applied
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFieldChange {
+    pub field: String,
+    pub method: ConfigApplyMethod,
+}
+
+/// Result of apply_config, also emitted as the "config-applied" event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigApplyResult {
+    /// The most disruptive method that was actually used
+    pub method: ConfigApplyMethod,
+    pub changes: Vec<ConfigFieldChange>,
+    pub proxy_running: bool,
+    pub message: String,
+}
+
+/// apply_config put the previous config back because the proxy didn't restart with the
+/// new one, emitted as "config-rolled-back"
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub struct ConfigRollback {
+    /// Why the restart with the new config failed
+    pub error: String,
+    /// The previous config is saved again
+    pub restored: bool,
+    /// The proxy runs again with the previous config
+    pub proxy_running: bool,
+    pub message: String,
+}
+
+/// The proxy or Copilot bridge moved to a free port, emitted as "port-changed"
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PortChange {
+    /// "proxy" or "copilot"
+    pub service: String,
+    pub from: u16,
+    pub to: u16,
+    /// Ids of SSH tunnels whose local_port followed the move
+    pub ssh_tunnels: Vec<String>,
+    /// Ids of Cloudflare tunnels whose local_port followed the move
+    pub cloudflare_tunnels: Vec<String>,
+    /// Agents whose settings were rewritten for the new endpoint
+    pub reconfigured_agents: Vec<String>,
+    /// Agents that could not be reconfigured, with the error
+    pub agent_errors: Vec<String>,
+}
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -5,25 +6,28 @@ use std::collections::HashMap;
 /// Format: { "models": { "model_name": { "quotaInfo": { ... } } } }
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct AntigravityModelsResponse {
     pub models: Option<HashMap<String, AntigravityModelInfo>>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct AntigravityModelInfo {
     pub quota_info: Option<QuotaInfo>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct QuotaInfo {
     pub remaining_fraction: Option<f64>,
     pub reset_time: Option<String>,
 }
 
 /// Simplified quota data for frontend
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelQuota {
     pub model: String,
@@ -32,7 +36,7 @@ pub struct ModelQuota {
     pub reset_time: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AntigravityQuotaResult {
     pub account_email: String,
@@ -42,7 +46,7 @@ pub struct AntigravityQuotaResult {
 }
 
 /// Codex/ChatGPT Usage API Types (from chatgpt.com/backend-api/wham/usage)
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CodexQuotaResult {
     pub account_email: String,
@@ -63,7 +67,7 @@ pub struct CodexQuotaResult {
 }
 
 /// Copilot/GitHub Usage API Types (from api.github.com/copilot_internal/user)
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CopilotQuotaResult {
     pub account_login: String,
@@ -78,7 +82,7 @@ pub struct CopilotQuotaResult {
 }
 
 /// Claude/Anthropic Usage API Types (from api.anthropic.com/api/oauth/usage)
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ClaudeQuotaResult {
     pub account_email: String,
@@ -98,7 +102,7 @@ pub struct ClaudeQuotaResult {
 }
 
 /// Kiro Usage API Types (from kiro.dev/api/usage)
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct KiroQuotaResult {
     pub account_email: String,
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Result of get_config_schema: JSON Schemas (draft-07) for hand-edited config files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSchemas {
+    /// Schema of config.json
+    pub app_config: serde_json::Value,
+    /// Schema of proxy-config.yaml and proxy-config-custom.yaml
+    pub proxy_config: serde_json::Value,
+    /// Where app_config was written (config.schema.json next to config.json)
+    pub app_config_path: String,
+    /// Where proxy_config was written
+    pub proxy_config_path: String,
+}
@@ -31,6 +31,7 @@ impl ThinkingBudgetSettings {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub struct ReasoningEffortSettings {
     pub level: String,
 }
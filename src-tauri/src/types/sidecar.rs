@@ -0,0 +1,43 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A CLIProxyAPI binary installed into the config dir next to the bundled one
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledSidecar {
+    /// x.y.z, also the directory it lives in
+    pub version: String,
+    /// Lowercase hex SHA-256 of the binary, checked again before every start
+    pub sha256: String,
+    /// File path or URL it was installed from
+    pub source: String,
+    pub size: u64,
+    /// Unix milliseconds
+    pub installed_at: u64,
+}
+
+/// Installed CLIProxyAPI versions and which one start_proxy launches.
+/// Saved as sidecars/sidecars.json in the config dir.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarInventory {
+    /// Version start_proxy launches, the bundled binary when None
+    #[serde(default)]
+    pub selected: Option<String>,
+    /// What was selected before, switched back to when the selected version doesn't
+    /// become ready. None is the bundled binary.
+    #[serde(default)]
+    pub previous: Option<String>,
+    #[serde(default)]
+    pub installed: Vec<InstalledSidecar>,
+}
+
+/// Emitted as "sidecar-rolled-back" when a selected version failed to start
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarRollback {
+    pub from: String,
+    /// None is the bundled binary
+    pub to: Option<String>,
+    pub error: String,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// A saved copy of config.json (and the proxy-config.yaml it generates) in snapshots/<id>/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSnapshot {
+    pub id: String,
+    pub created_at: u64,
+    /// Top-level fields that differ from the previous snapshot
+    pub changed_fields: Vec<String>,
+    /// Why the snapshot was taken, e.g. "save" or "restore of <id>"
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub has_proxy_config: bool,
+}
+
+/// One changed value between two configs. Secret values are masked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFieldDiff {
+    /// camelCase JSON path, e.g. "claudeApiKeys[0].baseUrl"
+    pub path: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
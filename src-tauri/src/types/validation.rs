@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// A single problem found in an AppConfig, addressed by its camelCase JSON path
+/// (e.g. "claudeApiKeys[1].baseUrl")
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+}
+
+/// Result of validate_config. Errors block save/start, warnings are informational.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValidation {
+    pub valid: bool,
+    pub errors: Vec<ConfigIssue>,
+    pub warnings: Vec<ConfigIssue>,
+}
+
+impl ConfigValidation {
+    /// Human-readable summary of the errors, used when a command refuses a config
+    pub fn error_summary(&self) -> String {
+        let lines: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("- {}: {}", e.field, e.message))
+            .collect();
+        format!("Invalid configuration:\n{}", lines.join("\n"))
+    }
+}
+
+/// What happened the last time config.json was read
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigLoadStatus {
+    /// Set when config.json could not be read or parsed. Saving is refused until
+    /// the file is fixed and reloaded, or discarded.
+    pub error: Option<String>,
+    /// Copy of the unreadable config.json
+    pub corrupt_copy_path: Option<String>,
+    /// Migration steps that ran, e.g. "v2: move ampOpenaiProvider into ..."
+    pub migrations: Vec<String>,
+    /// Copies of config.json written before migrating or dropping fields
+    pub backup_paths: Vec<String>,
+    /// Fields that failed to load and were reset to their defaults
+    pub warnings: Vec<String>,
+}
+
+impl ConfigLoadStatus {
+    pub const fn new() -> Self {
+        Self {
+            error: None,
+            corrupt_copy_path: None,
+            migrations: Vec::new(),
+            backup_paths: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Utility functions for provider detection, model extraction, and cost estimation.
 
 /// Estimate cost based on model and tokens (pricing per 1M tokens)
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 pub fn estimate_request_cost(model: &str, tokens_in: u32, tokens_out: u32) -> f64 {
     let (input_rate, output_rate) = match model.to_lowercase().as_str() {
         // Claude models
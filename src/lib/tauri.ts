@@ -14,6 +14,36 @@ export interface ProxyStatus {
 	running: boolean;
 	port: number;
 	endpoint: string;
+	version?: string | null; // CLIProxyAPI version, once the running proxy reported it
+}
+
+// Emitted as "proxy-crashed" when the proxy exits without being stopped
+export interface ProxyCrash {
+	timestamp: number; // ms since epoch
+	exitCode: number | null;
+	signal: number | null;
+	message: string; // "exited with code 1", "failed to restart: ..."
+	stderrTail: string[];
+	recentExits: number; // Exits within the crash-loop window, this one included
+	restartInMs: number | null; // null when the proxy stays stopped
+	crashLoop: boolean; // Stopped because it exited too often
+}
+
+// Emitted as "proxy-restarted" when the proxy is back after a crash
+export interface ProxyRestart {
+	status: ProxyStatus;
+	exitCode: number | null;
+	recentExits: number;
+}
+
+// A Management API setting that couldn't be pushed when the proxy started
+export interface ManagementSettingError {
+	setting: string;
+	error: string;
+}
+
+export async function getProxyCrashes(): Promise<ProxyCrash[]> {
+	return invoke("get_proxy_crashes");
 }
 
 export async function getProxyStatus(): Promise<ProxyStatus> {
@@ -302,6 +332,26 @@ export interface CopilotConfig {
 	githubToken: string;
 	rateLimit?: number;
 	rateLimitWait: boolean;
+	hiddenModels?: string[]; // "*" wildcards allowed
+	extraAliases?: CopilotModelAlias[];
+	reasoningSuffixes?: CopilotReasoningSuffixes[];
+}
+
+export interface CopilotModelAlias {
+	name: string; // copilot model
+	alias: string; // what clients request
+}
+
+export interface CopilotReasoningSuffixes {
+	pattern: string; // e.g. "gpt-5*"
+	suffixes: string[];
+}
+
+// Models served by copilot-api, and where the list came from
+export interface CopilotModelList {
+	models: string[];
+	source: "live" | "cache" | "builtin";
+	fetchedAt?: number;
 }
 
 // Copilot status
@@ -366,6 +416,15 @@ export interface AppConfig {
 	cloudflareConfigs?: CloudflareConfig[];
 	disableControlPanel?: boolean; // Hide CLIProxyAPI's web management UI
 	sidebarPinned?: boolean;
+	snapshotLimit?: number; // Config snapshots kept for rollback (0 disables)
+	allowedModels?: string[]; // Models offered to the UI and agents, "*" wildcards (empty = all)
+	payloadRules?: PayloadRule[]; // Rendered into the payload section of proxy-config.yaml
+	autoRestartProxy?: boolean; // Restart the proxy with backoff when it crashes
+	crashLoopMaxExits?: number; // Stop restarting after this many exits...
+	crashLoopWindowMinutes?: number; // ...within this many minutes
+	proxyReadyTimeoutSecs?: number; // How long starting the proxy may take
+	autoSelectPort?: boolean; // Move to the next free port when another program holds ours
+	persistProcessOutput?: boolean; // Save each process's output when it exits
 }
 
 export async function getConfig(): Promise<AppConfig> {
@@ -380,6 +439,296 @@ export async function reloadConfig(): Promise<AppConfig> {
 	return invoke("reload_config");
 }
 
+export interface ConfigLoadStatus {
+	error: string | null;
+	corruptCopyPath: string | null;
+	migrations: string[];
+	backupPaths: string[];
+	warnings: string[];
+}
+
+export async function getConfigLoadStatus(): Promise<ConfigLoadStatus> {
+	return invoke("get_config_load_status");
+}
+
+// Where each config value comes from: organization baseline, user or launch override
+export type ConfigValueSource =
+	| "default"
+	| "baseline"
+	| "user"
+	| "override"
+	| "locked";
+
+// PROXYPAL_* environment variable or --set argument, never saved
+export interface ConfigOverride {
+	field: string; // e.g. "port" or "copilot.port"
+	source: string; // e.g. "PROXYPAL_PORT" or "--set port"
+}
+
+export interface ConfigSources {
+	baselinePath: string | null;
+	baselineError: string | null;
+	locked: (keyof AppConfig)[];
+	overrides: ConfigOverride[];
+	fields: Partial<Record<keyof AppConfig, ConfigValueSource>>;
+}
+
+export async function getConfigSources(): Promise<ConfigSources> {
+	return invoke("get_config_sources");
+}
+
+// Secrets are included in plaintext, or blanked when includeSecrets is false
+export async function exportConfig(
+	includeSecrets: boolean,
+): Promise<AppConfig> {
+	return invoke("export_config", { includeSecrets });
+}
+
+// Replace an unreadable config.json with the settings in use (a copy is kept)
+export async function discardCorruptConfig(): Promise<AppConfig> {
+	return invoke("discard_corrupt_config");
+}
+
+export interface ConfigIssue {
+	field: string; // camelCase path, e.g. "claudeApiKeys[1].baseUrl"
+	message: string;
+}
+
+export interface ConfigValidation {
+	valid: boolean;
+	errors: ConfigIssue[];
+	warnings: ConfigIssue[];
+}
+
+export async function validateConfig(
+	config: AppConfig,
+): Promise<ConfigValidation> {
+	return invoke("validate_config", { config });
+}
+
+export interface ProxyConfigPreviewLine {
+	text: string;
+	path: string;
+	fromCustom: boolean;
+}
+
+export interface ProxyConfigPreview {
+	yaml: string;
+	lines: ProxyConfigPreviewLine[];
+	overrides: string[];
+	customConfigPath: string;
+}
+
+export async function previewProxyConfig(): Promise<ProxyConfigPreview> {
+	return invoke("preview_proxy_config");
+}
+
+export type ConfigApplyMethod =
+	| "none"
+	| "management-api"
+	| "file-reload"
+	| "restart";
+
+export interface ConfigFieldChange {
+	field: string;
+	method: ConfigApplyMethod;
+}
+
+export interface ConfigApplyResult {
+	method: ConfigApplyMethod;
+	changes: ConfigFieldChange[];
+	proxyRunning: boolean;
+	message: string;
+}
+
+// Save the config and apply it to the running proxy, restarting only when needed
+export async function applyConfig(
+	config: AppConfig,
+): Promise<ConfigApplyResult> {
+	return invoke("apply_config", { config });
+}
+
+// Config snapshots
+export interface ConfigSnapshot {
+	id: string;
+	createdAt: number;
+	changedFields: string[];
+	reason: string;
+	hasProxyConfig: boolean;
+}
+
+export interface ConfigFieldDiff {
+	path: string;
+	before: unknown;
+	after: unknown;
+}
+
+export async function listConfigSnapshots(): Promise<ConfigSnapshot[]> {
+	return invoke("list_config_snapshots");
+}
+
+// Without `to`, compares against the current config
+export async function diffConfigSnapshots(
+	from: string,
+	to?: string,
+): Promise<ConfigFieldDiff[]> {
+	return invoke("diff_config_snapshots", { from, to });
+}
+
+export async function restoreConfigSnapshot(
+	id: string,
+): Promise<ConfigApplyResult> {
+	return invoke("restore_config_snapshot", { id });
+}
+
+// Payload rules. String param values may be "{{thinkingBudget}}" or
+// "{{geminiThinkingLevel}}", filled from the thinking settings.
+export type PayloadRuleMode = "default" | "override";
+
+export interface PayloadRule {
+	id: string; // Empty creates a new rule
+	description: string;
+	enabled: boolean;
+	models: string[]; // "*" wildcards allowed
+	protocol?: string; // claude, openai, gemini, ...
+	mode: PayloadRuleMode; // default: only when unset by the request
+	params: Record<string, unknown>; // JSON path in the request body -> value
+}
+
+export async function listPayloadRules(): Promise<PayloadRule[]> {
+	return invoke("list_payload_rules");
+}
+
+export async function savePayloadRule(
+	rule: PayloadRule,
+): Promise<ConfigApplyResult> {
+	return invoke("save_payload_rule", { rule });
+}
+
+export async function deletePayloadRule(
+	id: string,
+): Promise<ConfigApplyResult> {
+	return invoke("delete_payload_rule", { id });
+}
+
+// Restore the built-in rules, dropping custom ones
+export async function resetPayloadRules(): Promise<ConfigApplyResult> {
+	return invoke("reset_payload_rules");
+}
+
+// Team config bundles
+export type BundleImportMode = "merge" | "replace" | "skip";
+
+export interface ConfigBundle {
+	format: string;
+	version: number;
+	createdAt: number;
+	appVersion: string;
+	includesSecrets: boolean;
+	sections: Record<string, Partial<AppConfig>>;
+}
+
+export interface BundleConflict {
+	field: string;
+	item: string | null;
+	changes: ConfigFieldDiff[];
+}
+
+export interface BundleSectionPreview {
+	id: string;
+	label: string;
+	newItems: string[];
+	conflicts: BundleConflict[];
+	removedOnReplace: string[];
+}
+
+export interface BundlePreview {
+	version: number;
+	createdAt: number;
+	appVersion: string;
+	includesSecrets: boolean;
+	sections: BundleSectionPreview[];
+}
+
+// API keys are only included (in plaintext) when includeSecrets is true
+export async function exportConfigBundle(
+	includeSecrets: boolean,
+): Promise<ConfigBundle> {
+	return invoke("export_config_bundle", { includeSecrets });
+}
+
+export async function previewConfigBundle(
+	bundle: ConfigBundle,
+): Promise<BundlePreview> {
+	return invoke("preview_config_bundle", { bundle });
+}
+
+// Sections missing from `choices` are skipped
+export async function importConfigBundle(
+	bundle: ConfigBundle,
+	choices: Record<string, BundleImportMode>,
+): Promise<ConfigApplyResult> {
+	return invoke("import_config_bundle", { bundle, choices });
+}
+
+// Configuration profiles
+export interface ConfigProfile {
+	name: string;
+	description: string;
+	overlay: Partial<AppConfig>;
+	createdAt: number;
+	updatedAt: number;
+}
+
+export interface ProfileSummary {
+	name: string;
+	description: string;
+	fields: string[];
+	active: boolean;
+	updatedAt: number;
+}
+
+export interface ProfileActivation {
+	profile: string;
+	apply: ConfigApplyResult;
+	reconfiguredAgents: string[];
+	agentErrors: string[];
+}
+
+export async function listProfiles(): Promise<ProfileSummary[]> {
+	return invoke("list_profiles");
+}
+
+export async function getProfile(name: string): Promise<ConfigProfile> {
+	return invoke("get_profile", { name });
+}
+
+// Without an overlay the whole current config is captured
+export async function createProfile(
+	name: string,
+	description?: string,
+	overlay?: Partial<AppConfig>,
+): Promise<ConfigProfile> {
+	return invoke("create_profile", { name, description, overlay });
+}
+
+export async function cloneProfile(
+	source: string,
+	name: string,
+): Promise<ConfigProfile> {
+	return invoke("clone_profile", { source, name });
+}
+
+export async function deleteProfile(name: string): Promise<void> {
+	return invoke("delete_profile", { name });
+}
+
+export async function activateProfile(
+	name: string,
+): Promise<ProfileActivation> {
+	return invoke("activate_profile", { name });
+}
+
 // Event listeners
 export interface OAuthCallback {
 	provider: Provider;
@@ -394,6 +743,186 @@ export async function onProxyStatusChanged(
 	});
 }
 
+export async function onProxyCrashed(
+	callback: (crash: ProxyCrash) => void,
+): Promise<UnlistenFn> {
+	return listen<ProxyCrash>("proxy-crashed", (event) => {
+		callback(event.payload);
+	});
+}
+
+export async function onProxyRestarted(
+	callback: (restart: ProxyRestart) => void,
+): Promise<UnlistenFn> {
+	return listen<ProxyRestart>("proxy-restarted", (event) => {
+		callback(event.payload);
+	});
+}
+
+export type SupervisedProcess =
+	| "cli-proxy-api"
+	| "copilot-api"
+	| "ssh"
+	| "cloudflared";
+
+// A line a supervised process printed
+export interface OutputLine {
+	timestamp: number; // Unix ms
+	process: SupervisedProcess;
+	source: string | null; // Tunnel id for ssh and cloudflared
+	stream: "stdout" | "stderr";
+	text: string;
+}
+
+export interface ProcessOutput {
+	process: SupervisedProcess;
+	lines: OutputLine[]; // Oldest first
+	fromPreviousRun: boolean; // Saved by the run before this launch
+}
+
+export async function getProcessOutput(
+	process: SupervisedProcess,
+	lines?: number,
+): Promise<ProcessOutput> {
+	return invoke("get_process_output", { process, lines });
+}
+
+export async function onProcessOutput(
+	callback: (line: OutputLine) => void,
+): Promise<UnlistenFn> {
+	return listen<OutputLine>("process-output", (event) => {
+		callback(event.payload);
+	});
+}
+
+// A ProxyPal feature that needs a minimum CLIProxyAPI version
+export interface SidecarFeatureSupport {
+	id: string; // "auth-status", "auth-file-toggle"
+	description: string;
+	minVersion: string;
+	supported: boolean | null; // null while the running version is unknown
+	message: string | null; // Why it's unavailable
+}
+
+export async function getSidecarFeatures(): Promise<SidecarFeatureSupport[]> {
+	return invoke("get_sidecar_features");
+}
+
+// The proxy or Copilot bridge moved off a port another program holds
+export interface PortChange {
+	service: "proxy" | "copilot";
+	from: number;
+	to: number;
+	sshTunnels: string[]; // Ids of tunnels whose localPort followed the move
+	cloudflareTunnels: string[];
+	reconfiguredAgents: string[];
+	agentErrors: string[];
+}
+
+export async function onPortChanged(
+	callback: (change: PortChange) => void,
+): Promise<UnlistenFn> {
+	return listen<PortChange>("port-changed", (event) => {
+		callback(event.payload);
+	});
+}
+
+// CLIProxyAPI versions installed next to the bundled one
+export interface InstalledSidecar {
+	version: string;
+	sha256: string;
+	source: string; // File path or URL it was installed from
+	size: number;
+	installedAt: number; // ms since epoch
+}
+
+export interface SidecarInventory {
+	selected: string | null; // null runs the bundled binary
+	previous: string | null; // Switched back to if the selected one doesn't start
+	installed: InstalledSidecar[]; // Newest first
+}
+
+// Emitted as "sidecar-rolled-back" when a selected version failed to start
+export interface SidecarRollback {
+	from: string;
+	to: string | null; // null is the bundled binary
+	error: string;
+}
+
+export async function listSidecars(): Promise<SidecarInventory> {
+	return invoke("list_sidecars");
+}
+
+// source is a file path or https URL; URLs need sha256. version is needed when the
+// file name doesn't carry it
+export async function installSidecar(
+	source: string,
+	sha256?: string,
+	version?: string,
+): Promise<InstalledSidecar> {
+	return invoke("install_sidecar", { source, sha256, version });
+}
+
+// Takes effect on the next proxy start; null selects the bundled binary
+export async function selectSidecar(
+	version: string | null,
+): Promise<SidecarInventory> {
+	return invoke("select_sidecar", { version });
+}
+
+export async function removeSidecar(version: string): Promise<SidecarInventory> {
+	return invoke("remove_sidecar", { version });
+}
+
+export async function onSidecarRolledBack(
+	callback: (rollback: SidecarRollback) => void,
+): Promise<UnlistenFn> {
+	return listen<SidecarRollback>("sidecar-rolled-back", (event) => {
+		callback(event.payload);
+	});
+}
+
+export async function onProxySettingsFailed(
+	callback: (failures: ManagementSettingError[]) => void,
+): Promise<UnlistenFn> {
+	return listen<ManagementSettingError[]>("proxy-settings-failed", (event) => {
+		callback(event.payload);
+	});
+}
+
+export async function onConfigApplied(
+	callback: (result: ConfigApplyResult) => void,
+): Promise<UnlistenFn> {
+	return listen<ConfigApplyResult>("config-applied", (event) => {
+		callback(event.payload);
+	});
+}
+
+// Emitted as "config-rolled-back" when the proxy didn't restart with a new config
+// and the previous one was put back
+export interface ConfigRollback {
+	error: string;
+	restored: boolean;
+	proxyRunning: boolean;
+	message: string;
+}
+
+export async function onConfigRolledBack(
+	callback: (rollback: ConfigRollback) => void,
+): Promise<UnlistenFn> {
+	return listen<ConfigRollback>("config-rolled-back", (event) => {
+		callback(event.payload);
+	});
+}
+
+export async function onProfileActivated(
+	callback: (activation: ProfileActivation) => void,
+): Promise<UnlistenFn> {
+	return listen<ProfileActivation>("profile-activated", (event) => {
+		callback(event.payload);
+	});
+}
+
 export async function onAuthStatusChanged(
 	callback: (status: AuthStatus) => void,
 ): Promise<UnlistenFn> {
@@ -426,6 +955,10 @@ export async function getCopilotStatus(): Promise<CopilotStatus> {
 	return invoke("get_copilot_status");
 }
 
+export async function refreshCopilotModels(): Promise<CopilotModelList> {
+	return invoke("refresh_copilot_models");
+}
+
 export async function startCopilot(): Promise<CopilotStatus> {
 	return invoke("start_copilot");
 }
@@ -1204,10 +1737,23 @@ export async function getConfigYaml(): Promise<string> {
 	return invoke("get_config_yaml");
 }
 
+// Rejected with the schema errors when the YAML doesn't match the proxy config schema
 export async function setConfigYaml(yaml: string): Promise<void> {
 	return invoke("save_config_yaml", { yaml });
 }
 
+// JSON Schemas (draft-07) of config.json and proxy-config.yaml, also written next to config.json
+export interface ConfigSchemas {
+	appConfig: Record<string, unknown>;
+	proxyConfig: Record<string, unknown>;
+	appConfigPath: string;
+	proxyConfigPath: string;
+}
+
+export async function getConfigSchema(): Promise<ConfigSchemas> {
+	return invoke("get_config_schema");
+}
+
 // Request Error Logs - view error-specific logs
 export async function getRequestErrorLogs(): Promise<string[]> {
 	return invoke("get_request_error_logs");
@@ -13,8 +13,12 @@ import {
 	clearLogs,
 	getLogs,
 	getRequestErrorLogContent,
+	getProcessOutput,
 	getRequestErrorLogs,
 	type LogEntry,
+	type OutputLine,
+	onProcessOutput,
+	type SupervisedProcess,
 } from "../lib/tauri";
 import { appStore } from "../stores/app";
 import { toastStore } from "../stores/toast";
@@ -32,7 +36,16 @@ const levelColors: Record<string, string> = {
 const INITIAL_LOG_FETCH = 200;
 const DISPLAY_CHUNK_SIZE = 100;
 
-type LogTab = "server" | "errors";
+type LogTab = "server" | "errors" | "processes";
+
+const PROCESSES: { id: SupervisedProcess; label: string }[] = [
+	{ id: "cli-proxy-api", label: "CLIProxyAPI" },
+	{ id: "copilot-api", label: "Copilot" },
+	{ id: "ssh", label: "SSH" },
+	{ id: "cloudflared", label: "Cloudflare" },
+];
+// Lines kept on screen for the process tab
+const PROCESS_LINES = 500;
 
 export function LogViewerPage() {
 	const { proxyStatus } = appStore;
@@ -52,6 +65,12 @@ export function LogViewerPage() {
 	const [errorLogContent, setErrorLogContent] = createSignal<string>("");
 	const [loadingErrorLogs, setLoadingErrorLogs] = createSignal(false);
 
+	// Process output state
+	const [selectedProcess, setSelectedProcess] = createSignal<SupervisedProcess>("cli-proxy-api");
+	const [processLines, setProcessLines] = createSignal<OutputLine[]>([]);
+	const [fromPreviousRun, setFromPreviousRun] = createSignal(false);
+	let processContainerRef: HTMLDivElement | undefined;
+
 	let refreshInterval: ReturnType<typeof setInterval> | null = null;
 	let logContainerRef: HTMLDivElement | undefined;
 	let prevRunning = false;
@@ -222,6 +241,38 @@ export function LogViewerPage() {
 		}
 	});
 
+	// Load a process's buffered output when its tab or the process changes
+	createEffect(async () => {
+		if (activeTab() !== "processes") return;
+		const selected = selectedProcess();
+		try {
+			const output = await getProcessOutput(selected, PROCESS_LINES);
+			if (selectedProcess() !== selected) return;
+			setProcessLines(output.lines);
+			setFromPreviousRun(output.fromPreviousRun);
+			requestAnimationFrame(() => {
+				if (processContainerRef) {
+					processContainerRef.scrollTop = processContainerRef.scrollHeight;
+				}
+			});
+		} catch (err) {
+			toastStore.error(`Failed to load ${selected} output: ${err}`);
+		}
+	});
+
+	onMount(async () => {
+		const unlisten = await onProcessOutput((line) => {
+			if (line.process !== selectedProcess()) return;
+			if (fromPreviousRun()) {
+				setFromPreviousRun(false);
+				setProcessLines([line]);
+				return;
+			}
+			setProcessLines((prev) => [...prev.slice(-(PROCESS_LINES - 1)), line]);
+		});
+		onCleanup(unlisten);
+	});
+
 	const handleDownload = () => {
 		const content = logs()
 			.map((log) => {
@@ -289,6 +340,16 @@ export function LogViewerPage() {
 							>
 								Errors
 							</button>
+							<button
+								onClick={() => setActiveTab("processes")}
+								class={`px-3 py-1 text-xs font-medium rounded-md transition-colors ${
+									activeTab() === "processes"
+										? "bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 shadow-sm"
+										: "text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-100"
+								}`}
+							>
+								Processes
+							</button>
 						</div>
 
 						<Show when={loading() || loadingErrorLogs()}>
@@ -407,7 +468,7 @@ export function LogViewerPage() {
 			{/* Content */}
 			<main class="flex-1 flex flex-col overflow-hidden">
 				{/* Proxy not running warning */}
-				<Show when={!proxyStatus().running}>
+				<Show when={!proxyStatus().running && activeTab() !== "processes"}>
 					<div class="flex-1 flex items-center justify-center p-4">
 						<EmptyState
 							icon={
@@ -431,6 +492,64 @@ export function LogViewerPage() {
 					</div>
 				</Show>
 
+				{/* Process Output Tab, also useful when the proxy failed to start */}
+				<Show when={activeTab() === "processes"}>
+					<div class="px-4 sm:px-6 py-3 border-b border-gray-200 dark:border-gray-800 flex flex-wrap items-center gap-3">
+						<div class="flex items-center gap-1">
+							<For each={PROCESSES}>
+								{(p) => (
+									<button
+										onClick={() => setSelectedProcess(p.id)}
+										class={`px-2.5 py-1 rounded-lg text-xs font-medium transition-colors ${
+											selectedProcess() === p.id
+												? "bg-gray-200 dark:bg-gray-700 text-gray-900 dark:text-gray-100"
+												: "text-gray-600 dark:text-gray-400 hover:bg-gray-100 dark:hover:bg-gray-800"
+										}`}
+									>
+										{p.label}
+									</button>
+								)}
+							</For>
+						</div>
+						<Show when={fromPreviousRun()}>
+							<span class="text-xs text-gray-400 dark:text-gray-500">
+								Saved from the previous run
+							</span>
+						</Show>
+					</div>
+					<div
+						ref={processContainerRef}
+						class="flex-1 overflow-y-auto font-mono text-xs bg-gray-50 dark:bg-gray-900"
+					>
+						<Show
+							when={processLines().length > 0}
+							fallback={
+								<div class="text-xs text-gray-400 dark:text-gray-500 p-4 text-center">
+									Nothing printed yet
+								</div>
+							}
+						>
+							<div class="p-2 space-y-0.5">
+								<For each={processLines()}>
+									{(line) => (
+										<div class="flex items-start gap-2 py-0.5 px-2 hover:bg-gray-100 dark:hover:bg-gray-800 rounded">
+											<span class="text-gray-400 dark:text-gray-500 shrink-0 text-[11px] w-20 tabular-nums">
+												{new Date(line.timestamp).toLocaleTimeString()}
+											</span>
+											<Show when={line.source}>
+												<span class="text-gray-500 shrink-0">{line.source}</span>
+											</Show>
+											<span class="text-gray-700 dark:text-gray-300 break-words whitespace-pre-wrap flex-1 min-w-0">
+												{line.text}
+											</span>
+										</div>
+									)}
+								</For>
+							</div>
+						</Show>
+					</div>
+				</Show>
+
 				<Show when={proxyStatus().running}>
 					{/* Server Logs Tab */}
 					<Show when={activeTab() === "server"}>
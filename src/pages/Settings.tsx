@@ -35,6 +35,8 @@ import {
 	AMP_MODEL_SLOTS,
 	type AvailableModel,
 	appendToShellProfile,
+	applyConfig,
+	type ConfigSources,
 	type CopilotApiDetection,
 	checkForUpdates,
 	configureCliAgent,
@@ -48,6 +50,7 @@ import {
 	getClaudeCodeSettings,
 	getCloseToTray,
 	getConfig,
+	getConfigSources,
 	getConfigYaml,
 	getForceModelMappings,
 	getGptReasoningModels,
@@ -55,15 +58,21 @@ import {
 	getMaxRetryInterval,
 	getOAuthExcludedModels,
 	getReasoningEffortSettings,
+	getSidecarFeatures,
 	getThinkingBudgetSettings,
 	getThinkingBudgetTokens,
 	getWebsocketAuth,
+	installSidecar,
 	isUpdaterSupported,
+	listSidecars,
 	type OAuthExcludedModels,
 	type ReasoningEffortLevel,
+	removeSidecar,
 	saveCloudflareConfig,
 	saveConfig,
 	saveSshConfig,
+	type SidecarFeatureSupport,
+	type SidecarInventory,
 	setClaudeCodeModel,
 	setCloseToTray,
 	setCloudflareConnection,
@@ -73,11 +82,10 @@ import {
 	setMaxRetryInterval,
 	setOAuthExcludedModels,
 	setReasoningEffortSettings,
+	selectSidecar,
 	setSshConnection,
 	setThinkingBudgetSettings,
 	setWebsocketAuth,
-	startProxy,
-	stopProxy,
 	type ThinkingBudgetSettings,
 	testOpenAIProvider,
 	type UpdateInfo,
@@ -169,6 +177,9 @@ export function SettingsPage() {
 	const [activeTab, setActiveTab] = createSignal<SettingsTab>("general");
 	const [appVersion, setAppVersion] = createSignal("0.0.0");
 	const [models, setModels] = createSignal<AvailableModel[]>([]);
+	const [configSources, setConfigSources] = createSignal<ConfigSources | null>(
+		null,
+	);
 	const [agents, setAgents] = createSignal<AgentStatus[]>([]);
 
 	// GPT base models fetched from backend (single source of truth)
@@ -225,6 +236,13 @@ export function SettingsPage() {
 			console.error("Failed to get app version:", error);
 		}
 
+		// Fields locked by an organization baseline are read-only
+		try {
+			setConfigSources(await getConfigSources());
+		} catch (err) {
+			console.error("Failed to load config sources:", err);
+		}
+
 		// Load GPT reasoning models from backend (single source of truth)
 		try {
 			const gptModels = await getGptReasoningModels();
@@ -530,6 +548,72 @@ export function SettingsPage() {
 	const [cfLocalPort, setCfLocalPort] = createSignal(8317);
 	const [cfAdding, setCfAdding] = createSignal(false);
 
+	// CLIProxyAPI versions installed next to the bundled one
+	const [sidecars, setSidecars] = createSignal<SidecarInventory | null>(null);
+	const [sidecarSource, setSidecarSource] = createSignal("");
+	const [sidecarSha256, setSidecarSha256] = createSignal("");
+	const [sidecarVersion, setSidecarVersion] = createSignal("");
+	const [installingSidecar, setInstallingSidecar] = createSignal(false);
+
+	onMount(async () => {
+		try {
+			setSidecars(await listSidecars());
+		} catch (error) {
+			console.error("Failed to list CLIProxyAPI versions:", error);
+		}
+	});
+
+	const handlePickSidecarFile = async () => {
+		try {
+			const file = await open({ multiple: false });
+			if (file) setSidecarSource(file as string);
+		} catch (e) {
+			console.error(e);
+		}
+	};
+
+	const handleInstallSidecar = async () => {
+		setInstallingSidecar(true);
+		try {
+			const installed = await installSidecar(
+				sidecarSource().trim(),
+				sidecarSha256().trim() || undefined,
+				sidecarVersion().trim() || undefined,
+			);
+			setSidecars(await listSidecars());
+			setSidecarSource("");
+			setSidecarSha256("");
+			setSidecarVersion("");
+			toastStore.success(
+				`Installed CLIProxyAPI ${installed.version}`,
+				`SHA-256 ${installed.sha256}`,
+			);
+		} catch (error) {
+			toastStore.error("Failed to install CLIProxyAPI", String(error));
+		} finally {
+			setInstallingSidecar(false);
+		}
+	};
+
+	const handleSelectSidecar = async (version: string | null) => {
+		try {
+			setSidecars(await selectSidecar(version));
+			toastStore.success(
+				`${version ? `CLIProxyAPI ${version}` : "The bundled CLIProxyAPI"} runs from the next proxy start`,
+			);
+		} catch (error) {
+			toastStore.error("Failed to select CLIProxyAPI version", String(error));
+		}
+	};
+
+	const handleRemoveSidecar = async (version: string) => {
+		try {
+			setSidecars(await removeSidecar(version));
+		} catch (error) {
+			toastStore.error("Failed to remove CLIProxyAPI version", String(error));
+		}
+	};
+
 	// SSH Handlers
 	const handlePickKeyFile = async () => {
 		try {
@@ -732,6 +816,24 @@ export function SettingsPage() {
 		}
 	};
 
+	// Features the running CLIProxyAPI is too old for
+	const [unsupportedFeatures, setUnsupportedFeatures] = createSignal<
+		SidecarFeatureSupport[]
+	>([]);
+	createEffect(async () => {
+		const version = appStore.proxyStatus().version;
+		if (!version) {
+			setUnsupportedFeatures([]);
+			return;
+		}
+		try {
+			const features = await getSidecarFeatures();
+			setUnsupportedFeatures(features.filter((f) => f.supported === false));
+		} catch (error) {
+			console.error("Failed to fetch sidecar features:", error);
+		}
+	});
+
 	// Fetch available models and runtime settings when proxy is running
 	createEffect(async () => {
 		const proxyRunning = appStore.proxyStatus().running;
@@ -1093,13 +1195,8 @@ export function SettingsPage() {
 
 		setSaving(true);
 		try {
-			await saveConfig(newConfig);
-			// Restart proxy to regenerate config YAML with updated mappings
-			if (appStore.proxyStatus().running) {
-				await stopProxy();
-				await new Promise((resolve) => setTimeout(resolve, 300));
-				await startProxy();
-			}
+			// Rewrites proxy-config.yaml for a running proxy, no restart needed
+			await applyConfig(newConfig);
 			toastStore.success("Model mapping updated");
 		} catch (error) {
 			console.error("Failed to save config:", error);
@@ -1286,8 +1383,9 @@ export function SettingsPage() {
 		setSavingSlotReasoningLevels((prev) => new Set(prev).add(slotId));
 
 		try {
-			// Save the CURRENT config state to include any concurrent changes
-			await saveConfig(config());
+			// Save the CURRENT config state to include any concurrent changes.
+			// A running proxy picks the new mapping up from proxy-config.yaml.
+			await applyConfig(config());
 		} catch (error) {
 			// Revert only if the current alias still matches what we tried to save
 			// (i.e., user hasn't made other changes to this slot while save was in flight)
@@ -1312,19 +1410,6 @@ export function SettingsPage() {
 				return next;
 			});
 		}
-
-		// Proxy restart is separate - config is already persisted
-		if (appStore.proxyStatus().running) {
-			try {
-				await stopProxy();
-				await new Promise((resolve) => setTimeout(resolve, 300));
-				await startProxy();
-			} catch (error) {
-				toastStore.error(
-					`Reasoning level saved but proxy restart failed: ${error instanceof Error ? error.message : String(error)}`,
-				);
-			}
-		}
 	};
 
 	// Update an existing custom mapping
@@ -1346,13 +1431,8 @@ export function SettingsPage() {
 
 		setSaving(true);
 		try {
-			await saveConfig(newConfig);
-			// Restart proxy to regenerate config YAML with updated mappings
-			if (appStore.proxyStatus().running) {
-				await stopProxy();
-				await new Promise((resolve) => setTimeout(resolve, 300));
-				await startProxy();
-			}
+			// Rewrites proxy-config.yaml for a running proxy, no restart needed
+			await applyConfig(newConfig);
 			toastStore.success("Mapping updated");
 		} catch (error) {
 			console.error("Failed to save config:", error);
@@ -1536,26 +1616,40 @@ export function SettingsPage() {
 		return { customModels, builtInModels };
 	};
 
+	// Why a setting can't be edited here: locked by the organization baseline, or
+	// overridden for this launch (PROXYPAL_* / --set, which would win again on restart)
+	const lockReason = (key: keyof ReturnType<typeof config>) => {
+		const sources = configSources();
+		if (sources?.locked.includes(key)) {
+			return "Managed by your organization";
+		}
+		const override = sources?.overrides.find(
+			(o) => o.field.split(".")[0] === key,
+		);
+		return override ? `Set by ${override.source} for this launch` : null;
+	};
+	const isLocked = (key: keyof ReturnType<typeof config>) =>
+		lockReason(key) !== null;
+
 	const handleConfigChange = async (
 		key: keyof ReturnType<typeof config>,
 		value: boolean | number | string,
 	) => {
+		const reason = lockReason(key);
+		if (reason) {
+			toastStore.warning("This setting can't be changed", reason);
+			return;
+		}
 		const newConfig = { ...config(), [key]: value };
 		setConfig(newConfig);
 
 		// Auto-save config
 		setSaving(true);
 		try {
-			await saveConfig(newConfig);
-
-			// If management key changed and proxy is running, restart proxy to apply new key
-			if (key === "managementKey" && appStore.proxyStatus().running) {
-				toastStore.info("Restarting proxy to apply new management key...");
-				await stopProxy();
-				// Small delay to ensure config file is fully written and flushed
-				await new Promise((resolve) => setTimeout(resolve, 500));
-				await startProxy();
-				toastStore.success("Proxy restarted with new management key");
+			// Applied to the running proxy without a restart unless the field requires one
+			const result = await applyConfig(newConfig);
+			if (result.method === "restart") {
+				toastStore.success("Settings saved", result.message);
 			} else {
 				toastStore.success("Settings saved");
 			}
@@ -1763,6 +1857,39 @@ export function SettingsPage() {
 
 							<div class="border-t border-gray-200 dark:border-gray-700" />
 
+							<Switch
+								label="Restart proxy after a crash"
+								description={`Restart it with a growing delay; give up after ${config().crashLoopMaxExits ?? 5} crashes within ${config().crashLoopWindowMinutes ?? 10} minutes`}
+								checked={config().autoRestartProxy ?? true}
+								onChange={(checked) =>
+									handleConfigChange("autoRestartProxy", checked)
+								}
+							/>
+
+							<div class="border-t border-gray-200 dark:border-gray-700" />
+
+							<Switch
+								label="Pick a free port automatically"
+								description="When another program uses the proxy or Copilot port, move to the next free one and update tunnels and configured agents"
+								checked={config().autoSelectPort ?? true}
+								onChange={(checked) =>
+									handleConfigChange("autoSelectPort", checked)
+								}
+							/>
+
+							<div class="border-t border-gray-200 dark:border-gray-700" />
+
+							<Switch
+								label="Keep process output"
+								description="Save what the proxy, Copilot bridge and tunnels printed when they exit, to look at after a restart (Logs → Processes)"
+								checked={config().persistProcessOutput ?? true}
+								onChange={(checked) =>
+									handleConfigChange("persistProcessOutput", checked)
+								}
+							/>
+
+							<div class="border-t border-gray-200 dark:border-gray-700" />
+
 							<Switch
 								label="Close to tray"
 								description="Minimize to system tray instead of quitting when closing the window"
@@ -1783,6 +1910,21 @@ export function SettingsPage() {
 						</h2>
 
 						<div class="space-y-4 p-4 rounded-xl bg-gray-50 dark:bg-gray-800/50 border border-gray-200 dark:border-gray-700">
+							<Show when={appStore.proxyStatus().version}>
+								<div class="space-y-1">
+									<p class="text-xs text-gray-500 dark:text-gray-400">
+										Running CLIProxyAPI {appStore.proxyStatus().version}
+									</p>
+									<For each={unsupportedFeatures()}>
+										{(feature) => (
+											<p class="text-xs text-amber-600 dark:text-amber-400">
+												{feature.message}
+											</p>
+										)}
+									</For>
+								</div>
+							</Show>
+
 							<label class="block">
 								<span class="text-sm font-medium text-gray-700 dark:text-gray-300">
 									Port
@@ -1820,6 +1962,7 @@ export function SettingsPage() {
 									<input
 										type="checkbox"
 										checked={config().useSystemProxy}
+										disabled={isLocked("useSystemProxy")}
 										onChange={(e) =>
 											handleConfigChange(
 												"useSystemProxy",
@@ -1844,12 +1987,13 @@ export function SettingsPage() {
 										onInput={(e) =>
 											handleConfigChange("proxyUrl", e.currentTarget.value)
 										}
+										disabled={isLocked("proxyUrl")}
 										placeholder="socks5://127.0.0.1:1080"
-										class="mt-1 block w-full px-3 py-2 bg-white dark:bg-gray-900 border border-gray-300 dark:border-gray-600 rounded-lg text-sm focus:ring-2 focus:ring-brand-500 focus:border-transparent transition-smooth"
+										class="mt-1 block w-full px-3 py-2 bg-white dark:bg-gray-900 border border-gray-300 dark:border-gray-600 rounded-lg text-sm focus:ring-2 focus:ring-brand-500 focus:border-transparent transition-smooth disabled:opacity-60"
 									/>
 									<p class="mt-1 text-xs text-gray-500 dark:text-gray-400">
-										Optional SOCKS5/HTTP proxy for outbound requests (e.g.
-										socks5://host:port)
+										{lockReason("proxyUrl") ??
+											"Optional SOCKS5/HTTP proxy for outbound requests (e.g. socks5://host:port)"}
 									</p>
 								</label>
 							</Show>
@@ -2107,6 +2251,7 @@ export function SettingsPage() {
 									onChange={(e) =>
 										handleConfigChange("routingStrategy", e.currentTarget.value)
 									}
+									disabled={isLocked("routingStrategy")}
 									class="mt-1 block w-full px-3 py-2 bg-white dark:bg-gray-900 text-gray-900 dark:text-gray-100 border border-gray-300 dark:border-gray-600 rounded-lg text-sm focus:ring-2 focus:ring-brand-500 focus:border-transparent transition-smooth [&>option]:bg-white [&>option]:dark:bg-gray-900 [&>option]:text-gray-900 [&>option]:dark:text-gray-100"
 								>
 									<option value="round-robin">
@@ -3775,6 +3920,7 @@ export function SettingsPage() {
 								label="Debug Mode"
 								description="Enable verbose logging for troubleshooting"
 								checked={config().debug}
+								disabled={isLocked("debug")}
 								onChange={(checked) => handleConfigChange("debug", checked)}
 							/>
 
@@ -3919,6 +4065,126 @@ export function SettingsPage() {
 						</div>
 					</div>
 
+					{/* CLIProxyAPI Versions */}
+					<div
+						class="space-y-4"
+						classList={{ hidden: activeTab() !== "advanced" }}
+					>
+						<h2 class="text-sm font-semibold text-gray-600 dark:text-gray-400 uppercase tracking-wider">
+							CLIProxyAPI Versions
+						</h2>
+
+						<div class="space-y-4 p-4 rounded-xl bg-gray-50 dark:bg-gray-800/50 border border-gray-200 dark:border-gray-700">
+							<p class="text-xs text-gray-500 dark:text-gray-400">
+								Run another CLIProxyAPI release than the one bundled with
+								ProxyPal. The choice applies from the next proxy start; a
+								version that doesn't start is switched back from
+								automatically.
+							</p>
+
+							<div class="space-y-2">
+								<label class="flex items-center gap-3 text-sm text-gray-700 dark:text-gray-300">
+									<input
+										type="radio"
+										name="sidecar-version"
+										checked={!sidecars()?.selected}
+										onChange={() => handleSelectSidecar(null)}
+									/>
+									<span class="flex-1">Bundled</span>
+								</label>
+								<For each={sidecars()?.installed ?? []}>
+									{(sidecar) => (
+										<div class="flex items-center gap-3 text-sm text-gray-700 dark:text-gray-300">
+											<label class="flex flex-1 min-w-0 items-center gap-3">
+												<input
+													type="radio"
+													name="sidecar-version"
+													checked={sidecars()?.selected === sidecar.version}
+													onChange={() => handleSelectSidecar(sidecar.version)}
+												/>
+												<span class="flex-1 min-w-0">
+													<span class="font-medium">{sidecar.version}</span>
+													<Show when={sidecars()?.previous === sidecar.version}>
+														<span class="ml-2 text-xs text-gray-500 dark:text-gray-400">
+															(previous)
+														</span>
+													</Show>
+													<span
+														class="block text-xs text-gray-500 dark:text-gray-400 truncate"
+														title={`${sidecar.source}\nSHA-256 ${sidecar.sha256}`}
+													>
+														{sidecar.source}
+													</span>
+												</span>
+											</label>
+											<Button
+												variant="secondary"
+												size="sm"
+												onClick={() => handleRemoveSidecar(sidecar.version)}
+												disabled={sidecars()?.selected === sidecar.version}
+											>
+												Remove
+											</Button>
+										</div>
+									)}
+								</For>
+							</div>
+
+							<div class="border-t border-gray-200 dark:border-gray-700" />
+
+							<div class="space-y-2">
+								<span class="text-sm font-medium text-gray-700 dark:text-gray-300">
+									Install a version
+								</span>
+								<div class="flex gap-2">
+									<input
+										type="text"
+										value={sidecarSource()}
+										onInput={(e) => setSidecarSource(e.currentTarget.value)}
+										placeholder="File path or https:// URL of the binary"
+										class="flex-1 px-3 py-2 bg-white dark:bg-gray-900 border border-gray-300 dark:border-gray-600 rounded-lg text-sm focus:ring-2 focus:ring-brand-500 focus:border-transparent transition-smooth"
+									/>
+									<Button
+										variant="secondary"
+										size="sm"
+										onClick={handlePickSidecarFile}
+									>
+										Browse
+									</Button>
+								</div>
+								<div class="flex gap-2">
+									<input
+										type="text"
+										value={sidecarSha256()}
+										onInput={(e) => setSidecarSha256(e.currentTarget.value)}
+										placeholder="SHA-256 (required for URLs)"
+										class="flex-1 px-3 py-2 bg-white dark:bg-gray-900 border border-gray-300 dark:border-gray-600 rounded-lg text-sm font-mono focus:ring-2 focus:ring-brand-500 focus:border-transparent transition-smooth"
+									/>
+									<input
+										type="text"
+										value={sidecarVersion()}
+										onInput={(e) => setSidecarVersion(e.currentTarget.value)}
+										placeholder="Version, e.g. 6.7.18"
+										class="w-40 px-3 py-2 bg-white dark:bg-gray-900 border border-gray-300 dark:border-gray-600 rounded-lg text-sm focus:ring-2 focus:ring-brand-500 focus:border-transparent transition-smooth"
+									/>
+								</div>
+								<p class="text-xs text-gray-500 dark:text-gray-400">
+									The binary itself, not a release archive. Downloads are checked
+									against the SHA-256 and not installed when it doesn't match. The
+									version can be left empty when the file name carries it.
+								</p>
+								<Button
+									variant="primary"
+									size="sm"
+									onClick={handleInstallSidecar}
+									disabled={!sidecarSource().trim() || installingSidecar()}
+								>
+									{installingSidecar() ? "Installing..." : "Install"}
+								</Button>
+							</div>
+						</div>
+					</div>
+
 					{/* Quota Exceeded Behavior */}
 					<div
 						class="space-y-4"
@@ -11,12 +11,19 @@ import {
 	completeOAuth,
 	getAuthStatus,
 	getConfig,
+	getConfigLoadStatus,
 	getProxyStatus,
 	migrateAmpModelMappings,
 	onAuthStatusChanged,
 	onCloudflareStatusChanged,
+	onConfigRolledBack,
 	onOAuthCallback,
+	onProxyCrashed,
+	onProxyRestarted,
+	onProxySettingsFailed,
+	onPortChanged,
 	onProxyStatusChanged,
+	onSidecarRolledBack,
 	onSshStatusChanged,
 	onTrayToggleProxy,
 	refreshAuthStatus,
@@ -26,6 +33,7 @@ import {
 	stopProxy,
 	syncUsageFromProxy,
 } from "../lib/tauri";
+import { toastStore } from "./toast";
 
 function createAppStore() {
 	// Proxy state
@@ -136,8 +144,22 @@ function createAppStore() {
 			updateProxyStatus(proxyState);
 			setConfig(configState);
 
+			// Surface config.json problems instead of silently running on defaults
+			const loadStatus = await getConfigLoadStatus();
+			if (loadStatus.error) {
+				toastStore.error(
+					"Your config.json could not be loaded",
+					`${loadStatus.error}. Defaults are in use and saving is disabled until it is fixed or discarded.`,
+				);
+			} else if (loadStatus.warnings.length > 0) {
+				toastStore.warning(
+					"Some settings were reset to defaults",
+					loadStatus.warnings.join("\n"),
+				);
+			}
+
 			// Auto-migrate amp model mappings when slot models change across versions
-			if (configState.ampModelMappings?.length) {
+			if (!loadStatus.error && configState.ampModelMappings?.length) {
 				const result = migrateAmpModelMappings(configState.ampModelMappings);
 				if (result.migrated) {
 					const updatedConfig = {
@@ -167,6 +189,70 @@ function createAppStore() {
 				updateProxyStatus(status);
 			});
 
+			const unlistenCrash = await onProxyCrashed((crash) => {
+				const details = crash.stderrTail.slice(-5).join("\n");
+				if (crash.restartInMs !== null) {
+					toastStore.warning(
+						`Proxy ${crash.message}`,
+						`Restarting in ${Math.round(crash.restartInMs / 1000)}s${details ? `\n${details}` : ""}`,
+					);
+				} else if (crash.crashLoop) {
+					toastStore.error(
+						"Proxy keeps crashing",
+						`It exited ${crash.recentExits} times in a row and was left stopped${details ? `\n${details}` : ""}`,
+					);
+					showSystemNotification("ProxyPal", "The proxy keeps crashing and was stopped");
+				} else {
+					toastStore.error(`Proxy ${crash.message}`, details || undefined);
+					showSystemNotification("ProxyPal", `The proxy ${crash.message}`);
+				}
+			});
+
+			const unlistenRestart = await onProxyRestarted((restart) => {
+				updateProxyStatus(restart.status);
+				toastStore.success("Proxy restarted after a crash");
+			});
+
+			const unlistenSettingsFailed = await onProxySettingsFailed((failures) => {
+				toastStore.warning(
+					"Proxy started, but some settings weren't applied",
+					failures.map((f) => `${f.setting}: ${f.error}`).join("\n"),
+				);
+			});
+
+			const unlistenSidecarRollback = await onSidecarRolledBack((rollback) => {
+				const to = rollback.to
+					? `CLIProxyAPI ${rollback.to}`
+					: "the bundled CLIProxyAPI";
+				toastStore.warning(
+					`CLIProxyAPI ${rollback.from} didn't start, switched back to ${to}`,
+					rollback.error,
+				);
+			});
+
+			// apply_config's error carries the message, only the state needs catching up
+			const unlistenConfigRollback = await onConfigRolledBack(async () => {
+				setConfig(await getConfig());
+				updateProxyStatus(await getProxyStatus());
+			});
+
+			const unlistenPortChanged = await onPortChanged(async (change) => {
+				setConfig(await getConfig());
+				const service = change.service === "proxy" ? "Proxy" : "Copilot bridge";
+				const tunnels = change.sshTunnels.length + change.cloudflareTunnels.length;
+				const details = [
+					tunnels > 0 ? `${tunnels} tunnel(s) now forward port ${change.to}` : "",
+					change.reconfiguredAgents.length > 0
+						? `Reconfigured: ${change.reconfiguredAgents.join(", ")}`
+						: "",
+					...change.agentErrors,
+				].filter(Boolean);
+				toastStore.warning(
+					`Port ${change.from} was taken, ${service} moved to ${change.to}`,
+					details.join("\n") || undefined,
+				);
+			});
+
 			const unlistenAuth = await onAuthStatusChanged((status) => {
 				setAuthStatus(status);
 			});
@@ -234,6 +320,12 @@ function createAppStore() {
 			// Cleanup on unmount
 			onCleanup(() => {
 				unlistenProxy();
+				unlistenCrash();
+				unlistenRestart();
+				unlistenSettingsFailed();
+				unlistenPortChanged();
+				unlistenConfigRollback();
+				unlistenSidecarRollback();
 				unlistenAuth();
 				unlistenOAuth();
 				unlistenTray();